@@ -5,16 +5,65 @@
 #include <stdint.h>
 #include <stdlib.h>
 
+/**
+ * Version of the C ABI described by `headers/zk_neural_rust_core.h`.
+ *
+ * Bumped whenever an exported function signature or a `#[repr(C)]` type changes, so hosts
+ * built against another header can detect the mismatch at runtime.
+ */
+#define ZKNEURAL_ABI_VERSION 1
+
+typedef enum FrameRotation {
+  Rotation0,
+  Rotation90,
+  Rotation180,
+  Rotation270,
+} FrameRotation;
+
 typedef enum ImagePreprocessing {
   None,
   FaceRecognition,
 } ImagePreprocessing;
 
+typedef enum PixelFormat {
+  /**
+   * Packed 8-bit RGBA, 4 bytes per pixel.
+   */
+  Rgba,
+  /**
+   * Packed 8-bit BGRA, 4 bytes per pixel (iOS `kCVPixelFormatType_32BGRA`).
+   */
+  Bgra,
+  /**
+   * Packed 8-bit RGB, 3 bytes per pixel.
+   */
+  Rgb,
+  /**
+   * Y plane followed by an interleaved VU plane (Android `ImageFormat.NV21`).
+   */
+  Nv21,
+  /**
+   * Y plane followed by U and V planes of half the stride (I420).
+   */
+  Yuv420,
+} PixelFormat;
+
+typedef enum ZKNeuralLogLevel {
+  Off,
+  Error,
+  Warn,
+  Info,
+  Debug,
+  Trace,
+} ZKNeuralLogLevel;
+
 typedef enum ZKNeuralProvingType {
   Groth,
   UltraGroth,
 } ZKNeuralProvingType;
 
+typedef struct FaceDetector FaceDetector;
+
 typedef struct TensorInvoker TensorInvoker;
 
 typedef struct ZKNeuralCore ZKNeuralCore;
@@ -47,6 +96,79 @@ typedef int32_t (*GenerateProofCallback)(const uint8_t *zkey_buffer,
                                          uint8_t *error_msg,
                                          uintptr_t error_msg_maxsize);
 
+/**
+ * Receives a log message of the library, `target` is the Rust module it comes from.
+ *
+ * It may be called from any thread the library is used on, both strings are only valid
+ * for the duration of the call.
+ */
+typedef void (*LogCallback)(enum ZKNeuralLogLevel level, const char *target, const char *message);
+
+/**
+ * Describes the memory layout of a raw camera frame.
+ *
+ * `stride` is the number of bytes per row of the first plane, `0` means the rows are
+ * tightly packed. `rotation` is the clockwise rotation to apply to get an upright image.
+ */
+typedef struct RawFrameDescriptor {
+  uint32_t width;
+  uint32_t height;
+  uint32_t stride;
+  enum PixelFormat pixel_format;
+  enum FrameRotation rotation;
+} RawFrameDescriptor;
+
+/**
+ * Returns the version of the library.
+ *
+ * # Returns
+ *
+ * Returns a pointer to a static null-terminated string, it must not be freed.
+ */
+const char *rs_zkneural_version(void);
+
+/**
+ * Returns the C ABI version the library was built with.
+ *
+ * # Returns
+ *
+ * Returns the `ZKNEURAL_ABI_VERSION` of the library.
+ */
+uint32_t rs_zkneural_abi_version(void);
+
+/**
+ * Checks that the library implements the C ABI a host was built against.
+ *
+ * Hosts call it with the `ZKNEURAL_ABI_VERSION` of their header, or use the
+ * `RS_ZKNEURAL_CHECK_ABI()` macro which aborts on a mismatch.
+ *
+ * # Arguments
+ * * `header_abi_version` - The `ZKNEURAL_ABI_VERSION` of the header the host was built with.
+ *
+ * # Returns
+ *
+ * Returns `true` if the versions match, `false` otherwise.
+ */
+bool rs_zkneural_check_abi_version(uint32_t header_abi_version);
+
+/**
+ * Registers the callback receiving the library logs, e.g. to route them to os_log or logcat.
+ *
+ * Messages cover stage timings, buffer sizes, model shapes and detection scores, never image
+ * content or embeddings. The callback may be called from any thread the library is used on.
+ *
+ * # Arguments
+ * * `callback` - The log callback, null stops the logging without installing the library
+ *   `tracing` subscriber.
+ * * `max_level` - The most verbose level forwarded to the callback.
+ *
+ * # Returns
+ *
+ * Returns `false` if the host installed its own `tracing` subscriber before a callback was
+ * set, `true` otherwise.
+ */
+bool rs_zkneural_set_log_callback(LogCallback callback, enum ZKNeuralLogLevel max_level);
+
 /**
  * Frees the memory allocated for the ZkNeuralCoreResult.
  *
@@ -143,6 +265,47 @@ struct ZkNeuralCoreResult *rs_zkneural_generate_proof(struct ZKNeuralCore *core,
                                                       const uint8_t *wtns_buffer,
                                                       uintptr_t wtns_len);
 
+/**
+ * Loads the model bundle of a directory into the ZKNeural core.
+ *
+ * Reads `manifest.json`, the model and the circuit, checks their checksums and the model
+ * input against the bundle preprocessing spec, and sets the proving type of the bundle. The
+ * zkey is read and checked when a proof is generated. The result value is the JSON-encoded
+ * manifest, e.g. to read the bundle threshold.
+ *
+ * # Arguments
+ *
+ * * `core` - A pointer to the `ZKNeuralCore` instance.
+ * * `directory` - A null-terminated path to the bundle directory.
+ *
+ * # Returns
+ *
+ * Returns a pointer to a `ZkNeuralCoreResult` containing the manifest.
+ */
+struct ZkNeuralCoreResult *rs_zkneural_load_bundle(struct ZKNeuralCore *core,
+                                                   const char *directory);
+
+/**
+ * Generates the witness and the proof of the JSON inputs with the circuit and the zkey of the
+ * loaded bundle.
+ *
+ * Fails if the proving type was changed after loading the bundle, the `threshold` input
+ * differs from the bundle threshold or the `ultra_groth` input from its proving type.
+ *
+ * # Arguments
+ *
+ * * `core` - A pointer to the `ZKNeuralCore` instance.
+ * * `json_buffer` - A pointer to the buffer containing the JSON data.
+ * * `json_len` - The length of the JSON buffer in bytes.
+ *
+ * # Returns
+ *
+ * Returns a pointer to a `ZkNeuralCoreResult` containing the JSON-encoded proof.
+ */
+struct ZkNeuralCoreResult *rs_zkneural_prove_with_bundle(struct ZKNeuralCore *core,
+                                                         const uint8_t *json_buffer,
+                                                         uintptr_t json_len);
+
 /**
  * Creates a new `TensorInvoker` instance from the provided model buffer slice.
  *
@@ -162,8 +325,245 @@ struct ZkNeuralCoreResult *rs_zkneural_generate_proof(struct ZKNeuralCore *core,
 struct TensorInvoker *rs_zkneural_tensor_invoker_new(const uint8_t *model_buffer,
                                                      uintptr_t model_len);
 
+/**
+ * Creates a `TensorInvoker` of the model of the loaded bundle.
+ *
+ * The invoker uses the bundle preprocessing spec and signal encoding, and only accepts the
+ * model hash of the bundle manifest.
+ *
+ * # Arguments
+ *
+ * * `core` - A pointer to the `ZKNeuralCore` instance with a loaded bundle.
+ * * `should_process` - Whether the model output is processed.
+ *
+ * # Returns
+ *
+ * Returns a pointer to the `TensorInvoker`, or null if no bundle is loaded or the model could not be loaded.
+ */
+struct TensorInvoker *rs_zkneural_tensor_invoker_new_from_bundle(struct ZKNeuralCore *core,
+                                                                 bool should_process);
+
 void rs_zkneural_tensor_invoker_free(struct TensorInvoker *invoker);
 
+/**
+ * Sets whether the TensorInvoker applies the EXIF orientation of encoded images.
+ *
+ * The orientation is applied by default, so that photos straight from phone cameras
+ * are upright before face detection and inference.
+ *
+ * # Arguments
+ * * `invoker` - A pointer to the `TensorInvoker` instance.
+ * * `apply_exif_orientation` - Whether to apply the EXIF orientation.
+ */
+void rs_zkneural_tensor_invoker_set_apply_exif_orientation(struct TensorInvoker *invoker,
+                                                           bool apply_exif_orientation);
+
+/**
+ * Sets the preprocessing spec used to turn images into the model input tensor.
+ *
+ * # Arguments
+ * * `invoker` - A pointer to the `TensorInvoker` instance.
+ * * `spec_buffer` - A pointer to a JSON-encoded `PreprocessingSpec`, may be null.
+ * * `spec_len` - The length of the spec buffer in bytes, `0` restores the default spec.
+ *
+ * # Returns
+ *
+ * Returns `false` if the spec could not be parsed or is invalid, `true` otherwise.
+ */
+bool rs_zkneural_tensor_invoker_set_preprocessing_spec(struct TensorInvoker *invoker,
+                                                       const uint8_t *spec_buffer,
+                                                       uintptr_t spec_len);
+
+/**
+ * Sets the fixed-point encoding used to turn image pixels and features into circuit signals.
+ *
+ * # Arguments
+ * * `invoker` - A pointer to the `TensorInvoker` instance.
+ * * `encoding_buffer` - A pointer to a JSON-encoded `SignalEncoding`, may be null.
+ * * `encoding_len` - The length of the encoding buffer in bytes, `0` restores the default encoding.
+ *
+ * # Returns
+ *
+ * Returns `false` if the encoding could not be parsed or is invalid, `true` otherwise.
+ */
+bool rs_zkneural_tensor_invoker_set_signal_encoding(struct TensorInvoker *invoker,
+                                                    const uint8_t *encoding_buffer,
+                                                    uintptr_t encoding_len);
+
+/**
+ * Decodes circuit signals back to the values they encode with the invoker's signal encoding.
+ *
+ * Intended for debugging generated inputs, the result value is a JSON array of numbers.
+ * Features come back multiplied by `feature_scale`, decode them with `decode_features`.
+ *
+ * # Arguments
+ * * `invoker` - A pointer to the `TensorInvoker` instance.
+ * * `signals_buffer` - A pointer to a JSON array of decimal field element strings.
+ * * `signals_len` - The length of the signals buffer in bytes.
+ *
+ * # Returns
+ *
+ * Returns a pointer to a `ZkNeuralCoreResult` containing the decoded values.
+ */
+struct ZkNeuralCoreResult *rs_zkneural_tensor_invoker_decode_signals(struct TensorInvoker *invoker,
+                                                                     const uint8_t *signals_buffer,
+                                                                     uintptr_t signals_len);
+
+/**
+ * Decodes the `features` signals of generated inputs back to the model output, undoing the
+ * `feature_scale` of the invoker's signal encoding that `decode_signals` keeps.
+ *
+ * # Arguments
+ * * `invoker` - A pointer to the `TensorInvoker` instance.
+ * * `signals_buffer` - A pointer to a JSON array of decimal field element strings.
+ * * `signals_len` - The length of the signals buffer in bytes.
+ *
+ * # Returns
+ *
+ * Returns a pointer to a `ZkNeuralCoreResult` containing the decoded features.
+ */
+struct ZkNeuralCoreResult *rs_zkneural_tensor_invoker_decode_features(struct TensorInvoker *invoker,
+                                                                      const uint8_t *signals_buffer,
+                                                                      uintptr_t signals_len);
+
+/**
+ * Sets the face quality thresholds checked before inference with `FaceRecognition` preprocessing.
+ *
+ * Faces that are too small, blurry, badly exposed or turned away are rejected with a
+ * specific error instead of producing an embedding.
+ *
+ * # Arguments
+ * * `invoker` - A pointer to the `TensorInvoker` instance.
+ * * `thresholds_buffer` - A pointer to a JSON-encoded `FaceQualityThresholds`, may be null.
+ * * `thresholds_len` - The length of the thresholds buffer in bytes, `0` disables the quality gate.
+ *
+ * # Returns
+ *
+ * Returns `false` if the thresholds could not be parsed, `true` otherwise.
+ */
+bool rs_zkneural_tensor_invoker_set_face_quality_thresholds(struct TensorInvoker *invoker,
+                                                            const uint8_t *thresholds_buffer,
+                                                            uintptr_t thresholds_len);
+
+/**
+ * Sets the liveness model checked by `rs_zkneural_tensor_invoker_drain_generic_inputs`.
+ *
+ * The model is a user-supplied TFLite anti-spoofing model that runs on the preprocessed
+ * face crop. Inputs scoring below the threshold are rejected.
+ *
+ * # Arguments
+ * * `invoker` - A pointer to the `TensorInvoker` instance.
+ * * `model_buffer` - A pointer to the serialized TFLite liveness model, may be null.
+ * * `model_len` - The length of the model buffer in bytes, `0` disables the liveness stage.
+ * * `threshold` - The minimum liveness score accepted.
+ *
+ * # Returns
+ *
+ * Returns `false` if the model could not be loaded, `true` otherwise.
+ */
+bool rs_zkneural_tensor_invoker_set_liveness_model(struct TensorInvoker *invoker,
+                                                   const uint8_t *model_buffer,
+                                                   uintptr_t model_len,
+                                                   float threshold);
+
+/**
+ * Scores the provided image buffer with the liveness model of the TensorInvoker.
+ *
+ * The result value is the JSON-encoded liveness score, higher is more likely a live face.
+ *
+ * # Arguments
+ * * `invoker` - A pointer to the `TensorInvoker` instance.
+ * * `image_buffer` - A pointer to the image data buffer.
+ * * `image_len` - The length of the image buffer in bytes.
+ * * `image_preprocessing` - The preprocessing type for the image.
+ *
+ * # Returns
+ *
+ * Returns a pointer to a `ZkNeuralCoreResult` containing the liveness score.
+ */
+struct ZkNeuralCoreResult *rs_zkneural_tensor_invoker_liveness_score(struct TensorInvoker *invoker,
+                                                                     const uint8_t *image_buffer,
+                                                                     uintptr_t image_len,
+                                                                     enum ImagePreprocessing image_preprocessing);
+
+/**
+ * Enables or disables the debug artifact dump of the TensorInvoker.
+ *
+ * Every following call records the decoded image, the detector input and scores, the face
+ * box and crop, and the model input and output tensors. The artifacts contain the user's
+ * face, only enable the dump with their consent.
+ *
+ * # Arguments
+ * * `invoker` - A pointer to the `TensorInvoker` instance.
+ * * `options_buffer` - A pointer to a JSON-encoded `DebugDumpOptions`, may be null.
+ * * `options_len` - The length of the options buffer in bytes, `0` disables the dump.
+ *
+ * # Returns
+ *
+ * Returns `false` if the options could not be parsed or the directory could not be created, `true` otherwise.
+ */
+bool rs_zkneural_tensor_invoker_set_debug_dump(struct TensorInvoker *invoker,
+                                               const uint8_t *options_buffer,
+                                               uintptr_t options_len);
+
+/**
+ * Takes the debug artifacts the TensorInvoker kept in memory.
+ *
+ * The result value is a JSON-encoded `DebugBundle` with base64-encoded artifact data, the
+ * artifacts are cleared. It is empty when the dump is disabled or writes to a directory.
+ *
+ * # Arguments
+ * * `invoker` - A pointer to the `TensorInvoker` instance.
+ *
+ * # Returns
+ *
+ * Returns a pointer to a `ZkNeuralCoreResult` containing the bundle.
+ */
+struct ZkNeuralCoreResult *rs_zkneural_tensor_invoker_take_debug_bundle(struct TensorInvoker *invoker);
+
+/**
+ * Returns the hash of the TensorInvoker model.
+ *
+ * The result value is the JSON-encoded lowercase hex SHA-256 of the model bytes, to be
+ * checked against the model hash of the circuit configuration.
+ *
+ * # Arguments
+ * * `invoker` - A pointer to the `TensorInvoker` instance.
+ *
+ * # Returns
+ *
+ * Returns a pointer to a `ZkNeuralCoreResult` containing the model hash.
+ */
+struct ZkNeuralCoreResult *rs_zkneural_tensor_invoker_model_hash(struct TensorInvoker *invoker);
+
+/**
+ * Returns the hash of the ArcFace model the Bionetta circuit is built for.
+ *
+ * # Returns
+ *
+ * Returns a pointer to a `ZkNeuralCoreResult` containing the JSON-encoded hex SHA-256 hash.
+ */
+struct ZkNeuralCoreResult *rs_zkneural_arcface_model_hash(void);
+
+/**
+ * Sets the model hashes the TensorInvoker accepts.
+ *
+ * While set, every invocation fails unless the model hash is on the list. The list is kept
+ * even when the loaded model is not on it.
+ *
+ * # Arguments
+ * * `invoker` - A pointer to the `TensorInvoker` instance.
+ * * `hashes_buffer` - A pointer to a JSON array of hex SHA-256 hashes, may be null.
+ * * `hashes_len` - The length of the hashes buffer in bytes, `0` accepts any model.
+ *
+ * # Returns
+ *
+ * Returns `false` if the hashes could not be parsed or the loaded model is not allowed, `true` otherwise.
+ */
+bool rs_zkneural_tensor_invoker_set_allowed_model_hashes(struct TensorInvoker *invoker,
+                                                         const uint8_t *hashes_buffer,
+                                                         uintptr_t hashes_len);
+
 /**
  * Invokes the TensorInvoker with the provided image buffer.
  *
@@ -183,13 +583,433 @@ struct ZkNeuralCoreResult *rs_zkneural_tensor_invoker_image_fire(struct TensorIn
                                                                  uintptr_t image_len,
                                                                  enum ImagePreprocessing image_preprocessing);
 
+/**
+ * Invokes the TensorInvoker with the provided raw camera frame.
+ *
+ * # Arguments
+ * * `invoker` - A pointer to the `TensorInvoker` instance.
+ * * `frame_buffer` - A pointer to the raw pixel buffer.
+ * * `frame_len` - The length of the frame buffer in bytes.
+ * * `frame_descriptor` - The size, stride, pixel format and rotation of the frame.
+ * * `image_preprocessing` - The preprocessing type for the image.
+ *
+ * # Returns
+ *
+ * Returns a pointer to a `ZkNeuralCoreResult` containing the result of the invocation.
+ */
+struct ZkNeuralCoreResult *rs_zkneural_tensor_invoker_frame_fire(struct TensorInvoker *invoker,
+                                                                 const uint8_t *frame_buffer,
+                                                                 uintptr_t frame_len,
+                                                                 struct RawFrameDescriptor frame_descriptor,
+                                                                 enum ImagePreprocessing image_preprocessing);
+
+/**
+ * Invokes the TensorInvoker with several image buffers at once.
+ *
+ * The model is loaded once for the whole batch. The result value is a JSON array with one
+ * `{"output": ..., "error": ...}` entry per image, in input order, so a single unusable image
+ * does not fail the whole batch.
+ *
+ * # Arguments
+ * * `invoker` - A pointer to the `TensorInvoker` instance.
+ * * `image_buffers` - A pointer to an array of image data buffer pointers.
+ * * `image_lens` - A pointer to an array with the length of each image buffer in bytes.
+ * * `images_count` - The number of images in the batch, both arrays may be null when it is `0`.
+ * * `image_preprocessing` - The preprocessing type for the images.
+ *
+ * # Returns
+ *
+ * Returns a pointer to a `ZkNeuralCoreResult` containing the results of the batch.
+ */
+struct ZkNeuralCoreResult *rs_zkneural_tensor_invoker_batch_fire(struct TensorInvoker *invoker,
+                                                                 const uint8_t *const *image_buffers,
+                                                                 const uintptr_t *image_lens,
+                                                                 uintptr_t images_count,
+                                                                 enum ImagePreprocessing image_preprocessing);
+
+/**
+ * Drains generic inputs from the TensorInvoker.
+ *
+ * # Arguments
+ * * `invoker` - A pointer to the `TensorInvoker` instance.
+ * * `image_buffer` - A pointer to the image data buffer.
+ * * `image_len` - The length of the image buffer in bytes.
+ * * `image_preprocessing` - The preprocessing type for the image.
+ * * `address` - A pointer to a C-style string containing the address.
+ * * `threshold` - A pointer to a C-style string containing the threshold.
+ * * `nonce` - A pointer to a C-style string containing the nonce.
+ *
+ * `ultra_groth` is drained for `ZKNeuralProvingType::Groth` and `rand` is drawn from the OS
+ * CSPRNG, use `rs_zkneural_tensor_invoker_drain_generic_inputs_with_core` to choose them.
+ *
+ * # Returns
+ *
+ * Returns a pointer to a `ZkNeuralCoreResult` containing the result of the operation.
+ */
 struct ZkNeuralCoreResult *rs_zkneural_tensor_invoker_drain_generic_inputs(struct TensorInvoker *invoker,
                                                                            const uint8_t *image_buffer,
                                                                            uintptr_t image_len,
                                                                            enum ImagePreprocessing image_preprocessing,
-                                                                           char *address,
-                                                                           char *threshold,
-                                                                           char *nonce);
+                                                                           const char *address,
+                                                                           const char *threshold,
+                                                                           const char *nonce);
+
+/**
+ * Drains generic inputs from the TensorInvoker for the proving type of a `ZKNeuralCore`.
+ *
+ * # Arguments
+ * * `invoker` - A pointer to the `TensorInvoker` instance.
+ * * `image_buffer` - A pointer to the image data buffer.
+ * * `image_len` - The length of the image buffer in bytes.
+ * * `image_preprocessing` - The preprocessing type for the image.
+ * * `address` - A pointer to a C-style string containing the address.
+ * * `threshold` - A pointer to a C-style string containing the threshold.
+ * * `nonce` - A pointer to a C-style string containing the nonce.
+ * * `core` - A pointer to the `ZKNeuralCore` instance whose proving type selects `ultra_groth`.
+ * * `rand` - A pointer to a C-style string containing the blinding value, may be null to draw
+ *   a fresh one from the OS CSPRNG. The value used is returned in the drained inputs.
+ *
+ * # Returns
+ *
+ * Returns a pointer to a `ZkNeuralCoreResult` containing the result of the operation, an error
+ * result if `core` is null.
+ */
+struct ZkNeuralCoreResult *rs_zkneural_tensor_invoker_drain_generic_inputs_with_core(struct TensorInvoker *invoker,
+                                                                                     const uint8_t *image_buffer,
+                                                                                     uintptr_t image_len,
+                                                                                     enum ImagePreprocessing image_preprocessing,
+                                                                                     const char *address,
+                                                                                     const char *threshold,
+                                                                                     const char *nonce,
+                                                                                     struct ZKNeuralCore *core,
+                                                                                     const char *rand);
+
+/**
+ * Drains generic inputs from the TensorInvoker using a raw camera frame.
+ *
+ * # Arguments
+ * * `invoker` - A pointer to the `TensorInvoker` instance.
+ * * `frame_buffer` - A pointer to the raw pixel buffer.
+ * * `frame_len` - The length of the frame buffer in bytes.
+ * * `frame_descriptor` - The size, stride, pixel format and rotation of the frame.
+ * * `image_preprocessing` - The preprocessing type for the image.
+ * * `address` - A pointer to a C-style string containing the address.
+ * * `threshold` - A pointer to a C-style string containing the threshold.
+ * * `nonce` - A pointer to a C-style string containing the nonce.
+ *
+ * `ultra_groth` is drained for `ZKNeuralProvingType::Groth` and `rand` is drawn from the OS
+ * CSPRNG, use `rs_zkneural_tensor_invoker_drain_generic_inputs_from_frame_with_core` to
+ * choose them.
+ *
+ * # Returns
+ *
+ * Returns a pointer to a `ZkNeuralCoreResult` containing the result of the operation.
+ */
+struct ZkNeuralCoreResult *rs_zkneural_tensor_invoker_drain_generic_inputs_from_frame(struct TensorInvoker *invoker,
+                                                                                      const uint8_t *frame_buffer,
+                                                                                      uintptr_t frame_len,
+                                                                                      struct RawFrameDescriptor frame_descriptor,
+                                                                                      enum ImagePreprocessing image_preprocessing,
+                                                                                      const char *address,
+                                                                                      const char *threshold,
+                                                                                      const char *nonce);
+
+/**
+ * Drains generic inputs from the TensorInvoker using a raw camera frame, for the proving type
+ * of a `ZKNeuralCore`.
+ *
+ * # Arguments
+ * * `invoker` - A pointer to the `TensorInvoker` instance.
+ * * `frame_buffer` - A pointer to the raw pixel buffer.
+ * * `frame_len` - The length of the frame buffer in bytes.
+ * * `frame_descriptor` - The size, stride, pixel format and rotation of the frame.
+ * * `image_preprocessing` - The preprocessing type for the image.
+ * * `address` - A pointer to a C-style string containing the address.
+ * * `threshold` - A pointer to a C-style string containing the threshold.
+ * * `nonce` - A pointer to a C-style string containing the nonce.
+ * * `core` - A pointer to the `ZKNeuralCore` instance whose proving type selects `ultra_groth`.
+ * * `rand` - A pointer to a C-style string containing the blinding value, may be null to draw
+ *   a fresh one from the OS CSPRNG. The value used is returned in the drained inputs.
+ *
+ * # Returns
+ *
+ * Returns a pointer to a `ZkNeuralCoreResult` containing the result of the operation, an error
+ * result if `core` is null.
+ */
+struct ZkNeuralCoreResult *rs_zkneural_tensor_invoker_drain_generic_inputs_from_frame_with_core(struct TensorInvoker *invoker,
+                                                                                                const uint8_t *frame_buffer,
+                                                                                                uintptr_t frame_len,
+                                                                                                struct RawFrameDescriptor frame_descriptor,
+                                                                                                enum ImagePreprocessing image_preprocessing,
+                                                                                                const char *address,
+                                                                                                const char *threshold,
+                                                                                                const char *nonce,
+                                                                                                struct ZKNeuralCore *core,
+                                                                                                const char *rand);
+
+/**
+ * Compares the best faces of two image buffers with the model of the TensorInvoker.
+ *
+ * The result value is a JSON-encoded `FaceMatch`. The match is a local estimate from the
+ * fixed-point squared distance of `circuit_reference`, it is not verified against the circuit
+ * and does not guarantee that the proof verifies.
+ *
+ * # Arguments
+ * * `invoker` - A pointer to the `TensorInvoker` instance.
+ * * `image_a_buffer` - A pointer to the first image data buffer.
+ * * `image_a_len` - The length of the first image buffer in bytes.
+ * * `image_b_buffer` - A pointer to the second image data buffer.
+ * * `image_b_len` - The length of the second image buffer in bytes.
+ * * `threshold` - A pointer to a C-style string containing the decimal threshold.
+ *
+ * # Returns
+ *
+ * Returns a pointer to a `ZkNeuralCoreResult` containing the result of the comparison.
+ */
+struct ZkNeuralCoreResult *rs_zkneural_tensor_invoker_match_faces(struct TensorInvoker *invoker,
+                                                                  const uint8_t *image_a_buffer,
+                                                                  uintptr_t image_a_len,
+                                                                  const uint8_t *image_b_buffer,
+                                                                  uintptr_t image_b_len,
+                                                                  const char *threshold);
+
+/**
+ * Compares two embeddings, e.g. a fresh one against an enrolled template.
+ *
+ * The result value is a JSON-encoded `FaceMatch` computed with the signal encoding of the
+ * TensorInvoker.
+ *
+ * # Arguments
+ * * `invoker` - A pointer to the `TensorInvoker` instance.
+ * * `embedding_a_buffer` - A pointer to the first embedding as a JSON array of numbers.
+ * * `embedding_a_len` - The length of the first embedding buffer in bytes.
+ * * `embedding_b_buffer` - A pointer to the second embedding as a JSON array of numbers.
+ * * `embedding_b_len` - The length of the second embedding buffer in bytes.
+ * * `threshold` - A pointer to a C-style string containing the decimal threshold.
+ *
+ * # Returns
+ *
+ * Returns a pointer to a `ZkNeuralCoreResult` containing the result of the comparison.
+ */
+struct ZkNeuralCoreResult *rs_zkneural_tensor_invoker_compare_embeddings(struct TensorInvoker *invoker,
+                                                                         const uint8_t *embedding_a_buffer,
+                                                                         uintptr_t embedding_a_len,
+                                                                         const uint8_t *embedding_b_buffer,
+                                                                         uintptr_t embedding_b_len,
+                                                                         const char *threshold);
+
+/**
+ * Evaluates the local model of the threshold comparison on drained inputs.
+ *
+ * The result value is a JSON-encoded `CircuitReferenceResult` with the integer score and
+ * whether it passes the threshold of the inputs. The model is not verified against the
+ * circuit, a passing result does not guarantee that the proof verifies.
+ *
+ * # Arguments
+ * * `invoker` - A pointer to the `TensorInvoker` instance.
+ * * `inputs_buffer` - A pointer to JSON-encoded `BionettaGenericInputs`.
+ * * `inputs_len` - The length of the inputs buffer in bytes.
+ * * `template_buffer` - A pointer to the reference template as a JSON array of field elements.
+ * * `template_len` - The length of the template buffer in bytes.
+ *
+ * # Returns
+ *
+ * Returns a pointer to a `ZkNeuralCoreResult` containing the result of the evaluation.
+ */
+struct ZkNeuralCoreResult *rs_zkneural_tensor_invoker_evaluate_circuit_reference(struct TensorInvoker *invoker,
+                                                                                 const uint8_t *inputs_buffer,
+                                                                                 uintptr_t inputs_len,
+                                                                                 const uint8_t *template_buffer,
+                                                                                 uintptr_t template_len);
+
+/**
+ * Registers a circuit input schema on the TensorInvoker, replacing one with the same name.
+ *
+ * The built-in `bionetta_generic` schema cannot be replaced.
+ *
+ * # Arguments
+ * * `invoker` - A pointer to the `TensorInvoker` instance.
+ * * `name` - A pointer to a C-style string containing the schema name.
+ * * `schema_buffer` - A pointer to a JSON-encoded `InputSchema`.
+ * * `schema_len` - The length of the schema buffer in bytes.
+ *
+ * # Returns
+ *
+ * Returns `false` if the schema could not be parsed or is invalid, `true` otherwise.
+ */
+bool rs_zkneural_tensor_invoker_register_input_schema(struct TensorInvoker *invoker,
+                                                      const char *name,
+                                                      const uint8_t *schema_buffer,
+                                                      uintptr_t schema_len);
+
+/**
+ * Drains the inputs of a registered circuit input schema from the TensorInvoker.
+ *
+ * # Arguments
+ * * `invoker` - A pointer to the `TensorInvoker` instance.
+ * * `image_buffer` - A pointer to the image data buffer.
+ * * `image_len` - The length of the image buffer in bytes.
+ * * `image_preprocessing` - The preprocessing type for the image.
+ * * `schema_name` - A pointer to a C-style string containing the schema name.
+ * * `values_buffer` - A pointer to a JSON object with the caller signals by name, may be null.
+ * * `values_len` - The length of the values buffer in bytes.
+ * * `core` - A pointer to the `ZKNeuralCore` instance, may be null if the schema has no
+ *   `ultra_groth` signal.
+ * * `rand` - A pointer to a C-style string containing the blinding value, may be null to draw
+ *   a fresh one from the OS CSPRNG.
+ *
+ * # Returns
+ *
+ * Returns a pointer to a `ZkNeuralCoreResult` containing the result of the operation.
+ */
+struct ZkNeuralCoreResult *rs_zkneural_tensor_invoker_drain_inputs(struct TensorInvoker *invoker,
+                                                                   const uint8_t *image_buffer,
+                                                                   uintptr_t image_len,
+                                                                   enum ImagePreprocessing image_preprocessing,
+                                                                   const char *schema_name,
+                                                                   const uint8_t *values_buffer,
+                                                                   uintptr_t values_len,
+                                                                   struct ZKNeuralCore *core,
+                                                                   const char *rand);
+
+/**
+ * Creates a new `FaceDetector` instance.
+ *
+ * The detector is meant to be long-lived and reused across calls, e.g. for every camera frame.
+ *
+ * # Arguments
+ *
+ * * `options_buffer` - A pointer to a JSON-encoded `FaceDetectionOptions`, may be null.
+ * * `options_len` - The length of the options buffer in bytes, `0` selects the default options.
+ *
+ * # Returns
+ *
+ * Returns a pointer to a newly allocated `FaceDetector` instance, or a null pointer if the options are invalid.
+ */
+struct FaceDetector *rs_zkneural_face_detector_new(const uint8_t *options_buffer,
+                                                   uintptr_t options_len);
+
+/**
+ * Frees the memory allocated for the FaceDetector instance.
+ *
+ * # Arguments
+ * * `detector` - A pointer to the `FaceDetector` instance to free.
+ */
+void rs_zkneural_face_detector_free(struct FaceDetector *detector);
+
+/**
+ * Enables or disables the debug artifact dump of the FaceDetector.
+ *
+ * Every following detection records the decoded image, the detector input and scores, and
+ * the chosen face box. The artifacts contain the user's face, only enable the dump with
+ * their consent.
+ *
+ * # Arguments
+ * * `detector` - A pointer to the `FaceDetector` instance.
+ * * `options_buffer` - A pointer to a JSON-encoded `DebugDumpOptions`, may be null.
+ * * `options_len` - The length of the options buffer in bytes, `0` disables the dump.
+ *
+ * # Returns
+ *
+ * Returns `false` if the options could not be parsed or the directory could not be created, `true` otherwise.
+ */
+bool rs_zkneural_face_detector_set_debug_dump(struct FaceDetector *detector,
+                                              const uint8_t *options_buffer,
+                                              uintptr_t options_len);
+
+/**
+ * Takes the debug artifacts the FaceDetector kept in memory.
+ *
+ * The result value is the same JSON-encoded `DebugBundle` as for
+ * `rs_zkneural_tensor_invoker_take_debug_bundle`.
+ *
+ * # Arguments
+ * * `detector` - A pointer to the `FaceDetector` instance.
+ *
+ * # Returns
+ *
+ * Returns a pointer to a `ZkNeuralCoreResult` containing the bundle.
+ */
+struct ZkNeuralCoreResult *rs_zkneural_face_detector_take_debug_bundle(struct FaceDetector *detector);
+
+/**
+ * Returns the hash of the BlazeFace model built into the FaceDetector.
+ *
+ * # Arguments
+ * * `detector` - A pointer to the `FaceDetector` instance.
+ *
+ * # Returns
+ *
+ * Returns a pointer to a `ZkNeuralCoreResult` containing the JSON-encoded hex SHA-256 hash.
+ */
+struct ZkNeuralCoreResult *rs_zkneural_face_detector_model_hash(struct FaceDetector *detector);
+
+/**
+ * Detects faces in the provided image buffer.
+ *
+ * The result value is a JSON-encoded `FaceDetectionResult` with normalized and pixel-space
+ * bounding boxes, scores and keypoints of every detected face, sorted by score.
+ *
+ * # Arguments
+ * * `detector` - A pointer to the `FaceDetector` instance.
+ * * `image_buffer` - A pointer to the image data buffer.
+ * * `image_len` - The length of the image buffer in bytes.
+ * * `options_buffer` - A pointer to a JSON-encoded `FaceDetectionOptions` overriding the detector options, may be null.
+ * * `options_len` - The length of the options buffer in bytes, `0` keeps the detector options.
+ *
+ * # Returns
+ *
+ * Returns a pointer to a `ZkNeuralCoreResult` containing the result of the detection.
+ */
+struct ZkNeuralCoreResult *rs_zkneural_detect_faces(struct FaceDetector *detector,
+                                                    const uint8_t *image_buffer,
+                                                    uintptr_t image_len,
+                                                    const uint8_t *options_buffer,
+                                                    uintptr_t options_len);
+
+/**
+ * Detects faces in the provided raw camera frame.
+ *
+ * The result value is the same JSON-encoded `FaceDetectionResult` as for `rs_zkneural_detect_faces`,
+ * with pixel coordinates relative to the rotated frame.
+ *
+ * # Arguments
+ * * `detector` - A pointer to the `FaceDetector` instance.
+ * * `frame_buffer` - A pointer to the raw pixel buffer.
+ * * `frame_len` - The length of the frame buffer in bytes.
+ * * `frame_descriptor` - The size, stride, pixel format and rotation of the frame.
+ * * `options_buffer` - A pointer to a JSON-encoded `FaceDetectionOptions` overriding the detector options, may be null.
+ * * `options_len` - The length of the options buffer in bytes, `0` keeps the detector options.
+ *
+ * # Returns
+ *
+ * Returns a pointer to a `ZkNeuralCoreResult` containing the result of the detection.
+ */
+struct ZkNeuralCoreResult *rs_zkneural_detect_faces_in_frame(struct FaceDetector *detector,
+                                                             const uint8_t *frame_buffer,
+                                                             uintptr_t frame_len,
+                                                             struct RawFrameDescriptor frame_descriptor,
+                                                             const uint8_t *options_buffer,
+                                                             uintptr_t options_len);
+
+/**
+ * Assesses the quality of the highest scoring face in the provided image buffer.
+ *
+ * The result value is a JSON-encoded `FaceQuality` with the face size, sharpness,
+ * brightness, yaw and roll, so apps can guide users before running inference.
+ *
+ * # Arguments
+ * * `detector` - A pointer to the `FaceDetector` instance.
+ * * `image_buffer` - A pointer to the image data buffer.
+ * * `image_len` - The length of the image buffer in bytes.
+ *
+ * # Returns
+ *
+ * Returns a pointer to a `ZkNeuralCoreResult` containing the result of the assessment.
+ */
+struct ZkNeuralCoreResult *rs_zkneural_assess_face_quality(struct FaceDetector *detector,
+                                                           const uint8_t *image_buffer,
+                                                           uintptr_t image_len);
 
 /**
  * Allocates a buffer of the specified length.
@@ -211,3 +1031,14 @@ uint8_t *rs_zkneural_alloc(uintptr_t len);
  * * `len` - The length of the buffer in bytes.
  */
 void rs_zkneural_dealloc(uint8_t *ptr, uintptr_t len);
+
+#include <stdio.h>
+
+#define RS_ZKNEURAL_CHECK_ABI() \
+  do { \
+    if (!rs_zkneural_check_abi_version(ZKNEURAL_ABI_VERSION)) { \
+      fprintf(stderr, "zk-neural-rust-core ABI mismatch: header %u, library %u\n", \
+              (unsigned)ZKNEURAL_ABI_VERSION, (unsigned)rs_zkneural_abi_version()); \
+      abort(); \
+    } \
+  } while (0)
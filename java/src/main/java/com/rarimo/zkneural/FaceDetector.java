@@ -0,0 +1,38 @@
+package com.rarimo.zkneural;
+
+/** BlazeFace face detection, meant to be reused across camera frames. */
+public class FaceDetector implements AutoCloseable {
+    static {
+        NativeLibrary.load();
+    }
+
+    private long handle;
+
+    /** Creates a detector with the default options. */
+    public FaceDetector() throws ZKNeuralException {
+        this(null);
+    }
+
+    /** Creates a detector from JSON-encoded {@code FaceDetectionOptions}. */
+    public FaceDetector(byte[] optionsJson) throws ZKNeuralException {
+        handle = nativeNew(optionsJson);
+    }
+
+    /** Returns the JSON-encoded {@code FaceDetectionResult}. */
+    public byte[] detectFaces(byte[] image) throws ZKNeuralException {
+        return nativeDetectFaces(handle, image);
+    }
+
+    @Override
+    public void close() {
+        nativeFree(handle);
+        handle = 0;
+    }
+
+    private static native long nativeNew(byte[] options) throws ZKNeuralException;
+
+    private static native void nativeFree(long detector);
+
+    private static native byte[] nativeDetectFaces(long detector, byte[] image)
+            throws ZKNeuralException;
+}
@@ -0,0 +1,7 @@
+package com.rarimo.zkneural;
+
+/** Mirrors {@code ImagePreprocessing}, the ordinal is passed to the native library. */
+public enum ImagePreprocessing {
+    NONE,
+    FACE_RECOGNITION,
+}
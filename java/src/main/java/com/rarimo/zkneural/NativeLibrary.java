@@ -0,0 +1,14 @@
+package com.rarimo.zkneural;
+
+final class NativeLibrary {
+    private static boolean loaded = false;
+
+    private NativeLibrary() {}
+
+    static synchronized void load() {
+        if (!loaded) {
+            System.loadLibrary("zk_neural_rust_core");
+            loaded = true;
+        }
+    }
+}
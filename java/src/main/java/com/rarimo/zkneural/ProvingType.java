@@ -0,0 +1,7 @@
+package com.rarimo.zkneural;
+
+/** Mirrors {@code ZKNeuralProvingType}, the ordinal is passed to the native library. */
+public enum ProvingType {
+    GROTH,
+    ULTRA_GROTH,
+}
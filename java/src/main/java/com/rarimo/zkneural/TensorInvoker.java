@@ -0,0 +1,78 @@
+package com.rarimo.zkneural;
+
+/** Runs a TFLite model on images and drains circuit inputs from its output. */
+public class TensorInvoker implements AutoCloseable {
+    static {
+        NativeLibrary.load();
+    }
+
+    private long handle;
+
+    public TensorInvoker(byte[] model, boolean shouldProcess) throws ZKNeuralException {
+        handle = nativeNew(model, shouldProcess);
+    }
+
+    /** Returns the model output as a JSON array. */
+    public byte[] fire(byte[] image, ImagePreprocessing preprocessing) throws ZKNeuralException {
+        return nativeFire(handle, image, preprocessing.ordinal());
+    }
+
+    /**
+     * Returns JSON-encoded {@code BionettaGenericInputs}. A {@code null} rand draws a fresh
+     * blinding value, the value used is part of the result.
+     */
+    public byte[] drainGenericInputs(
+            ZKNeuralCore core,
+            byte[] image,
+            ImagePreprocessing preprocessing,
+            String address,
+            String threshold,
+            String nonce,
+            String rand)
+            throws ZKNeuralException {
+        return nativeDrainGenericInputs(
+                handle,
+                core.handle(),
+                image,
+                preprocessing.ordinal(),
+                address,
+                threshold,
+                nonce,
+                rand);
+    }
+
+    /** Returns the JSON-encoded {@code FaceMatch} of the best faces of both images. */
+    public byte[] matchFaces(byte[] imageA, byte[] imageB, String threshold)
+            throws ZKNeuralException {
+        return nativeMatchFaces(handle, imageA, imageB, threshold);
+    }
+
+    @Override
+    public void close() {
+        nativeFree(handle);
+        handle = 0;
+    }
+
+    private static native long nativeNew(byte[] model, boolean shouldProcess)
+            throws ZKNeuralException;
+
+    private static native void nativeFree(long invoker);
+
+    private static native byte[] nativeFire(long invoker, byte[] image, int preprocessing)
+            throws ZKNeuralException;
+
+    private static native byte[] nativeDrainGenericInputs(
+            long invoker,
+            long core,
+            byte[] image,
+            int preprocessing,
+            String address,
+            String threshold,
+            String nonce,
+            String rand)
+            throws ZKNeuralException;
+
+    private static native byte[] nativeMatchFaces(
+            long invoker, byte[] imageA, byte[] imageB, String threshold)
+            throws ZKNeuralException;
+}
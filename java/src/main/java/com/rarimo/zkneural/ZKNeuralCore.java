@@ -0,0 +1,80 @@
+package com.rarimo.zkneural;
+
+/** Witness and proof generation through native callbacks. */
+public class ZKNeuralCore implements AutoCloseable {
+    static {
+        NativeLibrary.load();
+    }
+
+    private long handle;
+
+    public ZKNeuralCore() {
+        handle = nativeNew();
+    }
+
+    long handle() {
+        return handle;
+    }
+
+    public void setProvingType(ProvingType provingType) throws ZKNeuralException {
+        nativeSetProvingType(handle, provingType.ordinal());
+    }
+
+    /**
+     * Sets the witness callback to a native function, e.g. resolved with {@code dlsym} from a
+     * witness calculator library.
+     *
+     * <p>Unsafe: {@code functionAddress} must point to a function with the exact C signature of
+     * {@code GenerateWitnessCallback} in {@code zk_neural_rust_core.h}. Only {@code 0} is
+     * rejected, any other invalid address crashes the process on the next witness generation.
+     */
+    public void setGenerateWitnessCallback(long functionAddress) throws ZKNeuralException {
+        nativeSetGenerateWitnessCallback(handle, functionAddress);
+    }
+
+    /**
+     * Sets the proof callback to a native function, e.g. resolved with {@code dlsym} from a
+     * prover library.
+     *
+     * <p>Unsafe: {@code functionAddress} must point to a function with the exact C signature of
+     * {@code GenerateProofCallback} in {@code zk_neural_rust_core.h}, see {@link
+     * #setGenerateWitnessCallback}.
+     */
+    public void setGenerateProofCallback(long functionAddress) throws ZKNeuralException {
+        nativeSetGenerateProofCallback(handle, functionAddress);
+    }
+
+    public byte[] generateWitness(byte[] circuit, byte[] inputsJson) throws ZKNeuralException {
+        return nativeGenerateWitness(handle, circuit, inputsJson);
+    }
+
+    /** Returns the JSON-encoded proof with its public signals. */
+    public byte[] generateProof(byte[] zkey, byte[] witness) throws ZKNeuralException {
+        return nativeGenerateProof(handle, zkey, witness);
+    }
+
+    @Override
+    public void close() {
+        nativeFree(handle);
+        handle = 0;
+    }
+
+    private static native long nativeNew();
+
+    private static native void nativeFree(long core);
+
+    private static native void nativeSetProvingType(long core, int provingType)
+            throws ZKNeuralException;
+
+    private static native void nativeSetGenerateWitnessCallback(long core, long callback)
+            throws ZKNeuralException;
+
+    private static native void nativeSetGenerateProofCallback(long core, long callback)
+            throws ZKNeuralException;
+
+    private static native byte[] nativeGenerateWitness(long core, byte[] circuit, byte[] json)
+            throws ZKNeuralException;
+
+    private static native byte[] nativeGenerateProof(long core, byte[] zkey, byte[] witness)
+            throws ZKNeuralException;
+}
@@ -0,0 +1,8 @@
+package com.rarimo.zkneural;
+
+/** Thrown by the native library for every {@code ZKNeuralError}, carrying its message. */
+public class ZKNeuralException extends Exception {
+    public ZKNeuralException(String message) {
+        super(message);
+    }
+}
@@ -0,0 +1,92 @@
+package com.rarimo.zkneural;
+
+import java.nio.charset.StandardCharsets;
+import java.nio.file.Files;
+import java.nio.file.Path;
+
+/** Runs the bindings against a host JVM, see {@code scripts/run_jni_test.sh}. */
+public class ZKNeuralJniTest {
+    public static void main(String[] args) throws Exception {
+        testErrorsAreThrown();
+        testFaceDetection();
+        testDrainGenericInputs();
+
+        System.out.println("All JNI tests passed");
+    }
+
+    static void testErrorsAreThrown() throws Exception {
+        try (ZKNeuralCore core = new ZKNeuralCore()) {
+            expectException(
+                    () -> core.generateWitness(new byte[0], new byte[0]),
+                    "Generate witness callback not set");
+            expectException(() -> core.setGenerateProofCallback(0), "Native handle is null");
+
+            core.setProvingType(ProvingType.ULTRA_GROTH);
+        }
+
+        expectException(() -> new TensorInvoker(new byte[0], false), null);
+    }
+
+    static void testFaceDetection() throws Exception {
+        byte[] image = Files.readAllBytes(Path.of("assets/face.jpeg"));
+
+        try (FaceDetector detector = new FaceDetector()) {
+            String result = new String(detector.detectFaces(image), StandardCharsets.UTF_8);
+
+            check(result.contains("\"faces\""), "Unexpected detection result: " + result);
+        }
+    }
+
+    static void testDrainGenericInputs() throws Exception {
+        byte[] model = Files.readAllBytes(Path.of("assets/arcface.tflite"));
+        byte[] image = Files.readAllBytes(Path.of("assets/face.jpeg"));
+
+        try (ZKNeuralCore core = new ZKNeuralCore();
+                TensorInvoker invoker = new TensorInvoker(model, false)) {
+            expectException(
+                    () ->
+                            invoker.drainGenericInputs(
+                                    core, image, ImagePreprocessing.FACE_RECOGNITION, "1", "2", "3", null),
+                    "Proving type not set");
+
+            core.setProvingType(ProvingType.GROTH);
+
+            expectException(
+                    () ->
+                            invoker.drainGenericInputs(
+                                    core, image, ImagePreprocessing.FACE_RECOGNITION, "0x12", "2", "3", null),
+                    "Invalid address");
+
+            String inputs =
+                    new String(
+                            invoker.drainGenericInputs(
+                                    core, image, ImagePreprocessing.FACE_RECOGNITION, "1", "2", "3", "42"),
+                            StandardCharsets.UTF_8);
+
+            check(inputs.contains("\"rand\":\"42\""), "Unexpected inputs: " + inputs);
+        }
+    }
+
+    interface Call {
+        void run() throws Exception;
+    }
+
+    static void expectException(Call call, String messagePrefix) throws Exception {
+        try {
+            call.run();
+        } catch (ZKNeuralException e) {
+            check(
+                    messagePrefix == null || e.getMessage().startsWith(messagePrefix),
+                    "Unexpected message: " + e.getMessage());
+            return;
+        }
+
+        throw new AssertionError("Expected a ZKNeuralException");
+    }
+
+    static void check(boolean condition, String message) {
+        if (!condition) {
+            throw new AssertionError(message);
+        }
+    }
+}
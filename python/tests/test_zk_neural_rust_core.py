@@ -0,0 +1,74 @@
+from pathlib import Path
+
+import numpy as np
+import pytest
+
+import zk_neural_rust_core as zk
+
+ASSETS = Path(__file__).parents[2] / "assets"
+
+
+def read_asset(name):
+    return (ASSETS / name).read_bytes()
+
+
+def test_signal_encoding_round_trip():
+    encoding = zk.SignalEncoding()
+
+    signals = encoding.encode(np.array([0.5, -0.25]))
+
+    assert signals[0] == "16384"
+    assert int(signals[1]) == int(encoding.field_modulus) - 8192
+    assert encoding.fixed_point(np.array([0.5, -0.25])) == [16384, -8192]
+    np.testing.assert_array_equal(encoding.decode(signals), [0.5, -0.25])
+
+
+def test_feature_encoding_round_trip():
+    encoding = zk.SignalEncoding()
+
+    signals = encoding.encode_features(np.array([255.0, -127.5]))
+
+    assert signals[0] == "32768"
+    assert int(signals[1]) == int(encoding.field_modulus) - 16384
+    np.testing.assert_array_equal(encoding.decode_features(signals), [255.0, -127.5])
+
+    unscaled = zk.SignalEncoding(15, "truncate", feature_scale=1.0)
+    assert unscaled.encode_features(np.array([0.5])) == ["16384"]
+
+
+def test_invalid_signal_encoding():
+    with pytest.raises(zk.ZKNeuralException):
+        zk.SignalEncoding(precision_bits=65)
+
+    with pytest.raises(zk.ZKNeuralException):
+        zk.SignalEncoding(negative_handling="reject").encode(np.array([-1.0]))
+
+
+def test_face_detection_from_bytes_and_pixels():
+    detector = zk.FaceDetector()
+
+    result = detector.detect_faces(read_asset("face.jpeg"))
+    assert len(result["faces"]) > 0
+
+    face = detector.crop_face(read_asset("face.jpeg"))
+    assert face.dtype == np.uint8 and face.shape[2] == 3
+
+    pixels = np.ascontiguousarray(face)
+    assert detector.detect_faces(pixels)["image_width"] == face.shape[1]
+
+
+def test_tensor_invoker_matches_drained_inputs():
+    invoker = zk.TensorInvoker(read_asset("arcface.tflite"))
+    image = read_asset("face.jpeg")
+
+    tensor, signals = invoker.prepare_image(image, "face_recognition")
+    assert tensor.size == len(signals)
+
+    inputs = invoker.drain_generic_inputs(image, "1", "2", "3", rand="42")
+    embedding = invoker.embed(image, "face_recognition")
+
+    assert inputs["rand"] == "42"
+    assert inputs["features"] == invoker.signal_encoding.encode_features(embedding)
+
+    with pytest.raises(zk.ZKNeuralException):
+        invoker.drain_generic_inputs(image, "0x12", "2", "3")
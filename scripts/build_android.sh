@@ -0,0 +1,11 @@
+#!/bin/bash
+
+set -e
+
+cargo install cargo-ndk
+
+rustup target add aarch64-linux-android
+rustup target add armv7-linux-androideabi
+rustup target add x86_64-linux-android
+
+cargo ndk -t arm64-v8a -t armeabi-v7a -t x86_64 -o target/jniLibs build --release --features jni
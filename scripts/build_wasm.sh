@@ -0,0 +1,7 @@
+#!/bin/bash
+
+set -e
+
+rustup target add wasm32-unknown-unknown
+
+wasm-pack build --target web --out-dir target/wasm-pkg -- --no-default-features --features wasm
@@ -0,0 +1,16 @@
+#!/bin/bash
+
+set -e
+
+cargo build --release --features uniffi
+
+if [ "$(uname)" == "Darwin" ]; then
+    LIBRARY=target/release/libzk_neural_rust_core.dylib
+else
+    LIBRARY=target/release/libzk_neural_rust_core.so
+fi
+
+for LANGUAGE in swift kotlin; do
+    cargo run --release --features uniffi-cli --bin uniffi-bindgen -- \
+        generate --library "$LIBRARY" --language "$LANGUAGE" --out-dir "target/uniffi/$LANGUAGE"
+done
@@ -0,0 +1,8 @@
+#!/bin/bash
+
+set -e
+
+cargo build --release --features jni
+mkdir -p target/java
+javac -d target/java $(find java/src -name '*.java')
+java -Djava.library.path=target/release -cp target/java com.rarimo.zkneural.ZKNeuralJniTest
@@ -0,0 +1,10 @@
+#!/bin/bash
+
+set -e
+
+python3 -m venv target/venv
+source target/venv/bin/activate
+
+pip install maturin numpy pytest
+maturin develop --release
+pytest python/tests
@@ -5,4 +5,5 @@ set -e
 cargo build --release
 gcc -o test test.c -L./target/release -lzk_neural_rust_core -I.
 export DYLD_LIBRARY_PATH=target/release  # macOS
+export LD_LIBRARY_PATH=target/release  # Linux
 ./test
@@ -0,0 +1,7 @@
+#!/bin/bash
+
+set -e
+
+rustup target add wasm32-unknown-unknown
+
+wasm-pack test --node -- --no-default-features --features wasm
@@ -0,0 +1,3 @@
+fn main() -> std::process::ExitCode {
+    zk_neural_rust_core::cli::run()
+}
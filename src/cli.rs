@@ -0,0 +1,600 @@
+//! `zkneural` command-line tool, running the pipeline on files to debug it without a host app.
+//!
+//! Witness generation, proving and verification are loaded from shared libraries, the first
+//! two exporting the `callbacks` signatures, so any native prover can be plugged in. The
+//! library has no prover of its own, native backends such as a witness calculator or
+//! rapidsnark are used through their shared libraries, directly or behind a thin wrapper
+//! exporting these signatures.
+
+use std::{
+    ffi::{CString, OsString, c_char},
+    fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    sync::Arc,
+};
+
+use clap::{Arg, ArgAction, ArgMatches, Command, value_parser};
+use libloading::Library;
+use serde_json::Value;
+
+use crate::{
+    ZKNeuralCore, ZKNeuralError,
+    core::{
+        ZKNeuralProvingType,
+        callbacks::{GenerateProofCallback, GenerateWitnessCallback},
+        debug_dump::{DebugDump, DebugDumpOptions},
+        face_detection::{FaceDetectionOptions, FaceDetector},
+        tensor::{GenericInputsParams, ImagePreprocessing, TensorInvoker},
+        zk_proof::{GrothZkProof, UltraGrothProof},
+    },
+};
+
+const WITNESS_SYMBOL: &str = "generate_witness_callback";
+const PROOF_SYMBOL: &str = "generate_proof_callback";
+const VERIFY_SYMBOL: &str = "groth16_verify";
+
+const VERIFY_ERROR_MSG_MAXSIZE: usize = 256;
+
+/// Checks proof points against public signals and a verification key, all null-terminated
+/// JSON, with the signature of rapidsnark's `groth16_verify`.
+///
+/// Returns 0 for a valid proof, 1 for an invalid one and any other value on error, with the
+/// error message written to `error_msg`.
+pub type VerifyProofCallback = unsafe extern "C" fn(
+    proof: *const c_char,
+    public_signals: *const c_char,
+    verification_key: *const c_char,
+    error_msg: *mut c_char,
+    error_msg_maxsize: usize,
+) -> i32;
+
+#[derive(thiserror::Error, Debug)]
+pub enum CliError {
+    #[error(transparent)]
+    Core(#[from] ZKNeuralError),
+    #[error("Failed to access `{0}`: {1}")]
+    Io(PathBuf, std::io::Error),
+    #[error("Failed to load `{0}`: {1}")]
+    Library(String, libloading::Error),
+    #[error("Invalid proof: {0}")]
+    InvalidProof(String),
+    #[error("Verification failed: {0}")]
+    VerificationFailed(String),
+    #[error("`--verifier-symbol` is required for UltraGroth proofs")]
+    VerifierSymbolNotSet,
+}
+
+/// Runs the tool with the process arguments, printing results to stdout and errors to stderr.
+pub fn run() -> ExitCode {
+    run_with(std::env::args_os())
+}
+
+pub fn run_with<I, T>(args: I) -> ExitCode
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
+    let matches = command().get_matches_from(args);
+
+    let result = match matches.subcommand() {
+        Some(("detect", matches)) => detect(matches),
+        Some(("embed", matches)) => embed(matches),
+        Some(("inputs", matches)) => inputs(matches),
+        Some(("prove", matches)) => prove(matches),
+        Some(("verify", matches)) => verify(matches),
+        _ => unreachable!("a subcommand is required"),
+    };
+
+    match result {
+        Ok(output) => {
+            println!("{output}");
+
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprintln!("error: {error}");
+
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn command() -> Command {
+    Command::new("zkneural")
+        .version(env!("CARGO_PKG_VERSION"))
+        .about("Runs the zk-neural pipeline on files")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(
+            Command::new("detect")
+                .about("Prints the boxes, keypoints and scores of the faces in an image")
+                .arg(file_arg("image", "Encoded image"))
+                .arg(
+                    Arg::new("min-score")
+                        .long("min-score")
+                        .value_parser(value_parser!(f32))
+                        .help("Minimum score of a detection"),
+                )
+                .arg(
+                    Arg::new("max-faces")
+                        .long("max-faces")
+                        .value_parser(value_parser!(usize))
+                        .help("Maximum number of faces returned"),
+                )
+                .arg(debug_dir_arg()),
+        )
+        .subcommand(
+            Command::new("embed")
+                .about("Runs a TFLite model on an image and prints its output")
+                .arg(file_arg("model", "TFLite model"))
+                .arg(file_arg("image", "Encoded image"))
+                .args(invoker_args()),
+        )
+        .subcommand(
+            Command::new("inputs")
+                .about("Prints the circuit inputs of an image")
+                .arg(file_arg("model", "TFLite model"))
+                .arg(file_arg("image", "Encoded image"))
+                .args(invoker_args())
+                .arg(value_arg(
+                    "address",
+                    "Ethereum address or decimal field element",
+                ))
+                .arg(value_arg("threshold", "Decimal integer of at most 64 bits"))
+                .arg(value_arg("nonce", "Decimal field element"))
+                .arg(
+                    Arg::new("rand")
+                        .long("rand")
+                        .help("Blinding value, a fresh one is drawn when omitted"),
+                )
+                .arg(proving_type_arg()),
+        )
+        .subcommand(
+            Command::new("prove")
+                .about("Generates the witness and the proof of circuit inputs")
+                .arg(file_arg(
+                    "circuit",
+                    "Witness calculator graph of the circuit",
+                ))
+                .arg(file_arg("zkey", "Proving key"))
+                .arg(file_arg(
+                    "inputs",
+                    "Circuit inputs JSON, e.g. from `inputs`",
+                ))
+                .arg(library_arg(
+                    "witness-lib",
+                    "Library exporting the witness callback",
+                ))
+                .arg(symbol_arg("witness-symbol", WITNESS_SYMBOL))
+                .arg(library_arg(
+                    "prover-lib",
+                    "Library exporting the proof callback",
+                ))
+                .arg(symbol_arg("prover-symbol", PROOF_SYMBOL))
+                .arg(proving_type_arg())
+                .arg(
+                    Arg::new("witness-output")
+                        .long("witness-output")
+                        .value_parser(value_parser!(PathBuf))
+                        .help("Also writes the witness to this file"),
+                ),
+        )
+        .subcommand(
+            Command::new("verify")
+                .about("Checks a proof, exiting with a failure when it is invalid")
+                .arg(file_arg("proof", "Proof JSON, e.g. from `prove`"))
+                .arg(file_arg("verification-key", "Verification key JSON"))
+                .arg(library_arg(
+                    "verifier-lib",
+                    "Library exporting the verify function",
+                ))
+                .arg(
+                    Arg::new("verifier-symbol")
+                        .long("verifier-symbol")
+                        .help("Exported symbol name, `groth16_verify` for Groth proofs"),
+                )
+                .arg(proving_type_arg()),
+        )
+}
+
+fn file_arg(name: &'static str, help: &'static str) -> Arg {
+    Arg::new(name)
+        .required(true)
+        .value_parser(value_parser!(PathBuf))
+        .help(help)
+}
+
+fn value_arg(name: &'static str, help: &'static str) -> Arg {
+    Arg::new(name).long(name).required(true).help(help)
+}
+
+fn library_arg(name: &'static str, help: &'static str) -> Arg {
+    value_arg(name, help).value_parser(value_parser!(PathBuf))
+}
+
+fn symbol_arg(name: &'static str, default: &'static str) -> Arg {
+    Arg::new(name)
+        .long(name)
+        .default_value(default)
+        .help("Exported symbol name")
+}
+
+fn proving_type_arg() -> Arg {
+    Arg::new("proving-type")
+        .long("proving-type")
+        .value_parser(["groth", "ultra-groth"])
+        .default_value("groth")
+}
+
+fn debug_dir_arg() -> Arg {
+    Arg::new("debug-dir")
+        .long("debug-dir")
+        .value_parser(value_parser!(PathBuf))
+        .help("Writes the intermediate images and tensors to this directory")
+}
+
+fn invoker_args() -> [Arg; 4] {
+    [
+        debug_dir_arg(),
+        Arg::new("model-hash")
+            .long("model-hash")
+            .action(ArgAction::Append)
+            .help("Fails unless the model has this SHA-256, may be repeated"),
+        Arg::new("no-preprocessing")
+            .long("no-preprocessing")
+            .action(ArgAction::SetTrue)
+            .help("Feeds the whole image instead of the detected face"),
+        Arg::new("no-process")
+            .long("no-process")
+            .action(ArgAction::SetTrue)
+            .help("Keeps the raw model output"),
+    ]
+}
+
+fn detect(matches: &ArgMatches) -> Result<String, CliError> {
+    let image = read(path(matches, "image"))?;
+
+    let mut options = FaceDetectionOptions::default();
+
+    if let Some(min_score) = matches.get_one::<f32>("min-score") {
+        options.min_score = *min_score;
+    }
+
+    if let Some(max_faces) = matches.get_one::<usize>("max-faces") {
+        options.max_faces = *max_faces;
+    }
+
+    let mut detector = FaceDetector::new(options);
+    detector.debug_dump = debug_dump(matches)?;
+
+    let result = detector.detect_faces(&image)?;
+
+    to_json(&result)
+}
+
+fn embed(matches: &ArgMatches) -> Result<String, CliError> {
+    let (invoker, image) = invoker(matches)?;
+
+    let embedding = invoker.embed(&image, image_preprocessing(matches))?;
+
+    to_json(&embedding)
+}
+
+fn inputs(matches: &ArgMatches) -> Result<String, CliError> {
+    let (invoker, image) = invoker(matches)?;
+
+    let params = GenericInputsParams {
+        address: string(matches, "address"),
+        threshold: string(matches, "threshold"),
+        nonce: string(matches, "nonce"),
+        rand: matches.get_one::<String>("rand").cloned(),
+        proving_type: proving_type(matches),
+    };
+
+    let inputs = invoker.drain_generic_inputs(params, &image, image_preprocessing(matches))?;
+
+    Ok(String::from_utf8_lossy(&inputs).into_owned())
+}
+
+fn prove(matches: &ArgMatches) -> Result<String, CliError> {
+    let circuit = read(path(matches, "circuit"))?;
+    let zkey = read(path(matches, "zkey"))?;
+    let inputs = read(path(matches, "inputs"))?;
+
+    let witness_library = load(path(matches, "witness-lib"))?;
+    let prover_library = load(path(matches, "prover-lib"))?;
+
+    let mut core = ZKNeuralCore::new();
+
+    core.set_generate_witness_callback(symbol::<GenerateWitnessCallback>(
+        &witness_library,
+        &string(matches, "witness-symbol"),
+    )?);
+    core.set_generate_proof_callback(symbol::<GenerateProofCallback>(
+        &prover_library,
+        &string(matches, "prover-symbol"),
+    )?);
+    core.set_proving_type(proving_type(matches));
+
+    let witness = core.generate_witness(&circuit, &inputs)?;
+
+    if let Some(witness_output) = matches.get_one::<PathBuf>("witness-output") {
+        fs::write(witness_output, &witness).map_err(|e| CliError::Io(witness_output.clone(), e))?;
+    }
+
+    let proof = core.generate_proof(&zkey, &witness)?;
+
+    Ok(String::from_utf8_lossy(&proof).into_owned())
+}
+
+fn verify(matches: &ArgMatches) -> Result<String, CliError> {
+    let proof = read(path(matches, "proof"))?;
+    let verification_key = read(path(matches, "verification-key"))?;
+
+    let proving_type = proving_type(matches);
+
+    let (proof_points, pub_signals) = split_proof(&proof, proving_type)?;
+
+    let verifier_symbol = match (matches.get_one::<String>("verifier-symbol"), proving_type) {
+        (Some(symbol), _) => symbol.clone(),
+        (None, ZKNeuralProvingType::Groth) => VERIFY_SYMBOL.to_string(),
+        (None, ZKNeuralProvingType::UltraGroth) => return Err(CliError::VerifierSymbolNotSet),
+    };
+
+    let verifier_library = load(path(matches, "verifier-lib"))?;
+    let verify_proof = symbol::<VerifyProofCallback>(&verifier_library, &verifier_symbol)?;
+
+    let proof_points = c_string(proof_points.to_string())?;
+    let pub_signals = c_string(pub_signals.to_string())?;
+    let verification_key = c_string(verification_key)?;
+    let mut error_msg = vec![0u8; VERIFY_ERROR_MSG_MAXSIZE];
+
+    let result = unsafe {
+        verify_proof(
+            proof_points.as_ptr(),
+            pub_signals.as_ptr(),
+            verification_key.as_ptr(),
+            error_msg.as_mut_ptr() as *mut c_char,
+            error_msg.len(),
+        )
+    };
+
+    match result {
+        0 => Ok("valid".to_string()),
+        1 => Err(CliError::InvalidProof(
+            "verifier rejected the proof".to_string(),
+        )),
+        _ => Err(CliError::VerificationFailed(
+            String::from_utf8_lossy(&error_msg)
+                .trim_end_matches('\0')
+                .to_string(),
+        )),
+    }
+}
+
+/// Splits the proof JSON produced by `ZKNeuralCore::generate_proof` back into the proof
+/// points and public signals the prover returned, failing if it is not a proof of
+/// `proving_type`.
+fn split_proof(
+    proof: &[u8],
+    proving_type: ZKNeuralProvingType,
+) -> Result<(Value, Value), CliError> {
+    let layout = match proving_type {
+        ZKNeuralProvingType::Groth => serde_json::from_slice::<GrothZkProof>(proof).map(|_| ()),
+        ZKNeuralProvingType::UltraGroth => {
+            serde_json::from_slice::<UltraGrothProof>(proof).map(|_| ())
+        }
+    };
+
+    layout.map_err(|e| CliError::InvalidProof(format!("not a {proving_type:?} proof: {e}")))?;
+
+    let mut proof = serde_json::from_slice::<Value>(proof).map_err(ZKNeuralError::from)?;
+
+    let mut take = |key: &str| {
+        proof
+            .get_mut(key)
+            .map(Value::take)
+            .ok_or_else(|| CliError::InvalidProof(format!("missing `{key}`")))
+    };
+
+    Ok((take("proof")?, take("pub_signals")?))
+}
+
+fn invoker(matches: &ArgMatches) -> Result<(TensorInvoker, Vec<u8>), CliError> {
+    let model = read(path(matches, "model"))?;
+    let image = read(path(matches, "image"))?;
+
+    let mut invoker = TensorInvoker::new(&model, !matches.get_flag("no-process"))?;
+    invoker.debug_dump = debug_dump(matches)?;
+
+    if let Some(hashes) = matches.get_many::<String>("model-hash") {
+        invoker.set_allowed_model_hashes(Some(hashes.cloned().collect()))?;
+    }
+
+    Ok((invoker, image))
+}
+
+fn debug_dump(matches: &ArgMatches) -> Result<Option<Arc<DebugDump>>, CliError> {
+    let Some(directory) = matches.get_one::<PathBuf>("debug-dir") else {
+        return Ok(None);
+    };
+
+    let debug_dump = DebugDump::new(DebugDumpOptions {
+        directory: Some(directory.clone()),
+        ..Default::default()
+    })?;
+
+    Ok(Some(Arc::new(debug_dump)))
+}
+
+fn image_preprocessing(matches: &ArgMatches) -> ImagePreprocessing {
+    if matches.get_flag("no-preprocessing") {
+        ImagePreprocessing::None
+    } else {
+        ImagePreprocessing::FaceRecognition
+    }
+}
+
+fn proving_type(matches: &ArgMatches) -> ZKNeuralProvingType {
+    match string(matches, "proving-type").as_str() {
+        "ultra-groth" => ZKNeuralProvingType::UltraGroth,
+        _ => ZKNeuralProvingType::Groth,
+    }
+}
+
+fn path<'a>(matches: &'a ArgMatches, name: &str) -> &'a Path {
+    matches
+        .get_one::<PathBuf>(name)
+        .expect("argument is required")
+}
+
+fn string(matches: &ArgMatches, name: &str) -> String {
+    matches
+        .get_one::<String>(name)
+        .cloned()
+        .expect("argument is required or has a default")
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, CliError> {
+    fs::read(path).map_err(|e| CliError::Io(path.to_path_buf(), e))
+}
+
+fn load(path: &Path) -> Result<Library, CliError> {
+    // SAFETY: loading runs the library initializers, the user vouches for the library.
+    unsafe { Library::new(path) }.map_err(|e| CliError::Library(path.display().to_string(), e))
+}
+
+/// Looks up a function of `library`, it stays valid as long as the library is loaded.
+fn symbol<T: Copy>(library: &Library, name: &str) -> Result<T, CliError> {
+    // SAFETY: the caller names a symbol with the signature `T`.
+    unsafe { library.get::<T>(name.as_bytes()) }
+        .map(|symbol| *symbol)
+        .map_err(|e| CliError::Library(name.to_string(), e))
+}
+
+fn c_string(data: impl Into<Vec<u8>>) -> Result<CString, CliError> {
+    CString::new(data).map_err(|_| CliError::InvalidProof("contains a null byte".to_string()))
+}
+
+fn to_json(value: &impl serde::Serialize) -> Result<String, CliError> {
+    Ok(serde_json::to_string_pretty(value).map_err(ZKNeuralError::from)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::Path, process::ExitCode};
+
+    use serde_json::{Value, json};
+
+    use super::{CliError, command, detect, load, proving_type, run_with, split_proof};
+    use crate::core::ZKNeuralProvingType;
+
+    #[test]
+    fn test_command() {
+        command().debug_assert();
+
+        let matches = command()
+            .try_get_matches_from(["zkneural", "detect", "assets/face.jpeg", "--max-faces", "2"])
+            .unwrap();
+        let (name, matches) = matches.subcommand().unwrap();
+
+        assert_eq!(name, "detect");
+        assert_eq!(matches.get_one::<usize>("max-faces"), Some(&2));
+
+        assert!(
+            command()
+                .try_get_matches_from(["zkneural", "inputs", "model", "image"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_split_proof() {
+        let points = json!({
+            "pi_a": ["1"],
+            "pi_b": [["2"]],
+            "pi_c": ["3"],
+            "proof_protocol": "groth16"
+        });
+        let proof = json!({ "proof": points, "pub_signals": ["4"] }).to_string();
+
+        let (proof_points, pub_signals) =
+            split_proof(proof.as_bytes(), ZKNeuralProvingType::Groth).unwrap();
+
+        assert_eq!(proof_points, points);
+        assert_eq!(pub_signals, json!(["4"]));
+
+        assert!(matches!(
+            split_proof(proof.as_bytes(), ZKNeuralProvingType::UltraGroth),
+            Err(CliError::InvalidProof(_))
+        ));
+        assert!(matches!(
+            split_proof(br#"{"proof":{}}"#, ZKNeuralProvingType::Groth),
+            Err(CliError::InvalidProof(_))
+        ));
+
+        let ultra_groth = json!({
+            "proof": { "pi_a": ["1"], "pi_b": [["2"]], "pi_f": ["3"], "pi_r": ["4"] },
+            "pub_signals": ["5"]
+        });
+
+        assert!(
+            split_proof(
+                ultra_groth.to_string().as_bytes(),
+                ZKNeuralProvingType::UltraGroth
+            )
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_verify_ultra_groth_requires_symbol() {
+        let matches = command()
+            .try_get_matches_from([
+                "zkneural",
+                "verify",
+                "proof.json",
+                "verification_key.json",
+                "--verifier-lib",
+                "libverifier.so",
+                "--proving-type",
+                "ultra-groth",
+            ])
+            .unwrap();
+        let (_, matches) = matches.subcommand().unwrap();
+
+        assert!(matches.get_one::<String>("verifier-symbol").is_none());
+        assert_eq!(proving_type(matches), ZKNeuralProvingType::UltraGroth);
+    }
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(
+            run_with(["zkneural", "detect", "assets/face.jpeg"]),
+            ExitCode::SUCCESS
+        );
+
+        let matches = command()
+            .try_get_matches_from(["zkneural", "detect", "assets/face.jpeg"])
+            .unwrap();
+        let (_, matches) = matches.subcommand().unwrap();
+
+        let result: Value = serde_json::from_str(&detect(matches).unwrap()).unwrap();
+
+        assert!(!result["faces"].as_array().unwrap().is_empty());
+
+        assert_eq!(
+            run_with(["zkneural", "detect", "assets/missing.jpeg"]),
+            ExitCode::FAILURE
+        );
+    }
+
+    #[test]
+    fn test_missing_library() {
+        assert!(matches!(
+            load(Path::new("assets/missing.so")),
+            Err(CliError::Library(_, _))
+        ));
+    }
+}
@@ -1,3 +1,7 @@
+use std::ffi::c_char;
+
+use crate::core::logging::ZKNeuralLogLevel;
+
 pub type GenerateWitnessCallback = unsafe extern "C" fn(
     circuit_buffer: *const u8,
     circuit_size: usize,
@@ -21,3 +25,10 @@ pub type GenerateProofCallback = unsafe extern "C" fn(
     error_msg: *mut u8,
     error_msg_maxsize: usize,
 ) -> i32;
+
+/// Receives a log message of the library, `target` is the Rust module it comes from.
+///
+/// It may be called from any thread the library is used on, both strings are only valid
+/// for the duration of the call.
+pub type LogCallback =
+    unsafe extern "C" fn(level: ZKNeuralLogLevel, target: *const c_char, message: *const c_char);
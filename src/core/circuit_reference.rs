@@ -0,0 +1,166 @@
+//! Local model of the fixed-point threshold comparison, to catch obvious mismatches before
+//! witness generation.
+//!
+//! The circuit sources are not part of this repository, so the rules below are assumptions
+//! rather than a verified reproduction: the score is the squared L2 distance of the features
+//! and the template summed modulo the field, it must fit a `COMPARATOR_BITS`-bit `LessThan`
+//! and the check passes when it is strictly below the threshold. A passing result does not
+//! guarantee that the proof verifies.
+
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ZKNeuralError,
+    core::{
+        constants::COMPARATOR_BITS, public_inputs::parse_threshold_value,
+        signal_encoding::FieldModulus, tensor::BionettaGenericInputs,
+    },
+};
+
+/// Outcome of the modelled threshold comparison.
+///
+/// `score` is the squared L2 distance of the features and the template, summed in the field.
+/// The check passes when it is strictly below `threshold`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CircuitReferenceResult {
+    pub score: String,
+    pub threshold: String,
+    pub passed: bool,
+}
+
+/// Evaluates the modelled distance check on field-element features and a reference template
+/// encoded the same way.
+///
+/// Every operation is reduced modulo the field, negative values therefore enter as `p - |x|`
+/// as they are encoded in the witness. A score that does not fit the comparator is reported
+/// as `ScoreOutOfRange`.
+pub fn evaluate(
+    features: &[String],
+    template: &[String],
+    threshold: &str,
+    field_modulus: &FieldModulus,
+) -> Result<CircuitReferenceResult, ZKNeuralError> {
+    if features.is_empty() {
+        return Err(ZKNeuralError::InvalidEmbedding(
+            "features are empty".to_string(),
+        ));
+    }
+
+    if features.len() != template.len() {
+        return Err(ZKNeuralError::EmbeddingSizeMismatch(
+            features.len(),
+            template.len(),
+        ));
+    }
+
+    let threshold = parse_threshold_value(threshold)?;
+
+    let modulus = field_modulus.value();
+
+    let mut score = BigUint::ZERO;
+    for (feature, reference) in features.iter().zip(template) {
+        let feature = field_modulus.parse_element(feature)?;
+        let reference = field_modulus.parse_element(reference)?;
+
+        let difference = (feature + modulus - reference) % modulus;
+
+        score = (score + &difference * &difference) % modulus;
+    }
+
+    if score.bits() > COMPARATOR_BITS {
+        return Err(ZKNeuralError::ScoreOutOfRange(score.to_string()));
+    }
+
+    Ok(CircuitReferenceResult {
+        passed: score < threshold,
+        score: score.to_string(),
+        threshold: threshold.to_string(),
+    })
+}
+
+/// Runs `evaluate` on drained inputs with their own threshold.
+pub fn evaluate_inputs(
+    inputs: &BionettaGenericInputs,
+    template: &[String],
+    field_modulus: &FieldModulus,
+) -> Result<CircuitReferenceResult, ZKNeuralError> {
+    evaluate(&inputs.features, template, &inputs.threshold, field_modulus)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ZKNeuralError,
+        core::{
+            circuit_reference::{evaluate, evaluate_inputs},
+            signal_encoding::{FieldModulus, SignalEncoding},
+            tensor::BionettaGenericInputs,
+        },
+    };
+
+    fn signals(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn test_mixed_sign_score() {
+        let encoding = SignalEncoding::default();
+
+        let features = encoding.encode_all(&[0.5, -0.25, 0.0]).unwrap();
+        let template = encoding.encode_all(&[0.25, 0.25, -0.125]).unwrap();
+
+        // Differences of 8192, -16384 and 4096 in fixed point.
+        let result = evaluate(&features, &template, "356515841", &encoding.field_modulus).unwrap();
+        assert_eq!(result.score, "352321536");
+        assert!(result.passed);
+
+        let result = evaluate(&features, &template, "352321536", &encoding.field_modulus).unwrap();
+        assert!(!result.passed);
+    }
+
+    #[test]
+    fn test_drained_inputs() {
+        let inputs = BionettaGenericInputs {
+            ultra_groth: "1".to_string(),
+            address: "1".to_string(),
+            threshold: "10".to_string(),
+            nonce: "1".to_string(),
+            features: signals(&["3", "4"]),
+            image: vec![],
+            rand: "0".to_string(),
+        };
+
+        let result =
+            evaluate_inputs(&inputs, &signals(&["0", "1"]), &FieldModulus::bn254()).unwrap();
+        assert_eq!(result.score, "18");
+        assert!(!result.passed);
+    }
+
+    #[test]
+    fn test_invalid_inputs() {
+        let field_modulus = FieldModulus::bn254();
+
+        assert!(matches!(
+            evaluate(&signals(&["1"]), &signals(&["1", "2"]), "1", &field_modulus),
+            Err(ZKNeuralError::EmbeddingSizeMismatch(1, 2))
+        ));
+        assert!(matches!(
+            evaluate(&[], &[], "1", &field_modulus),
+            Err(ZKNeuralError::InvalidEmbedding(_))
+        ));
+        assert!(matches!(
+            evaluate(&signals(&["-1"]), &signals(&["1"]), "1", &field_modulus),
+            Err(ZKNeuralError::InvalidFieldElement(_))
+        ));
+        assert!(matches!(
+            evaluate(
+                &signals(&["4294967296"]),
+                &signals(&["0"]),
+                "1",
+                &field_modulus
+            ),
+            Err(ZKNeuralError::ScoreOutOfRange(_))
+        ));
+    }
+}
@@ -2,3 +2,7 @@ pub(super) const WITNESS_SIZE: usize = 100 * 1024 * 1024;
 pub(super) const WITNESS_ERROR_MSG_MAXSIZE: usize = 256;
 pub(super) const PROOF_SIZE: usize = 4 * 1024 * 1024;
 pub(super) const PUB_SIGNALS_SIZE: usize = 4 * 1024 * 1024;
+
+// Assumed width of the `LessThan` comparator of the distance and the threshold. It is not
+// taken from the circuit sources, which are not part of this repository.
+pub(super) const COMPARATOR_BITS: u64 = 64;
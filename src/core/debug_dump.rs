@@ -0,0 +1,224 @@
+use std::{
+    fs,
+    io::Cursor,
+    path::PathBuf,
+    sync::{Mutex, PoisonError},
+};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use image::{DynamicImage, ImageFormat};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use tracing::warn;
+
+use crate::ZKNeuralError;
+
+const DEBUG_DUMP_SCORE_FLOOR: f32 = 0.5;
+
+/// Where the debug artifacts go and which detector scores are kept.
+///
+/// Artifacts are written to `directory` when it is set and kept in memory for
+/// `DebugDump::take_bundle` otherwise. `score_floor` is the minimum sigmoid score of the raw
+/// detector anchors that are dumped, independently of the detection `min_score`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct DebugDumpOptions {
+    pub directory: Option<PathBuf>,
+    pub score_floor: f32,
+}
+
+impl Default for DebugDumpOptions {
+    fn default() -> Self {
+        DebugDumpOptions {
+            directory: None,
+            score_floor: DEBUG_DUMP_SCORE_FLOOR,
+        }
+    }
+}
+
+/// A single intermediate artifact, PNG for images, JSON for metadata and little-endian bytes
+/// for tensors. `data` is base64 encoded in JSON.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DebugArtifact {
+    pub name: String,
+    #[serde(serialize_with = "serialize_base64")]
+    #[serde(deserialize_with = "deserialize_base64")]
+    pub data: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct DebugBundle {
+    pub artifacts: Vec<DebugArtifact>,
+}
+
+/// Collects the intermediate artifacts of the preprocessing pipeline for bug reports.
+///
+/// Shared by a `FaceDetector` and the `TensorInvoker` it runs for, so the artifacts of a failed
+/// call, e.g. one ending with `FaceNotFound`, are kept up to the failing stage. An artifact
+/// recorded again under the same name replaces the previous one.
+///
+/// Artifacts are not tagged with the call that recorded them, so calls running concurrently on
+/// one dump mix their artifacts. Give each thread its own dump, or take the bundle between
+/// calls, to get the artifacts of a single call.
+///
+/// The artifacts contain the user's face, only enable it with their consent.
+pub struct DebugDump {
+    options: DebugDumpOptions,
+    artifacts: Mutex<Vec<DebugArtifact>>,
+}
+
+impl DebugDump {
+    /// Creates the dump, creating its directory if needed.
+    pub fn new(options: DebugDumpOptions) -> Result<Self, ZKNeuralError> {
+        if let Some(directory) = &options.directory {
+            fs::create_dir_all(directory)
+                .map_err(|e| ZKNeuralError::DebugDumpFailed(e.to_string()))?;
+        }
+
+        Ok(DebugDump {
+            options,
+            artifacts: Mutex::new(vec![]),
+        })
+    }
+
+    pub fn score_floor(&self) -> f32 {
+        self.options.score_floor
+    }
+
+    /// Records an artifact. A failure to encode or write it is logged and otherwise ignored, so
+    /// dumping never changes the result of the pipeline.
+    pub fn record(&self, name: &str, data: Vec<u8>) {
+        self.log_failure(name, self.write(name, data));
+    }
+
+    pub fn record_image(&self, name: &str, image: &DynamicImage) {
+        let mut data = Cursor::new(vec![]);
+
+        let result = image
+            .write_to(&mut data, ImageFormat::Png)
+            .map_err(ZKNeuralError::from)
+            .and_then(|_| self.write(name, data.into_inner()));
+
+        self.log_failure(name, result);
+    }
+
+    pub fn record_json(&self, name: &str, value: &impl Serialize) {
+        let result = serde_json::to_vec_pretty(value)
+            .map_err(ZKNeuralError::from)
+            .and_then(|data| self.write(name, data));
+
+        self.log_failure(name, result);
+    }
+
+    /// Returns the artifacts kept in memory and clears them, empty when dumping to a directory.
+    pub fn take_bundle(&self) -> DebugBundle {
+        let mut artifacts = self
+            .artifacts
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        DebugBundle {
+            artifacts: std::mem::take(&mut *artifacts),
+        }
+    }
+
+    fn write(&self, name: &str, data: Vec<u8>) -> Result<(), ZKNeuralError> {
+        if let Some(directory) = &self.options.directory {
+            return fs::write(directory.join(name), data)
+                .map_err(|e| ZKNeuralError::DebugDumpFailed(e.to_string()));
+        }
+
+        let mut artifacts = self
+            .artifacts
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        let artifact = DebugArtifact {
+            name: name.to_string(),
+            data,
+        };
+
+        match artifacts.iter_mut().find(|artifact| artifact.name == name) {
+            Some(existing) => *existing = artifact,
+            None => artifacts.push(artifact),
+        }
+
+        Ok(())
+    }
+
+    fn log_failure(&self, name: &str, result: Result<(), ZKNeuralError>) {
+        if let Err(e) = result {
+            warn!(name, error = %e, "debug artifact not recorded");
+        }
+    }
+}
+
+fn serialize_base64<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&STANDARD.encode(data))
+}
+
+fn deserialize_base64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    let encoded = String::deserialize(deserializer)?;
+
+    STANDARD.decode(encoded).map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{DynamicImage, RgbImage};
+    use serde_json::json;
+
+    use crate::core::debug_dump::{DebugBundle, DebugDump, DebugDumpOptions};
+
+    #[test]
+    fn test_in_memory_bundle() {
+        let debug_dump = DebugDump::new(DebugDumpOptions::default()).unwrap();
+
+        debug_dump.record("tensor.bin", vec![1, 2, 3]);
+        debug_dump.record("tensor.bin", vec![4, 5]);
+        debug_dump.record_image("image.png", &DynamicImage::ImageRgb8(RgbImage::new(2, 2)));
+
+        let bundle = debug_dump.take_bundle();
+
+        assert_eq!(bundle.artifacts.len(), 2);
+        assert_eq!(bundle.artifacts[0].data, vec![4, 5]);
+
+        let image = image::load_from_memory(&bundle.artifacts[1].data).unwrap();
+        assert_eq!((image.width(), image.height()), (2, 2));
+
+        let bundle_json = serde_json::to_value(&bundle).unwrap();
+        assert_eq!(
+            bundle_json["artifacts"][0],
+            json!({ "name": "tensor.bin", "data": "BAU=" })
+        );
+
+        let decoded: DebugBundle = serde_json::from_value(bundle_json).unwrap();
+        assert_eq!(decoded, bundle);
+
+        assert!(debug_dump.take_bundle().artifacts.is_empty());
+    }
+
+    #[test]
+    fn test_directory_dump() {
+        let directory = std::env::temp_dir().join("zkneural_test_directory_dump");
+
+        let debug_dump = DebugDump::new(DebugDumpOptions {
+            directory: Some(directory.clone()),
+            ..Default::default()
+        })
+        .unwrap();
+
+        debug_dump.record_json("box.json", &json!([1, 2]));
+
+        let written: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(directory.join("box.json")).unwrap()).unwrap();
+
+        assert_eq!(written, json!([1, 2]));
+        assert!(debug_dump.take_bundle().artifacts.is_empty());
+
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        // Write failures are only logged.
+        debug_dump.record("tensor.bin", vec![1]);
+        assert!(!directory.exists());
+    }
+}
@@ -1,13 +1,23 @@
 use thiserror::Error;
 
+use crate::core::ZKNeuralProvingType;
+
 #[derive(Error, Debug)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Error), uniffi(flat_error))]
 pub enum ZKNeuralError {
     #[error("Image processing error: {0}")]
     ImageProcessingError(#[from] image::ImageError),
     #[error("JSON error: {0}")]
     JsonError(#[from] serde_json::Error),
+    #[cfg(feature = "tflite")]
     #[error("TensorFlow Lite error: {0}")]
     TensorFlowLiteError(#[from] tflitec::Error),
+    #[cfg(feature = "jni")]
+    #[error("JNI error: {0}")]
+    JniError(#[from] jni::errors::Error),
+    #[cfg(any(feature = "jni", feature = "uniffi"))]
+    #[error("Native code panicked: {0}")]
+    NativePanic(String),
 
     #[error("Generate witness callback not set")]
     WitnessCallbackNotSet,
@@ -20,13 +30,108 @@ pub enum ZKNeuralError {
     #[error("Proving type not set")]
     ProvingTypeNotSet,
 
+    #[error("Inference backend not set")]
+    InferenceBackendNotSet,
+    #[error("Inference failed: {0}")]
+    InferenceFailed(String),
+    #[error("Invalid tensor: {0}")]
+    InvalidTensor(String),
     #[error("TensorFlow Lite model does not have four dimensions")]
     ModelNotFourDimensional,
+    #[error("TensorFlow Lite model has unsupported batch size {0}")]
+    InvalidModelBatchSize(usize),
     #[error("TenserFlow Lite Model have invalid channel")]
     InvalidModelChannel,
     #[error("TenserFlow Lite Model have invalid data type")]
     InvalidModelDataType,
+    #[error("Invalid model hash: {0}")]
+    InvalidModelHash(String),
+    #[error("Model hash {0} is not allowed")]
+    ModelHashNotAllowed(String),
+    #[error("Invalid model bundle: {0}")]
+    InvalidModelBundle(String),
+    #[error("Model bundle format version {0} is not supported")]
+    UnsupportedModelBundleVersion(u32),
+    #[error("Checksum of bundle file `{0}` does not match the manifest")]
+    ModelBundleChecksumMismatch(String),
+    #[error("Model bundle not loaded")]
+    ModelBundleNotLoaded,
+    #[error("Inputs do not match the model bundle: {0}")]
+    ModelBundleInputMismatch(String),
+    #[error("Proving type {0:?} does not match the model bundle")]
+    ModelBundleProvingTypeMismatch(ZKNeuralProvingType),
+    #[error("Invalid preprocessing spec: {0}")]
+    InvalidPreprocessingSpec(String),
+    #[error("Invalid signal encoding: {0}")]
+    InvalidSignalEncoding(String),
+    #[error("Signal value {0} is out of range")]
+    SignalOutOfRange(f64),
+    #[error("Signal value {0} is negative")]
+    NegativeSignal(f64),
+    #[error("Invalid field element: {0}")]
+    InvalidFieldElement(String),
+    #[error("Randomness unavailable: {0}")]
+    RandomnessUnavailable(String),
+
+    #[error("Invalid address: {0}")]
+    InvalidAddress(String),
+    #[error("Invalid threshold: {0}")]
+    InvalidThreshold(String),
+    #[error("Invalid nonce: {0}")]
+    InvalidNonce(String),
+    #[error("Argument `{0}` is not a valid UTF-8 string")]
+    InvalidString(String),
+    #[error("Argument `{0}` is null")]
+    NullBuffer(String),
+
+    #[error("Embedding sizes {0} and {1} do not match")]
+    EmbeddingSizeMismatch(usize, usize),
+    #[error("Invalid embedding: {0}")]
+    InvalidEmbedding(String),
+    #[error("Score {0} does not fit the circuit comparator")]
+    ScoreOutOfRange(String),
+
+    #[error("Invalid input schema: {0}")]
+    InvalidInputSchema(String),
+    #[error("Input schema `{0}` is not registered")]
+    InputSchemaNotFound(String),
+    #[error("Required signal `{0}` is missing")]
+    MissingSignal(String),
+    #[error("Signal `{0}` is not part of the input schema")]
+    UnknownSignal(String),
 
     #[error("Face not found")]
     FaceNotFound,
+    #[error("Face is too small")]
+    FaceTooSmall,
+    #[error("Face is blurry")]
+    FaceBlurry,
+    #[error("Face is too dark")]
+    FaceTooDark,
+    #[error("Face is too bright")]
+    FaceTooBright,
+    #[error("Face pose is out of range")]
+    FacePoseOutOfRange,
+
+    #[error("Liveness model not set")]
+    LivenessModelNotSet,
+    #[error("Liveness model has invalid output")]
+    InvalidLivenessOutput,
+    #[error("Liveness check failed with score {0}")]
+    LivenessCheckFailed(f32),
+
+    #[error("Invalid raw frame: {0}")]
+    InvalidRawFrame(String),
+
+    #[error("Debug dump failed: {0}")]
+    DebugDumpFailed(String),
+
+    #[error("Native handle is null")]
+    NullHandle,
+    #[cfg(feature = "jni")]
+    #[error("Invalid {0} value {1}")]
+    InvalidEnumValue(String, i32),
+    #[cfg(feature = "uniffi")]
+    #[error("{0} lock is poisoned")]
+    LockPoisoned(String),
 }
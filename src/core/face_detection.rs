@@ -1,10 +1,20 @@
+use std::sync::{Arc, Mutex, PoisonError};
+
 use image::{DynamicImage, imageops::FilterType};
-use tflitec::{interpreter::Interpreter, model::Model};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tracing::{debug, debug_span, warn};
 
 use crate::{
     ZKNeuralError,
     core::{
+        debug_dump::DebugDump,
+        face_quality::FaceQuality,
+        image_source::ImageSource,
+        inference::{self, InferenceBackend, InferenceSession, default_backend},
         math::sigmoid,
+        model_integrity::BLAZE_FACE_MODEL_SHA256,
+        signal_encoding::SignalEncoding,
         tensor::{collect_processed_data_to_float, prepare_data_by_float_type},
     },
 };
@@ -15,39 +25,280 @@ const IMAGE_SCALE: u32 = 128;
 
 const FACE_DETECTION_MIN_SCORE: f32 = 0.9;
 
+const FACE_DETECTION_IOU_THRESHOLD: f32 = 0.3;
+
+const FACE_DETECTION_MAX_FACES: usize = 10;
+
+const BLAZE_FACE_VALUES_PER_ANCHOR: usize = 16;
+
+const BLAZE_FACE_KEYPOINTS: usize = 6;
+
+/// Pinned by `BLAZE_FACE_MODEL_SHA256`.
 const BLAZE_FACE_MODEL_BYTES: &[u8] = include_bytes!("../../assets/blaze_face_short_range.tflite");
 
-pub struct FaceDetector {}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct FaceDetectionOptions {
+    pub min_score: f32,
+    pub iou_threshold: f32,
+    pub max_faces: usize,
+    pub apply_exif_orientation: bool,
+}
+
+impl Default for FaceDetectionOptions {
+    fn default() -> Self {
+        FaceDetectionOptions {
+            min_score: FACE_DETECTION_MIN_SCORE,
+            iou_threshold: FACE_DETECTION_IOU_THRESHOLD,
+            max_faces: FACE_DETECTION_MAX_FACES,
+            apply_exif_orientation: true,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct FaceBox {
+    pub x_min: f32,
+    pub y_min: f32,
+    pub x_max: f32,
+    pub y_max: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct FaceKeypoint {
+    pub x: f32,
+    pub y: f32,
+}
+
+/// A single face found by BlazeFace.
+///
+/// `bounding_box` and `keypoints` are normalized to `[0, 1]` relative to the input image,
+/// the `pixel_*` counterparts are the same values in pixels, clamped to the image bounds.
+/// Keypoints are ordered as BlazeFace emits them: right eye, left eye, nose tip, mouth
+/// center, right ear tragion, left ear tragion.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct FaceDetection {
+    pub score: f32,
+    pub bounding_box: FaceBox,
+    pub pixel_bounding_box: FaceBox,
+    pub keypoints: Vec<FaceKeypoint>,
+    pub pixel_keypoints: Vec<FaceKeypoint>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct FaceDetectionResult {
+    pub image_width: u32,
+    pub image_height: u32,
+    pub faces: Vec<FaceDetection>,
+}
+
+/// BlazeFace detector, running on `backend` or on the default backend when it is `None`.
+///
+/// The model is loaded once when the detector is created and kept for every call, backends
+/// that cannot keep a session load it per call instead.
+///
+/// With a `debug_dump`, the detector records the decoded image, the 128px detector input, the
+/// raw anchor scores above the dump floor and the chosen face box.
+pub struct FaceDetector {
+    pub options: FaceDetectionOptions,
+    pub debug_dump: Option<Arc<DebugDump>>,
+    backend: Option<Arc<dyn InferenceBackend>>,
+    session: Mutex<Option<Box<dyn InferenceSession + Send>>>,
+}
+
+impl Default for FaceDetector {
+    fn default() -> Self {
+        FaceDetector::new(FaceDetectionOptions::default())
+    }
+}
 
 impl FaceDetector {
-    pub fn detect_face(image_data: &[u8]) -> Result<DynamicImage, ZKNeuralError> {
-        let loaded_rescaled_image = image::load_from_memory(image_data)?.resize_exact(
-            IMAGE_SCALE,
-            IMAGE_SCALE,
-            FilterType::CatmullRom,
+    pub fn new(options: FaceDetectionOptions) -> Self {
+        Self::with_optional_backend(options, None)
+    }
+
+    pub fn with_backend(options: FaceDetectionOptions, backend: Arc<dyn InferenceBackend>) -> Self {
+        Self::with_optional_backend(options, Some(backend))
+    }
+
+    fn with_optional_backend(
+        options: FaceDetectionOptions,
+        backend: Option<Arc<dyn InferenceBackend>>,
+    ) -> Self {
+        let mut detector = FaceDetector {
+            options,
+            debug_dump: None,
+            backend,
+            session: Mutex::new(None),
+        };
+
+        // A failed load is not fatal here, the error is reported by the first detection.
+        match detector
+            .backend()
+            .and_then(|backend| backend.load(BLAZE_FACE_MODEL_BYTES))
+        {
+            Ok(session) => detector.session = Mutex::new(session),
+            Err(e) => warn!(error = %e, "face detector model not loaded"),
+        }
+
+        detector
+    }
+
+    fn backend(&self) -> Result<Arc<dyn InferenceBackend>, ZKNeuralError> {
+        match &self.backend {
+            Some(backend) => Ok(backend.clone()),
+            None => default_backend(),
+        }
+    }
+
+    /// SHA-256 of the built-in BlazeFace model.
+    pub fn model_hash(&self) -> &'static str {
+        BLAZE_FACE_MODEL_SHA256
+    }
+
+    /// Decodes the image and crops the highest scoring face out of it.
+    pub fn detect_face<'a>(
+        &self,
+        image: impl Into<ImageSource<'a>>,
+    ) -> Result<DynamicImage, ZKNeuralError> {
+        let loaded_image = image.into().decode(self.options.apply_exif_orientation)?;
+
+        self.crop_face(&loaded_image)
+    }
+
+    /// Crops the highest scoring face out of an already decoded image.
+    pub fn crop_face(&self, loaded_image: &DynamicImage) -> Result<DynamicImage, ZKNeuralError> {
+        let best_detection = self.detect_best_face(loaded_image)?;
+
+        Ok(Self::crop_detection(loaded_image, &best_detection))
+    }
+
+    /// Crops the detected face out of the image it was detected in.
+    pub fn crop_detection(loaded_image: &DynamicImage, detection: &FaceDetection) -> DynamicImage {
+        Self::resize_original_image(loaded_image, &detection.bounding_box)
+    }
+
+    /// Returns the highest scoring face of an already decoded image.
+    pub fn detect_best_face(
+        &self,
+        loaded_image: &DynamicImage,
+    ) -> Result<FaceDetection, ZKNeuralError> {
+        self.detect_best_face_recorded(loaded_image, self.debug_dump.as_deref())
+    }
+
+    /// `detect_best_face` recording to `debug_dump` instead of the detector's own dump, for
+    /// the detector a `TensorInvoker` keeps.
+    pub(crate) fn detect_best_face_recorded(
+        &self,
+        loaded_image: &DynamicImage,
+        debug_dump: Option<&DebugDump>,
+    ) -> Result<FaceDetection, ZKNeuralError> {
+        let best_detection = self
+            .detect_in_image(loaded_image, &self.options, debug_dump)?
+            .into_iter()
+            .next()
+            .ok_or(ZKNeuralError::FaceNotFound)?;
+
+        debug!(
+            score = best_detection.score,
+            bounding_box = ?best_detection.pixel_bounding_box,
+            "best face selected"
         );
 
-        let rgb_image_data: Vec<u8> = loaded_rescaled_image.to_rgb8().to_vec();
+        if let Some(debug_dump) = debug_dump {
+            debug_dump.record_json("face_box.json", &best_detection);
+        }
 
-        let (prepared_image_data, _) = prepare_data_by_float_type::<f32>(rgb_image_data);
+        Ok(best_detection)
+    }
 
-        let model = Model::from_bytes(BLAZE_FACE_MODEL_BYTES)?;
+    /// Scores the sharpness, exposure, size and pose of the highest scoring face in the image.
+    pub fn assess_quality<'a>(
+        &self,
+        image: impl Into<ImageSource<'a>>,
+    ) -> Result<FaceQuality, ZKNeuralError> {
+        let loaded_image = image.into().decode(self.options.apply_exif_orientation)?;
 
-        let interpreter = Interpreter::new(&model, None)?;
+        let best_detection = self.detect_best_face(&loaded_image)?;
 
-        interpreter.allocate_tensors()?;
+        Ok(FaceQuality::assess(&loaded_image, &best_detection))
+    }
 
-        interpreter.copy(&prepared_image_data, 0)?;
+    /// Detects all faces in the image using the detector options.
+    pub fn detect_faces<'a>(
+        &self,
+        image: impl Into<ImageSource<'a>>,
+    ) -> Result<FaceDetectionResult, ZKNeuralError> {
+        self.detect_faces_with_options(image, &self.options)
+    }
 
-        interpreter.invoke()?;
+    /// Detects all faces in the image, overriding the detector options for this call.
+    ///
+    /// Faces are sorted by score in descending order, overlapping detections are suppressed.
+    pub fn detect_faces_with_options<'a>(
+        &self,
+        image: impl Into<ImageSource<'a>>,
+        options: &FaceDetectionOptions,
+    ) -> Result<FaceDetectionResult, ZKNeuralError> {
+        let loaded_image = image.into().decode(options.apply_exif_orientation)?;
+
+        let faces = self.detect_in_image(&loaded_image, options, self.debug_dump.as_deref())?;
+
+        Ok(FaceDetectionResult {
+            image_width: loaded_image.width(),
+            image_height: loaded_image.height(),
+            faces,
+        })
+    }
+
+    fn detect_in_image(
+        &self,
+        loaded_image: &DynamicImage,
+        options: &FaceDetectionOptions,
+        debug_dump: Option<&DebugDump>,
+    ) -> Result<Vec<FaceDetection>, ZKNeuralError> {
+        let _span = debug_span!(
+            "detect_faces",
+            width = loaded_image.width(),
+            height = loaded_image.height()
+        )
+        .entered();
+
+        let loaded_rescaled_image =
+            loaded_image.resize_exact(IMAGE_SCALE, IMAGE_SCALE, FilterType::CatmullRom);
+
+        if let Some(debug_dump) = debug_dump {
+            debug_dump.record_image("decoded_image.png", loaded_image);
+            debug_dump.record_image("detector_input.png", &loaded_rescaled_image);
+        }
+
+        let rgb_image_data: Vec<u8> = loaded_rescaled_image.to_rgb8().to_vec();
 
-        let face_detections_tensor = interpreter.output(0)?;
-        let face_scores_tensor = interpreter.output(1)?;
+        let (prepared_image_data, _) =
+            prepare_data_by_float_type::<f32>(rgb_image_data, &SignalEncoding::default())?;
+
+        let session = self.session.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let outputs = match session.as_ref() {
+            Some(session) => session.invoke(&prepared_image_data)?,
+            None => self.backend()?.session(BLAZE_FACE_MODEL_BYTES, |session| {
+                session.invoke(&prepared_image_data)
+            })?,
+        };
+
+        drop(session);
+
+        let face_detections_tensor = inference::output(&outputs, 0)?;
+        let face_scores_tensor = inference::output(&outputs, 1)?;
 
         let face_detections =
-            collect_processed_data_to_float::<f32>(face_detections_tensor.data().to_vec(), false);
+            collect_processed_data_to_float::<f32>(face_detections_tensor.data.clone(), false);
         let raw_face_scores =
-            collect_processed_data_to_float::<f32>(face_scores_tensor.data().to_vec(), false);
+            collect_processed_data_to_float::<f32>(face_scores_tensor.data.clone(), false);
 
         let face_scores: Vec<f32> = raw_face_scores
             .iter()
@@ -55,36 +306,73 @@ impl FaceDetector {
             .collect();
 
         let boxes = face_detections
-            .chunks(16)
+            .chunks(BLAZE_FACE_VALUES_PER_ANCHOR)
             .map(|chunk| chunk.to_vec())
             .collect::<Vec<Vec<f32>>>();
 
         let decoded_boxes = Self::decode_boxes(boxes);
 
-        let mut best_score_index: usize = 0;
-        for (i, &score) in face_scores.iter().enumerate() {
-            if score > face_scores[best_score_index] {
-                best_score_index = i;
-            }
+        if let Some(debug_dump) = debug_dump {
+            debug_dump.record_json(
+                "detector_scores.json",
+                &Self::scores_above(&face_scores, &decoded_boxes, debug_dump.score_floor()),
+            );
         }
 
-        if face_scores[best_score_index] < FACE_DETECTION_MIN_SCORE {
-            return Err(ZKNeuralError::FaceNotFound);
+        let max_score = face_scores
+            .iter()
+            .copied()
+            .fold(f32::NEG_INFINITY, f32::max);
+
+        let mut candidates: Vec<(f32, Vec<f32>)> = face_scores
+            .into_iter()
+            .zip(decoded_boxes)
+            .filter(|(score, _)| *score >= options.min_score)
+            .collect();
+
+        candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        debug!(
+            candidates = candidates.len(),
+            max_score,
+            min_score = options.min_score,
+            "anchors scored"
+        );
+
+        let mut selected: Vec<(f32, Vec<f32>)> = vec![];
+        for (score, decoded_box) in candidates {
+            if selected.len() >= options.max_faces {
+                break;
+            }
+
+            let overlaps = selected.iter().any(|(_, kept)| {
+                intersection_over_union(kept, &decoded_box) > options.iou_threshold
+            });
+
+            if !overlaps {
+                selected.push((score, decoded_box));
+            }
         }
 
-        Ok(Self::resize_original_image(
-            image_data,
-            &decoded_boxes[best_score_index],
-        ))
-    }
+        debug!(
+            scores = ?selected.iter().map(|(score, _)| *score).collect::<Vec<f32>>(),
+            "faces selected"
+        );
+
+        let width = loaded_image.width() as f32;
+        let height = loaded_image.height() as f32;
 
-    fn resize_original_image(image_data: &[u8], best_box: &[f32]) -> DynamicImage {
-        let loaded_image = image::load_from_memory(&image_data).unwrap();
+        Ok(selected
+            .into_iter()
+            .map(|(score, decoded_box)| to_face_detection(score, &decoded_box, width, height))
+            .collect())
+    }
 
-        let x_min = loaded_image.width() as f32 * best_box[0];
-        let y_min = loaded_image.height() as f32 * best_box[1];
-        let x_max = loaded_image.width() as f32 * best_box[2];
-        let y_max = loaded_image.height() as f32 * best_box[3];
+    fn resize_original_image(loaded_image: &DynamicImage, best_box: &FaceBox) -> DynamicImage {
+        let x_min = loaded_image.width() as f32 * best_box.x_min;
+        let y_min = loaded_image.height() as f32 * best_box.y_min;
+        let x_max = loaded_image.width() as f32 * best_box.x_max;
+        let y_max = loaded_image.height() as f32 * best_box.y_max;
 
         loaded_image.crop_imm(
             x_min as u32,
@@ -94,6 +382,29 @@ impl FaceDetector {
         )
     }
 
+    /// Anchors scoring at least `floor`, by descending score, with their decoded boxes.
+    fn scores_above(scores: &[f32], decoded_boxes: &[Vec<f32>], floor: f32) -> Vec<Value> {
+        let mut anchors: Vec<(usize, f32)> = scores
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|(_, score)| *score >= floor)
+            .collect();
+
+        anchors.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        anchors
+            .into_iter()
+            .map(|(anchor, score)| {
+                json!({
+                    "anchor": anchor,
+                    "score": score,
+                    "box": decoded_boxes.get(anchor).map(|decoded_box| &decoded_box[..4]),
+                })
+            })
+            .collect()
+    }
+
     fn decode_boxes(boxes: Vec<Vec<f32>>) -> Vec<Vec<f32>> {
         let mut decoded_boxes: Vec<Vec<f32>> = vec![];
         for (element, anchor) in boxes.iter().zip(BLAZE_FACE_SHORT_RANGE_ANCHORS.iter()) {
@@ -102,25 +413,175 @@ impl FaceDetector {
             let width = element[2] / IMAGE_SCALE as f32 * anchor[2];
             let height = element[3] / IMAGE_SCALE as f32 * anchor[3];
 
-            let decoded_box = vec![
+            let mut decoded_box = vec![
                 x_center - width / 2.0,
                 y_center - height / 2.0,
                 x_center + width / 2.0,
                 y_center + height / 2.0,
             ];
 
+            for keypoint in 0..BLAZE_FACE_KEYPOINTS {
+                let offset = 4 + keypoint * 2;
+
+                decoded_box.push(element[offset] / IMAGE_SCALE as f32 * anchor[2] + anchor[0]);
+                decoded_box.push(element[offset + 1] / IMAGE_SCALE as f32 * anchor[3] + anchor[1]);
+            }
+
             decoded_boxes.push(decoded_box);
         }
 
-        return decoded_boxes;
+        decoded_boxes
+    }
+}
+
+fn intersection_over_union(a: &[f32], b: &[f32]) -> f32 {
+    let x_min = a[0].max(b[0]);
+    let y_min = a[1].max(b[1]);
+    let x_max = a[2].min(b[2]);
+    let y_max = a[3].min(b[3]);
+
+    let intersection = (x_max - x_min).max(0.0) * (y_max - y_min).max(0.0);
+    let union = (a[2] - a[0]) * (a[3] - a[1]) + (b[2] - b[0]) * (b[3] - b[1]) - intersection;
+
+    if union <= 0.0 {
+        return 0.0;
+    }
+
+    intersection / union
+}
+
+fn to_face_detection(score: f32, decoded_box: &[f32], width: f32, height: f32) -> FaceDetection {
+    let bounding_box = FaceBox {
+        x_min: decoded_box[0],
+        y_min: decoded_box[1],
+        x_max: decoded_box[2],
+        y_max: decoded_box[3],
+    };
+
+    let pixel_bounding_box = FaceBox {
+        x_min: (bounding_box.x_min * width).clamp(0.0, width),
+        y_min: (bounding_box.y_min * height).clamp(0.0, height),
+        x_max: (bounding_box.x_max * width).clamp(0.0, width),
+        y_max: (bounding_box.y_max * height).clamp(0.0, height),
+    };
+
+    let keypoints: Vec<FaceKeypoint> = decoded_box[4..]
+        .chunks_exact(2)
+        .map(|point| FaceKeypoint {
+            x: point[0],
+            y: point[1],
+        })
+        .collect();
+
+    let pixel_keypoints = keypoints
+        .iter()
+        .map(|point| FaceKeypoint {
+            x: (point.x * width).clamp(0.0, width),
+            y: (point.y * height).clamp(0.0, height),
+        })
+        .collect();
+
+    FaceDetection {
+        score,
+        bounding_box,
+        pixel_bounding_box,
+        keypoints,
+        pixel_keypoints,
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::{fs::File, io::Read};
+    use std::{
+        fs::File,
+        io::Read,
+        sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        },
+    };
+
+    use crate::{
+        ZKNeuralError,
+        core::{
+            face_detection::{BLAZE_FACE_MODEL_BYTES, FaceDetectionOptions, FaceDetector},
+            inference::{DataType, InferenceBackend, InferenceSession, Shape, Tensor, TensorSpec},
+            model_integrity::{BLAZE_FACE_MODEL_SHA256, model_hash},
+        },
+    };
+
+    /// Backend counting how often BlazeFace is loaded, with outputs scoring no face.
+    #[derive(Default)]
+    struct CountingBackend {
+        loads: AtomicUsize,
+        sessions: AtomicUsize,
+    }
+
+    struct EmptySession;
+
+    impl InferenceBackend for CountingBackend {
+        fn with_session(
+            &self,
+            _model: &[u8],
+            f: &mut dyn FnMut(&dyn InferenceSession) -> Result<(), ZKNeuralError>,
+        ) -> Result<(), ZKNeuralError> {
+            self.sessions.fetch_add(1, Ordering::Relaxed);
+
+            f(&EmptySession)
+        }
 
-    use crate::core::face_detection::FaceDetector;
+        fn load(
+            &self,
+            _model: &'static [u8],
+        ) -> Result<Option<Box<dyn InferenceSession + Send>>, ZKNeuralError> {
+            self.loads.fetch_add(1, Ordering::Relaxed);
+
+            Ok(Some(Box::new(EmptySession)))
+        }
+    }
+
+    impl InferenceSession for EmptySession {
+        fn input(&self) -> Result<TensorSpec, ZKNeuralError> {
+            Ok(TensorSpec {
+                shape: Shape::new(vec![1, 128, 128, 3]),
+                data_type: DataType::Float32,
+            })
+        }
+
+        fn invoke(&self, _input: &[u8]) -> Result<Vec<Tensor>, ZKNeuralError> {
+            let tensor = |values: usize| Tensor {
+                spec: TensorSpec {
+                    shape: Shape::new(vec![1, values]),
+                    data_type: DataType::Float32,
+                },
+                data: vec![0; values * size_of::<f32>()],
+            };
+
+            Ok(vec![tensor(896 * 16), tensor(896)])
+        }
+    }
+
+    #[test]
+    fn test_session_reuse() {
+        let backend = Arc::new(CountingBackend::default());
+
+        let detector = FaceDetector::with_backend(FaceDetectionOptions::default(), backend.clone());
+
+        let image_data = std::fs::read("assets/face_cropped_resized.jpg").unwrap();
+
+        for _ in 0..3 {
+            let result = detector.detect_faces(&image_data).unwrap();
+            assert!(result.faces.is_empty());
+        }
+
+        assert_eq!(backend.loads.load(Ordering::Relaxed), 1);
+        assert_eq!(backend.sessions.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn test_model_hash() {
+        assert_eq!(model_hash(BLAZE_FACE_MODEL_BYTES), BLAZE_FACE_MODEL_SHA256);
+    }
 
     #[test]
     fn test_face_detection() {
@@ -130,6 +591,32 @@ mod tests {
             .collect::<Result<Vec<_>, _>>()
             .unwrap();
 
-        let _ = FaceDetector::detect_face(&image_data).expect("Face detection failed");
+        let _ = FaceDetector::default()
+            .detect_face(&image_data)
+            .expect("Face detection failed");
+    }
+
+    #[test]
+    fn test_detect_faces() {
+        let image_data = File::open("assets/face3.jpg")
+            .unwrap()
+            .bytes()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        let detector = FaceDetector::new(FaceDetectionOptions::default());
+
+        let result = detector
+            .detect_faces(&image_data)
+            .expect("Face detection failed");
+
+        assert!(!result.faces.is_empty());
+
+        let face = &result.faces[0];
+        assert_eq!(face.keypoints.len(), 6);
+        assert!(face.pixel_bounding_box.x_max <= result.image_width as f32);
+        assert!(face.pixel_bounding_box.y_max <= result.image_height as f32);
+
+        println!("Result: {}", serde_json::to_string(&result).unwrap());
     }
 }
@@ -0,0 +1,311 @@
+use image::{DynamicImage, GrayImage, imageops::FilterType};
+use serde::{Deserialize, Serialize};
+
+use crate::{ZKNeuralError, core::face_detection::FaceDetection};
+
+const SHARPNESS_SCALE: u32 = 128;
+
+const RIGHT_EYE: usize = 0;
+const LEFT_EYE: usize = 1;
+const NOSE_TIP: usize = 2;
+const RIGHT_EAR: usize = 4;
+const LEFT_EAR: usize = 5;
+
+/// Rejection thresholds for the face quality gate.
+///
+/// `min_face_size` is the larger side of the face box relative to the same side of the frame,
+/// brightness is the mean luma of the face in `[0, 255]`, angles are in degrees.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct FaceQualityThresholds {
+    pub min_face_size: f32,
+    pub min_sharpness: f32,
+    pub min_brightness: f32,
+    pub max_brightness: f32,
+    pub max_yaw: f32,
+    pub max_roll: f32,
+}
+
+impl Default for FaceQualityThresholds {
+    fn default() -> Self {
+        FaceQualityThresholds {
+            min_face_size: 0.2,
+            min_sharpness: 20.0,
+            min_brightness: 50.0,
+            max_brightness: 210.0,
+            max_yaw: 30.0,
+            max_roll: 20.0,
+        }
+    }
+}
+
+/// Quality scores of a detected face.
+///
+/// `sharpness` is the variance of the Laplacian of the face resized to 128x128 grayscale,
+/// `yaw` and `roll` are estimated from the BlazeFace keypoints.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct FaceQuality {
+    pub face_size: f32,
+    pub sharpness: f32,
+    pub brightness: f32,
+    pub yaw: f32,
+    pub roll: f32,
+}
+
+impl FaceQuality {
+    pub fn assess(image: &DynamicImage, detection: &FaceDetection) -> Self {
+        let bounding_box = &detection.pixel_bounding_box;
+
+        let face_width = bounding_box.x_max - bounding_box.x_min;
+        let face_height = bounding_box.y_max - bounding_box.y_min;
+
+        let face_size =
+            (face_width / image.width() as f32).max(face_height / image.height() as f32);
+
+        let face = image
+            .crop_imm(
+                bounding_box.x_min as u32,
+                bounding_box.y_min as u32,
+                (face_width as u32).max(1),
+                (face_height as u32).max(1),
+            )
+            .to_luma8();
+
+        let (yaw, roll) = estimate_pose(detection);
+
+        FaceQuality {
+            face_size,
+            sharpness: laplacian_variance(&face),
+            brightness: mean_brightness(&face),
+            yaw,
+            roll,
+        }
+    }
+
+    pub fn check(&self, thresholds: &FaceQualityThresholds) -> Result<(), ZKNeuralError> {
+        if self.face_size < thresholds.min_face_size {
+            return Err(ZKNeuralError::FaceTooSmall);
+        }
+
+        if self.brightness < thresholds.min_brightness {
+            return Err(ZKNeuralError::FaceTooDark);
+        }
+
+        if self.brightness > thresholds.max_brightness {
+            return Err(ZKNeuralError::FaceTooBright);
+        }
+
+        if self.yaw.abs() > thresholds.max_yaw || self.roll.abs() > thresholds.max_roll {
+            return Err(ZKNeuralError::FacePoseOutOfRange);
+        }
+
+        if self.sharpness < thresholds.min_sharpness {
+            return Err(ZKNeuralError::FaceBlurry);
+        }
+
+        Ok(())
+    }
+}
+
+fn laplacian_variance(face: &GrayImage) -> f32 {
+    let resized =
+        image::imageops::resize(face, SHARPNESS_SCALE, SHARPNESS_SCALE, FilterType::Triangle);
+
+    let mut responses: Vec<f32> = vec![];
+    for y in 1..SHARPNESS_SCALE - 1 {
+        for x in 1..SHARPNESS_SCALE - 1 {
+            let pixel = |dx: i32, dy: i32| {
+                resized.get_pixel((x as i32 + dx) as u32, (y as i32 + dy) as u32)[0] as f32
+            };
+
+            responses
+                .push(pixel(0, -1) + pixel(-1, 0) + pixel(1, 0) + pixel(0, 1) - 4.0 * pixel(0, 0));
+        }
+    }
+
+    let mean = responses.iter().sum::<f32>() / responses.len() as f32;
+
+    responses.iter().map(|r| (r - mean).powi(2)).sum::<f32>() / responses.len() as f32
+}
+
+fn mean_brightness(face: &GrayImage) -> f32 {
+    let pixels = face.as_raw();
+
+    pixels.iter().map(|&p| p as f32).sum::<f32>() / pixels.len() as f32
+}
+
+/// Estimates yaw and roll in degrees from the eye, nose and ear keypoints.
+///
+/// Roll is the angle of the line between the eyes. Yaw is derived from the offset of the
+/// nose tip from the midpoint between the ears, along that line.
+fn estimate_pose(detection: &FaceDetection) -> (f32, f32) {
+    let keypoints = &detection.pixel_keypoints;
+    if keypoints.len() <= LEFT_EAR {
+        return (0.0, 0.0);
+    }
+
+    let right_eye = keypoints[RIGHT_EYE];
+    let left_eye = keypoints[LEFT_EYE];
+
+    let roll = (left_eye.y - right_eye.y)
+        .atan2(left_eye.x - right_eye.x)
+        .to_degrees();
+
+    let right_ear = keypoints[RIGHT_EAR];
+    let left_ear = keypoints[LEFT_EAR];
+    let nose = keypoints[NOSE_TIP];
+
+    let (axis_x, axis_y) = (left_ear.x - right_ear.x, left_ear.y - right_ear.y);
+    let half_span = (axis_x * axis_x + axis_y * axis_y).sqrt() / 2.0;
+    if half_span == 0.0 {
+        return (0.0, roll);
+    }
+
+    let (mid_x, mid_y) = (
+        (left_ear.x + right_ear.x) / 2.0,
+        (left_ear.y + right_ear.y) / 2.0,
+    );
+
+    let offset = ((nose.x - mid_x) * axis_x + (nose.y - mid_y) * axis_y) / (2.0 * half_span);
+    let yaw = (offset / half_span).atan().to_degrees();
+
+    (yaw, roll)
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{DynamicImage, GrayImage, Luma, imageops};
+
+    use crate::{
+        ZKNeuralError,
+        core::{
+            face_detection::{FaceBox, FaceDetection, FaceKeypoint},
+            face_quality::{FaceQuality, FaceQualityThresholds},
+        },
+    };
+
+    fn detection(bounding_box: FaceBox, keypoints: Vec<(f32, f32)>) -> FaceDetection {
+        let keypoints: Vec<FaceKeypoint> = keypoints
+            .into_iter()
+            .map(|(x, y)| FaceKeypoint { x, y })
+            .collect();
+
+        FaceDetection {
+            score: 1.0,
+            bounding_box,
+            pixel_bounding_box: bounding_box,
+            keypoints: keypoints.clone(),
+            pixel_keypoints: keypoints,
+        }
+    }
+
+    fn frontal_keypoints() -> Vec<(f32, f32)> {
+        vec![
+            (70.0, 80.0),
+            (130.0, 80.0),
+            (100.0, 110.0),
+            (100.0, 140.0),
+            (40.0, 90.0),
+            (160.0, 90.0),
+        ]
+    }
+
+    fn checkerboard(value: u8) -> DynamicImage {
+        DynamicImage::ImageLuma8(GrayImage::from_fn(200, 200, |x, y| {
+            if (x / 4 + y / 4) % 2 == 0 {
+                Luma([value])
+            } else {
+                Luma([value / 2])
+            }
+        }))
+    }
+
+    fn full_frame() -> FaceBox {
+        FaceBox {
+            x_min: 20.0,
+            y_min: 20.0,
+            x_max: 180.0,
+            y_max: 180.0,
+        }
+    }
+
+    #[test]
+    fn test_good_face_passes() {
+        let image = checkerboard(200);
+        let quality = FaceQuality::assess(&image, &detection(full_frame(), frontal_keypoints()));
+
+        assert!(quality.yaw.abs() < 1.0);
+        assert!(quality.roll.abs() < 1.0);
+
+        quality
+            .check(&FaceQualityThresholds::default())
+            .expect("Quality check failed");
+    }
+
+    #[test]
+    fn test_blurry_face_rejected() {
+        let image = checkerboard(200);
+        let blurred = DynamicImage::ImageLuma8(imageops::blur(&image.to_luma8(), 8.0));
+
+        let sharp = FaceQuality::assess(&image, &detection(full_frame(), frontal_keypoints()));
+        let blurry = FaceQuality::assess(&blurred, &detection(full_frame(), frontal_keypoints()));
+
+        assert!(blurry.sharpness < sharp.sharpness);
+        assert!(matches!(
+            blurry.check(&FaceQualityThresholds::default()),
+            Err(ZKNeuralError::FaceBlurry)
+        ));
+    }
+
+    #[test]
+    fn test_small_and_dark_faces_rejected() {
+        let small_box = FaceBox {
+            x_min: 90.0,
+            y_min: 90.0,
+            x_max: 110.0,
+            y_max: 110.0,
+        };
+
+        let small = FaceQuality::assess(
+            &checkerboard(200),
+            &detection(small_box, frontal_keypoints()),
+        );
+        assert!(matches!(
+            small.check(&FaceQualityThresholds::default()),
+            Err(ZKNeuralError::FaceTooSmall)
+        ));
+
+        let dark = FaceQuality::assess(
+            &checkerboard(40),
+            &detection(full_frame(), frontal_keypoints()),
+        );
+        assert!(matches!(
+            dark.check(&FaceQualityThresholds::default()),
+            Err(ZKNeuralError::FaceTooDark)
+        ));
+    }
+
+    #[test]
+    fn test_pose_rejected() {
+        let mut turned = frontal_keypoints();
+        turned[2] = (145.0, 110.0);
+
+        let quality = FaceQuality::assess(&checkerboard(200), &detection(full_frame(), turned));
+        assert!(quality.yaw > 30.0);
+        assert!(matches!(
+            quality.check(&FaceQualityThresholds::default()),
+            Err(ZKNeuralError::FacePoseOutOfRange)
+        ));
+
+        let mut tilted = frontal_keypoints();
+        tilted[1] = (130.0, 120.0);
+
+        let quality = FaceQuality::assess(&checkerboard(200), &detection(full_frame(), tilted));
+        assert!(quality.roll > 20.0);
+        assert!(matches!(
+            quality.check(&FaceQualityThresholds::default()),
+            Err(ZKNeuralError::FacePoseOutOfRange)
+        ));
+    }
+}
@@ -0,0 +1,514 @@
+use std::io::Cursor;
+
+use image::{DynamicImage, ImageDecoder, ImageError, ImageReader, RgbImage, RgbaImage};
+
+use crate::ZKNeuralError;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelFormat {
+    /// Packed 8-bit RGBA, 4 bytes per pixel.
+    Rgba,
+    /// Packed 8-bit BGRA, 4 bytes per pixel (iOS `kCVPixelFormatType_32BGRA`).
+    Bgra,
+    /// Packed 8-bit RGB, 3 bytes per pixel.
+    Rgb,
+    /// Y plane followed by an interleaved VU plane (Android `ImageFormat.NV21`).
+    Nv21,
+    /// Y plane followed by U and V planes of half the stride (I420).
+    Yuv420,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameRotation {
+    Rotation0,
+    Rotation90,
+    Rotation180,
+    Rotation270,
+}
+
+/// Describes the memory layout of a raw camera frame.
+///
+/// `stride` is the number of bytes per row of the first plane, `0` means the rows are
+/// tightly packed. `rotation` is the clockwise rotation to apply to get an upright image.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct RawFrameDescriptor {
+    pub width: u32,
+    pub height: u32,
+    pub stride: u32,
+    pub pixel_format: PixelFormat,
+    pub rotation: FrameRotation,
+}
+
+/// An input image, either encoded (JPEG, PNG, ...) or a raw camera frame.
+#[derive(Debug, Clone, Copy)]
+pub enum ImageSource<'a> {
+    Encoded(&'a [u8]),
+    Raw {
+        data: &'a [u8],
+        descriptor: RawFrameDescriptor,
+    },
+}
+
+impl<'a> From<&'a [u8]> for ImageSource<'a> {
+    fn from(data: &'a [u8]) -> Self {
+        ImageSource::Encoded(data)
+    }
+}
+
+impl<'a> From<&'a Vec<u8>> for ImageSource<'a> {
+    fn from(data: &'a Vec<u8>) -> Self {
+        ImageSource::Encoded(data)
+    }
+}
+
+impl<'a> From<&ImageSource<'a>> for ImageSource<'a> {
+    fn from(source: &ImageSource<'a>) -> Self {
+        *source
+    }
+}
+
+impl ImageSource<'_> {
+    /// Decodes the image, rotating and flipping encoded images according to their EXIF
+    /// orientation tag unless `apply_exif_orientation` is `false`.
+    ///
+    /// Raw frames carry their orientation in the descriptor, which is always applied.
+    pub fn decode(&self, apply_exif_orientation: bool) -> Result<DynamicImage, ZKNeuralError> {
+        match self {
+            ImageSource::Encoded(data) => decode_encoded_image(data, apply_exif_orientation),
+            ImageSource::Raw { data, descriptor } => decode_raw_frame(data, descriptor),
+        }
+    }
+}
+
+fn decode_encoded_image(
+    data: &[u8],
+    apply_exif_orientation: bool,
+) -> Result<DynamicImage, ZKNeuralError> {
+    if !apply_exif_orientation {
+        return Ok(image::load_from_memory(data)?);
+    }
+
+    let mut decoder = ImageReader::new(Cursor::new(data))
+        .with_guessed_format()
+        .map_err(ImageError::IoError)?
+        .into_decoder()?;
+
+    let orientation = decoder.orientation()?;
+
+    let mut decoded = DynamicImage::from_decoder(decoder)?;
+    decoded.apply_orientation(orientation);
+
+    Ok(decoded)
+}
+
+fn decode_raw_frame(
+    data: &[u8],
+    descriptor: &RawFrameDescriptor,
+) -> Result<DynamicImage, ZKNeuralError> {
+    let width = descriptor.width as usize;
+    let height = descriptor.height as usize;
+
+    if width == 0 || height == 0 {
+        return Err(ZKNeuralError::InvalidRawFrame(
+            "Frame width and height must be non-zero".to_string(),
+        ));
+    }
+
+    let decoded = match descriptor.pixel_format {
+        PixelFormat::Rgba | PixelFormat::Bgra | PixelFormat::Rgb => {
+            let bytes_per_pixel = match descriptor.pixel_format {
+                PixelFormat::Rgb => 3,
+                _ => 4,
+            };
+
+            let row_size = checked_size(width.checked_mul(bytes_per_pixel))?;
+            let stride = effective_stride(descriptor, row_size)?;
+            check_buffer_size(
+                data,
+                checked_size(
+                    stride
+                        .checked_mul(height - 1)
+                        .and_then(|size| size.checked_add(row_size)),
+                )?,
+            )?;
+
+            let mut pixels = Vec::with_capacity(checked_size(
+                width
+                    .checked_mul(height)
+                    .and_then(|size| size.checked_mul(4)),
+            )?);
+            for row in data.chunks(stride).take(height) {
+                for pixel in row[..row_size].chunks_exact(bytes_per_pixel) {
+                    match descriptor.pixel_format {
+                        PixelFormat::Rgba => pixels.extend_from_slice(pixel),
+                        PixelFormat::Bgra => {
+                            pixels.extend_from_slice(&[pixel[2], pixel[1], pixel[0], pixel[3]])
+                        }
+                        _ => pixels.extend_from_slice(&[pixel[0], pixel[1], pixel[2], 255]),
+                    }
+                }
+            }
+
+            DynamicImage::ImageRgba8(
+                RgbaImage::from_raw(width as u32, height as u32, pixels)
+                    .expect("Pixel buffer size matches frame dimensions"),
+            )
+        }
+        PixelFormat::Nv21 | PixelFormat::Yuv420 => {
+            let stride = effective_stride(descriptor, width)?;
+            let chroma_width = width.div_ceil(2);
+            let chroma_height = height.div_ceil(2);
+
+            let luma_size = checked_size(stride.checked_mul(height))?;
+            let (chroma_stride, chroma_size) = match descriptor.pixel_format {
+                PixelFormat::Nv21 => (
+                    stride,
+                    stride
+                        .checked_mul(chroma_height - 1)
+                        .and_then(|size| size.checked_add(chroma_width * 2)),
+                ),
+                _ => {
+                    let chroma_stride = stride.div_ceil(2);
+                    (
+                        chroma_stride,
+                        chroma_stride
+                            .checked_mul(chroma_height)
+                            .and_then(|size| size.checked_mul(2)),
+                    )
+                }
+            };
+
+            check_buffer_size(
+                data,
+                checked_size(chroma_size.and_then(|size| size.checked_add(luma_size)))?,
+            )?;
+
+            let mut pixels = Vec::with_capacity(checked_size(
+                width
+                    .checked_mul(height)
+                    .and_then(|size| size.checked_mul(3)),
+            )?);
+            for y in 0..height {
+                for x in 0..width {
+                    let luma = data[y * stride + x];
+
+                    let (u, v) = match descriptor.pixel_format {
+                        PixelFormat::Nv21 => {
+                            let offset = luma_size + (y / 2) * chroma_stride + (x / 2) * 2;
+                            (data[offset + 1], data[offset])
+                        }
+                        _ => {
+                            let offset = (y / 2) * chroma_stride + x / 2;
+                            let v_plane = luma_size + chroma_stride * chroma_height;
+                            (data[luma_size + offset], data[v_plane + offset])
+                        }
+                    };
+
+                    pixels.extend_from_slice(&yuv_to_rgb(luma, u, v));
+                }
+            }
+
+            DynamicImage::ImageRgb8(
+                RgbImage::from_raw(width as u32, height as u32, pixels)
+                    .expect("Pixel buffer size matches frame dimensions"),
+            )
+        }
+    };
+
+    Ok(match descriptor.rotation {
+        FrameRotation::Rotation0 => decoded,
+        FrameRotation::Rotation90 => decoded.rotate90(),
+        FrameRotation::Rotation180 => decoded.rotate180(),
+        FrameRotation::Rotation270 => decoded.rotate270(),
+    })
+}
+
+fn effective_stride(
+    descriptor: &RawFrameDescriptor,
+    row_size: usize,
+) -> Result<usize, ZKNeuralError> {
+    if descriptor.stride == 0 {
+        return Ok(row_size);
+    }
+
+    let stride = descriptor.stride as usize;
+    if stride < row_size {
+        return Err(ZKNeuralError::InvalidRawFrame(format!(
+            "Stride {stride} is smaller than the row size {row_size}"
+        )));
+    }
+
+    Ok(stride)
+}
+
+/// Fails the frame if computing one of its sizes overflowed.
+fn checked_size(size: Option<usize>) -> Result<usize, ZKNeuralError> {
+    size.ok_or_else(|| {
+        ZKNeuralError::InvalidRawFrame("Frame dimensions overflow the buffer size".to_string())
+    })
+}
+
+fn check_buffer_size(data: &[u8], expected_size: usize) -> Result<(), ZKNeuralError> {
+    if data.len() < expected_size {
+        return Err(ZKNeuralError::InvalidRawFrame(format!(
+            "Buffer has {} bytes, expected at least {expected_size}",
+            data.len()
+        )));
+    }
+
+    Ok(())
+}
+
+/// Converts a full-range BT.601 YUV sample to RGB, as produced by iOS and Android cameras.
+fn yuv_to_rgb(y: u8, u: u8, v: u8) -> [u8; 3] {
+    let y = y as f32;
+    let u = u as f32 - 128.0;
+    let v = v as f32 - 128.0;
+
+    let r = y + 1.402 * v;
+    let g = y - 0.344_136 * u - 0.714_136 * v;
+    let b = y + 1.772 * u;
+
+    [
+        r.round().clamp(0.0, 255.0) as u8,
+        g.round().clamp(0.0, 255.0) as u8,
+        b.round().clamp(0.0, 255.0) as u8,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use image::{DynamicImage, GenericImageView, ImageFormat, Rgb, RgbImage};
+
+    use crate::{
+        ZKNeuralError,
+        core::image_source::{FrameRotation, ImageSource, PixelFormat, RawFrameDescriptor},
+    };
+
+    fn descriptor(
+        width: u32,
+        height: u32,
+        stride: u32,
+        pixel_format: PixelFormat,
+        rotation: FrameRotation,
+    ) -> RawFrameDescriptor {
+        RawFrameDescriptor {
+            width,
+            height,
+            stride,
+            pixel_format,
+            rotation,
+        }
+    }
+
+    #[test]
+    fn test_bgra_with_padding() {
+        // 2x2 frame, 12-byte stride: red, green / blue, white, with 4 bytes of row padding.
+        let data = [
+            0, 0, 255, 255, 0, 255, 0, 255, 9, 9, 9, 9, //
+            255, 0, 0, 255, 255, 255, 255, 255, 9, 9, 9, 9,
+        ];
+
+        let source = ImageSource::Raw {
+            data: &data,
+            descriptor: descriptor(2, 2, 12, PixelFormat::Bgra, FrameRotation::Rotation0),
+        };
+
+        let image = source.decode(true).unwrap();
+
+        assert_eq!(image.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(1, 0).0, [0, 255, 0, 255]);
+        assert_eq!(image.get_pixel(0, 1).0, [0, 0, 255, 255]);
+        assert_eq!(image.get_pixel(1, 1).0, [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn test_rotation() {
+        // 2x1 RGB frame: red, green. Rotated by 90 degrees it becomes 1x2: red over green.
+        let data = [255, 0, 0, 0, 255, 0];
+
+        let source = ImageSource::Raw {
+            data: &data,
+            descriptor: descriptor(2, 1, 0, PixelFormat::Rgb, FrameRotation::Rotation90),
+        };
+
+        let image = source.decode(true).unwrap();
+
+        assert_eq!(image.dimensions(), (1, 2));
+        assert_eq!(image.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(0, 1).0, [0, 255, 0, 255]);
+    }
+
+    #[test]
+    fn test_nv21_and_yuv420_agree() {
+        let (width, height) = (4usize, 2usize);
+        let luma: Vec<u8> = (0..width * height).map(|i| (i * 30) as u8).collect();
+        let (u, v) = ([90u8, 200u8], [240u8, 60u8]);
+
+        let mut nv21 = luma.clone();
+        for i in 0..2 {
+            nv21.extend_from_slice(&[v[i], u[i]]);
+        }
+
+        let mut yuv420 = luma.clone();
+        yuv420.extend_from_slice(&u);
+        yuv420.extend_from_slice(&v);
+
+        let nv21_image = ImageSource::Raw {
+            data: &nv21,
+            descriptor: descriptor(4, 2, 0, PixelFormat::Nv21, FrameRotation::Rotation0),
+        }
+        .decode(true)
+        .unwrap();
+
+        let yuv420_image = ImageSource::Raw {
+            data: &yuv420,
+            descriptor: descriptor(4, 2, 0, PixelFormat::Yuv420, FrameRotation::Rotation0),
+        }
+        .decode(true)
+        .unwrap();
+
+        assert_eq!(nv21_image.to_rgb8(), yuv420_image.to_rgb8());
+    }
+
+    #[test]
+    fn test_short_buffer() {
+        let data = [0u8; 10];
+
+        let source = ImageSource::Raw {
+            data: &data,
+            descriptor: descriptor(2, 2, 0, PixelFormat::Rgba, FrameRotation::Rotation0),
+        };
+
+        assert!(source.decode(true).is_err());
+    }
+
+    #[test]
+    fn test_overflowing_dimensions() {
+        let data = [0u8; 16];
+
+        for descriptor in [
+            descriptor(
+                u32::MAX,
+                u32::MAX,
+                0,
+                PixelFormat::Rgba,
+                FrameRotation::Rotation0,
+            ),
+            descriptor(
+                u32::MAX,
+                u32::MAX,
+                u32::MAX,
+                PixelFormat::Nv21,
+                FrameRotation::Rotation0,
+            ),
+            descriptor(
+                u32::MAX,
+                u32::MAX,
+                u32::MAX,
+                PixelFormat::Yuv420,
+                FrameRotation::Rotation0,
+            ),
+        ] {
+            let source = ImageSource::Raw {
+                data: &data,
+                descriptor,
+            };
+
+            assert!(matches!(
+                source.decode(true),
+                Err(ZKNeuralError::InvalidRawFrame(_))
+            ));
+        }
+    }
+
+    const RED: [u8; 3] = [255, 0, 0];
+    const GREEN: [u8; 3] = [0, 255, 0];
+    const BLUE: [u8; 3] = [0, 0, 255];
+    const WHITE: [u8; 3] = [255, 255, 255];
+
+    /// Encodes a 64x32 JPEG with red, green, blue and white quadrants and an EXIF orientation tag.
+    fn jpeg_with_orientation(orientation: u16) -> Vec<u8> {
+        let image = RgbImage::from_fn(64, 32, |x, y| match (x < 32, y < 16) {
+            (true, true) => Rgb(RED),
+            (false, true) => Rgb(GREEN),
+            (true, false) => Rgb(BLUE),
+            (false, false) => Rgb(WHITE),
+        });
+
+        let mut jpeg = Vec::new();
+        DynamicImage::ImageRgb8(image)
+            .write_to(&mut Cursor::new(&mut jpeg), ImageFormat::Jpeg)
+            .unwrap();
+
+        let mut exif = b"Exif\0\0MM\0\x2a\0\0\0\x08\0\x01\x01\x12\0\x03\0\0\0\x01".to_vec();
+        exif.extend_from_slice(&orientation.to_be_bytes());
+        exif.extend_from_slice(&[0, 0, 0, 0, 0, 0]);
+
+        let mut segment = vec![0xff, 0xe1];
+        segment.extend_from_slice(&(exif.len() as u16 + 2).to_be_bytes());
+        segment.extend_from_slice(&exif);
+
+        jpeg.splice(2..2, segment);
+        jpeg
+    }
+
+    fn quadrant_colors(image: &DynamicImage) -> [[u8; 3]; 4] {
+        let (width, height) = image.dimensions();
+        let rgb = image.to_rgb8();
+
+        let (left, right) = (width / 4, width * 3 / 4);
+        let (top, bottom) = (height / 4, height * 3 / 4);
+
+        let color = |x: u32, y: u32| {
+            rgb.get_pixel(x, y)
+                .0
+                .map(|channel| if channel > 127 { 255 } else { 0 })
+        };
+
+        [
+            color(left, top),
+            color(right, top),
+            color(left, bottom),
+            color(right, bottom),
+        ]
+    }
+
+    #[test]
+    fn test_exif_orientations() {
+        let expected = [
+            (1, (64, 32), [RED, GREEN, BLUE, WHITE]),
+            (2, (64, 32), [GREEN, RED, WHITE, BLUE]),
+            (3, (64, 32), [WHITE, BLUE, GREEN, RED]),
+            (4, (64, 32), [BLUE, WHITE, RED, GREEN]),
+            (5, (32, 64), [RED, BLUE, GREEN, WHITE]),
+            (6, (32, 64), [BLUE, RED, WHITE, GREEN]),
+            (7, (32, 64), [WHITE, GREEN, BLUE, RED]),
+            (8, (32, 64), [GREEN, WHITE, RED, BLUE]),
+        ];
+
+        for (orientation, dimensions, colors) in expected {
+            let jpeg = jpeg_with_orientation(orientation);
+
+            let image = ImageSource::Encoded(&jpeg).decode(true).unwrap();
+
+            assert_eq!(image.dimensions(), dimensions, "orientation {orientation}");
+            assert_eq!(quadrant_colors(&image), colors, "orientation {orientation}");
+        }
+    }
+
+    #[test]
+    fn test_exif_orientation_opt_out() {
+        let jpeg = jpeg_with_orientation(6);
+
+        let image = ImageSource::Encoded(&jpeg).decode(false).unwrap();
+
+        assert_eq!(image.dimensions(), (64, 32));
+        assert_eq!(quadrant_colors(&image), [RED, GREEN, BLUE, WHITE]);
+    }
+}
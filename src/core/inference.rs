@@ -0,0 +1,147 @@
+use std::{str::FromStr, sync::Arc};
+
+use serde::Serialize;
+
+use crate::ZKNeuralError;
+
+/// Element type of a model tensor.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DataType {
+    Bool,
+    Uint8,
+    Int8,
+    Int16,
+    Int32,
+    Int64,
+    Float16,
+    Float32,
+    Float64,
+}
+
+impl FromStr for DataType {
+    type Err = ZKNeuralError;
+
+    /// Parses the lowercase name used by TFLite and TensorFlow.js, e.g. `float32`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bool" => Ok(DataType::Bool),
+            "uint8" => Ok(DataType::Uint8),
+            "int8" => Ok(DataType::Int8),
+            "int16" => Ok(DataType::Int16),
+            "int32" => Ok(DataType::Int32),
+            "int64" => Ok(DataType::Int64),
+            "float16" => Ok(DataType::Float16),
+            "float32" => Ok(DataType::Float32),
+            "float64" => Ok(DataType::Float64),
+            _ => Err(ZKNeuralError::InvalidTensor(format!(
+                "unknown data type `{s}`"
+            ))),
+        }
+    }
+}
+
+/// Dimensions of a model tensor.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct Shape {
+    dimensions: Vec<usize>,
+}
+
+impl Shape {
+    pub fn new(dimensions: Vec<usize>) -> Self {
+        Shape { dimensions }
+    }
+
+    pub fn dimensions(&self) -> &[usize] {
+        &self.dimensions
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct TensorSpec {
+    pub shape: Shape,
+    pub data_type: DataType,
+}
+
+/// A model output with its elements as little-endian bytes.
+#[derive(Debug, Clone)]
+pub struct Tensor {
+    pub spec: TensorSpec,
+    pub data: Vec<u8>,
+}
+
+/// A loaded model, valid for the duration of `InferenceBackend::with_session` or as long as
+/// the session returned by `InferenceBackend::load` is kept.
+pub trait InferenceSession {
+    /// Returns the spec of the first model input.
+    fn input(&self) -> Result<TensorSpec, ZKNeuralError>;
+
+    /// Runs the model on the little-endian bytes of its first input and returns every output.
+    fn invoke(&self, input: &[u8]) -> Result<Vec<Tensor>, ZKNeuralError>;
+}
+
+/// Runtime the models are executed with, TFLite natively and a JS-provided runtime on
+/// WebAssembly.
+pub trait InferenceBackend: Send + Sync {
+    /// Loads the model and passes the session to `f`, so several inputs can be invoked on
+    /// a single load.
+    fn with_session(
+        &self,
+        model: &[u8],
+        f: &mut dyn FnMut(&dyn InferenceSession) -> Result<(), ZKNeuralError>,
+    ) -> Result<(), ZKNeuralError>;
+
+    /// Loads a model into a session the caller keeps, e.g. a detector invoked on every camera
+    /// frame. Backends that cannot keep a session return `None` and are used through
+    /// `with_session` instead.
+    fn load(
+        &self,
+        _model: &'static [u8],
+    ) -> Result<Option<Box<dyn InferenceSession + Send>>, ZKNeuralError> {
+        Ok(None)
+    }
+}
+
+impl dyn InferenceBackend {
+    /// `with_session` returning the result of `f`.
+    pub fn session<T>(
+        &self,
+        model: &[u8],
+        f: impl FnOnce(&dyn InferenceSession) -> Result<T, ZKNeuralError>,
+    ) -> Result<T, ZKNeuralError> {
+        let mut f = Some(f);
+        let mut result = None;
+
+        self.with_session(model, &mut |session| {
+            let f = f.take().expect("Session callback is called once");
+
+            result = Some(f(session)?);
+
+            Ok(())
+        })?;
+
+        result.ok_or_else(|| {
+            ZKNeuralError::InferenceFailed("backend did not open a session".to_string())
+        })
+    }
+}
+
+/// Returns the output at `index`, failing if the model has fewer outputs.
+pub fn output(outputs: &[Tensor], index: usize) -> Result<&Tensor, ZKNeuralError> {
+    outputs
+        .get(index)
+        .ok_or_else(|| ZKNeuralError::InvalidTensor(format!("model has no output {index}")))
+}
+
+/// The backend models run with unless one is passed explicitly.
+#[cfg(feature = "tflite")]
+pub fn default_backend() -> Result<Arc<dyn InferenceBackend>, ZKNeuralError> {
+    Ok(Arc::new(crate::core::tflite::TfliteBackend))
+}
+
+/// The backend models run with unless one is passed explicitly.
+#[cfg(not(feature = "tflite"))]
+pub fn default_backend() -> Result<Arc<dyn InferenceBackend>, ZKNeuralError> {
+    Err(ZKNeuralError::InferenceBackendNotSet)
+}
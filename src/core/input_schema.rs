@@ -0,0 +1,308 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{ZKNeuralError, core::signal_encoding::FieldModulus};
+
+/// Name the `BionettaGenericInputs` layout is registered under on every `TensorInvoker`.
+pub const BIONETTA_GENERIC_SCHEMA: &str = "bionetta_generic";
+
+/// Where the value of a circuit signal comes from.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "source", rename_all = "snake_case")]
+pub enum SignalSource {
+    /// Model output encoded with the invoker's `SignalEncoding`.
+    ModelOutput,
+    /// Signals of the model input tensor.
+    ImageSignals,
+    /// Value provided by the caller under the signal name.
+    Caller,
+    /// Fixed decimal field element, or a possibly nested array of them.
+    Constant { value: Value },
+    /// Blinding value, supplied by the caller or drawn from the OS CSPRNG.
+    Rand,
+    /// `1` for UltraGroth and `0` for Groth.
+    UltraGroth,
+}
+
+/// A named circuit signal and its source.
+///
+/// `required` only applies to caller-provided signals, a missing optional signal is left out
+/// of the inputs. Every other source is always available.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SignalSpec {
+    pub name: String,
+    #[serde(flatten)]
+    pub source: SignalSource,
+    #[serde(default = "default_required")]
+    pub required: bool,
+}
+
+fn default_required() -> bool {
+    true
+}
+
+/// Layout of the JSON inputs of a circuit.
+///
+/// ```json
+/// {"signals": [
+///     {"name": "embedding", "source": "model_output"},
+///     {"name": "challenge", "source": "caller"},
+///     {"name": "version", "source": "constant", "value": "2"}
+/// ]}
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InputSchema {
+    pub signals: Vec<SignalSpec>,
+}
+
+/// Values the signals of an `InputSchema` are resolved from.
+pub struct SignalValues {
+    pub features: Vec<String>,
+    pub image: Vec<String>,
+    pub caller: Map<String, Value>,
+    pub rand: String,
+    pub ultra_groth: Option<String>,
+}
+
+impl InputSchema {
+    pub fn from_json(json: &[u8]) -> Result<Self, ZKNeuralError> {
+        let schema: InputSchema = serde_json::from_slice(json)?;
+
+        schema.validate()?;
+
+        Ok(schema)
+    }
+
+    /// The layout of `BionettaGenericInputs`.
+    pub fn bionetta_generic() -> Self {
+        let signal = |name: &str, source| SignalSpec {
+            name: name.to_string(),
+            source,
+            required: true,
+        };
+
+        InputSchema {
+            signals: vec![
+                signal("ultra_groth", SignalSource::UltraGroth),
+                signal("address", SignalSource::Caller),
+                signal("threshold", SignalSource::Caller),
+                signal("nonce", SignalSource::Caller),
+                signal("features", SignalSource::ModelOutput),
+                signal("image", SignalSource::ImageSignals),
+                signal("rand", SignalSource::Rand),
+            ],
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), ZKNeuralError> {
+        let mut names = HashSet::new();
+
+        for signal in &self.signals {
+            if signal.name.is_empty() {
+                return Err(ZKNeuralError::InvalidInputSchema(
+                    "signal names must not be empty".to_string(),
+                ));
+            }
+
+            if !names.insert(signal.name.as_str()) {
+                return Err(ZKNeuralError::InvalidInputSchema(format!(
+                    "signal `{}` is defined more than once",
+                    signal.name
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Resolves every signal of the schema into the JSON object passed to witness generation.
+    ///
+    /// Caller values and constants must be decimal field elements, caller values that are not
+    /// part of the schema are rejected.
+    pub fn build(
+        &self,
+        values: SignalValues,
+        field_modulus: &FieldModulus,
+    ) -> Result<Map<String, Value>, ZKNeuralError> {
+        if let Some(name) = values.caller.keys().find(|name| !self.accepts_caller(name)) {
+            return Err(ZKNeuralError::UnknownSignal(name.clone()));
+        }
+
+        let mut inputs = Map::new();
+
+        for signal in &self.signals {
+            let value = match &signal.source {
+                SignalSource::ModelOutput => Value::from(values.features.clone()),
+                SignalSource::ImageSignals => Value::from(values.image.clone()),
+                SignalSource::Caller => match values.caller.get(&signal.name) {
+                    Some(value) => field_elements(value, field_modulus)?,
+                    None if signal.required => {
+                        return Err(ZKNeuralError::MissingSignal(signal.name.clone()));
+                    }
+                    None => continue,
+                },
+                SignalSource::Constant { value } => field_elements(value, field_modulus)?,
+                SignalSource::Rand => Value::from(values.rand.clone()),
+                SignalSource::UltraGroth => Value::from(
+                    values
+                        .ultra_groth
+                        .clone()
+                        .ok_or(ZKNeuralError::ProvingTypeNotSet)?,
+                ),
+            };
+
+            inputs.insert(signal.name.clone(), value);
+        }
+
+        Ok(inputs)
+    }
+
+    fn accepts_caller(&self, name: &str) -> bool {
+        self.signals
+            .iter()
+            .any(|signal| signal.name == name && signal.source == SignalSource::Caller)
+    }
+}
+
+/// Checks that the value is a field element or a nested array of them, emitting numbers as
+/// decimal strings.
+fn field_elements(value: &Value, field_modulus: &FieldModulus) -> Result<Value, ZKNeuralError> {
+    match value {
+        Value::String(element) => {
+            field_modulus.parse_element(element)?;
+
+            Ok(value.clone())
+        }
+        Value::Number(number) => {
+            let element = number.to_string();
+
+            field_modulus.parse_element(&element)?;
+
+            Ok(Value::from(element))
+        }
+        Value::Array(elements) => elements
+            .iter()
+            .map(|element| field_elements(element, field_modulus))
+            .collect(),
+        _ => Err(ZKNeuralError::InvalidFieldElement(value.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Map, Value, json};
+
+    use crate::{
+        ZKNeuralError,
+        core::{
+            input_schema::{InputSchema, SignalValues},
+            signal_encoding::FieldModulus,
+            tensor::BionettaGenericInputs,
+        },
+    };
+
+    fn signal_values(caller: Value) -> SignalValues {
+        let Value::Object(caller) = caller else {
+            panic!("Expected a JSON object");
+        };
+
+        SignalValues {
+            features: vec!["1".to_string(), "2".to_string()],
+            image: vec!["3".to_string()],
+            caller,
+            rand: "42".to_string(),
+            ultra_groth: Some("0".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_custom_schema() {
+        let schema = InputSchema::from_json(
+            br#"{"signals": [
+                {"name": "embedding", "source": "model_output"},
+                {"name": "challenge", "source": "caller"},
+                {"name": "salt", "source": "caller", "required": false},
+                {"name": "version", "source": "constant", "value": ["2", 3]},
+                {"name": "blinding", "source": "rand"}
+            ]}"#,
+        )
+        .unwrap();
+
+        let inputs = schema
+            .build(
+                signal_values(json!({"challenge": 7})),
+                &FieldModulus::bn254(),
+            )
+            .unwrap();
+
+        assert_eq!(
+            Value::Object(inputs),
+            json!({
+                "embedding": ["1", "2"],
+                "challenge": "7",
+                "version": ["2", "3"],
+                "blinding": "42"
+            })
+        );
+    }
+
+    #[test]
+    fn test_schema_validation() {
+        assert!(matches!(
+            InputSchema::from_json(
+                br#"{"signals": [
+                    {"name": "a", "source": "rand"},
+                    {"name": "a", "source": "model_output"}
+                ]}"#
+            ),
+            Err(ZKNeuralError::InvalidInputSchema(_))
+        ));
+        assert!(InputSchema::from_json(br#"{"signals": [{"name": "a", "source": "x"}]}"#).is_err());
+
+        let schema = InputSchema::bionetta_generic();
+        let field_modulus = FieldModulus::bn254();
+
+        assert!(matches!(
+            schema.build(
+                signal_values(json!({"address": "1", "threshold": "2"})),
+                &field_modulus
+            ),
+            Err(ZKNeuralError::MissingSignal(name)) if name == "nonce"
+        ));
+        assert!(matches!(
+            schema.build(
+                signal_values(json!({"address": "1", "threshold": "2", "nonce": "3", "x": "4"})),
+                &field_modulus
+            ),
+            Err(ZKNeuralError::UnknownSignal(name)) if name == "x"
+        ));
+        assert!(matches!(
+            schema.build(
+                signal_values(json!({"address": "1", "threshold": "2", "nonce": "-3"})),
+                &field_modulus
+            ),
+            Err(ZKNeuralError::InvalidFieldElement(_))
+        ));
+    }
+
+    #[test]
+    fn test_bionetta_generic_schema() {
+        let inputs = InputSchema::bionetta_generic()
+            .build(
+                signal_values(json!({"address": "1", "threshold": "2", "nonce": "3"})),
+                &FieldModulus::bn254(),
+            )
+            .unwrap();
+
+        let generic_inputs: BionettaGenericInputs =
+            serde_json::from_value(Value::Object(inputs.clone())).unwrap();
+        assert_eq!(generic_inputs.features, vec!["1", "2"]);
+        assert_eq!(generic_inputs.rand, "42");
+
+        let expected: Map<String, Value> =
+            serde_json::from_value(serde_json::to_value(&generic_inputs).unwrap()).unwrap();
+        assert_eq!(inputs, expected);
+    }
+}
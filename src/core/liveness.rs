@@ -0,0 +1,96 @@
+use image::DynamicImage;
+use tracing::debug;
+
+use crate::{ZKNeuralError, core::tensor::TensorInvoker};
+
+const LIVENESS_DEFAULT_LIVE_CLASS_INDEX: usize = 0;
+
+/// Passive liveness stage backed by a user-supplied TFLite anti-spoofing model.
+///
+/// The model is loaded the same way as any other `TensorInvoker` model and runs on the
+/// preprocessed face crop. Models with a single output must emit the probability of a live
+/// face in `[0, 1]`, other values such as raw quantized outputs are rejected. Models with
+/// several outputs are treated as classifiers where `live_class_index` selects the live class,
+/// with softmax applied when the outputs are not yet probabilities.
+pub struct LivenessChecker {
+    pub invoker: TensorInvoker,
+    pub threshold: f32,
+    pub live_class_index: usize,
+}
+
+impl LivenessChecker {
+    pub fn new(model_data: &[u8], threshold: f32) -> Result<Self, ZKNeuralError> {
+        Ok(LivenessChecker {
+            invoker: TensorInvoker::new(model_data, false)?,
+            threshold,
+            live_class_index: LIVENESS_DEFAULT_LIVE_CLASS_INDEX,
+        })
+    }
+
+    pub fn score(&self, face_image: &DynamicImage) -> Result<f32, ZKNeuralError> {
+        let (data, _) = self.invoker.prepare_loaded_image(face_image)?;
+
+        let serialized_outputs = self.invoker.fire(&data)?;
+
+        let outputs: Vec<f64> = serde_json::from_slice(&serialized_outputs)?;
+
+        live_probability(&outputs, self.live_class_index)
+    }
+
+    /// Returns the liveness score, or `LivenessCheckFailed` if it is below the threshold.
+    pub fn check(&self, face_image: &DynamicImage) -> Result<f32, ZKNeuralError> {
+        let score = self.score(face_image)?;
+
+        debug!(score, threshold = self.threshold, "liveness scored");
+
+        if score < self.threshold {
+            return Err(ZKNeuralError::LivenessCheckFailed(score));
+        }
+
+        Ok(score)
+    }
+}
+
+fn live_probability(outputs: &[f64], live_class_index: usize) -> Result<f32, ZKNeuralError> {
+    match outputs.len() {
+        0 => Err(ZKNeuralError::InvalidLivenessOutput),
+        1 if (0.0..=1.0).contains(&outputs[0]) => Ok(outputs[0] as f32),
+        1 => Err(ZKNeuralError::InvalidLivenessOutput),
+        _ => {
+            let live_output = outputs
+                .get(live_class_index)
+                .ok_or(ZKNeuralError::InvalidLivenessOutput)?;
+
+            let is_distribution = outputs.iter().all(|&p| (0.0..=1.0).contains(&p))
+                && (outputs.iter().sum::<f64>() - 1.0).abs() < 1e-3;
+
+            if is_distribution {
+                return Ok(*live_output as f32);
+            }
+
+            let max_output = outputs.iter().cloned().fold(f64::MIN, f64::max);
+            let exp_sum: f64 = outputs.iter().map(|&o| (o - max_output).exp()).sum();
+
+            Ok(((live_output - max_output).exp() / exp_sum) as f32)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::liveness::live_probability;
+
+    #[test]
+    fn test_live_probability() {
+        assert_eq!(live_probability(&[0.75], 0).unwrap(), 0.75);
+        assert_eq!(live_probability(&[0.2, 0.8], 1).unwrap(), 0.8);
+
+        let from_logits = live_probability(&[2.0, 0.0], 0).unwrap();
+        assert!((from_logits - 0.880_797).abs() < 1e-5);
+
+        assert!(live_probability(&[], 0).is_err());
+        assert!(live_probability(&[200.0], 0).is_err());
+        assert!(live_probability(&[f64::NAN], 0).is_err());
+        assert!(live_probability(&[0.2, 0.8], 2).is_err());
+    }
+}
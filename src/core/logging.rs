@@ -0,0 +1,327 @@
+//! Forwards the `tracing` spans and events of the library to a host log callback.
+//!
+//! Rust hosts can install any subscriber instead. Events carry stage timings, buffer sizes,
+//! model shapes and detection scores, never image content or embeddings.
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    ffi::CString,
+    fmt::{self, Write},
+    sync::{
+        Mutex, OnceLock, RwLock,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Instant,
+};
+
+use tracing::{
+    Event, Level, Metadata, Subscriber,
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+    subscriber::Interest,
+};
+
+use crate::core::callbacks::LogCallback;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ZKNeuralLogLevel {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl From<&Level> for ZKNeuralLogLevel {
+    fn from(level: &Level) -> Self {
+        match *level {
+            Level::ERROR => ZKNeuralLogLevel::Error,
+            Level::WARN => ZKNeuralLogLevel::Warn,
+            Level::INFO => ZKNeuralLogLevel::Info,
+            Level::DEBUG => ZKNeuralLogLevel::Debug,
+            _ => ZKNeuralLogLevel::Trace,
+        }
+    }
+}
+
+static LOGGER: RwLock<Option<(LogCallback, ZKNeuralLogLevel)>> = RwLock::new(None);
+
+static SUBSCRIBER_INSTALLED: OnceLock<bool> = OnceLock::new();
+
+thread_local! {
+    static ENTERED_SPANS: RefCell<Vec<u64>> = const { RefCell::new(vec![]) };
+}
+
+/// Routes messages up to `max_level` to `callback`, replacing the previous callback. `None`
+/// or `Off` stops the forwarding.
+///
+/// The global `tracing` subscriber is only installed for a callback, so a host that never
+/// sets one keeps the slot free for its own. Returns `false` if another global subscriber was
+/// installed first, the callback then never receives anything.
+pub fn set_log_callback(callback: Option<LogCallback>, max_level: ZKNeuralLogLevel) -> bool {
+    let logger = callback
+        .filter(|_| max_level != ZKNeuralLogLevel::Off)
+        .map(|callback| (callback, max_level));
+
+    let installed = logger.is_none()
+        || *SUBSCRIBER_INSTALLED.get_or_init(|| {
+            tracing::subscriber::set_global_default(CallbackSubscriber::default()).is_ok()
+        });
+
+    *LOGGER.write().expect("Logger lock is poisoned") = logger;
+
+    installed
+}
+
+fn enabled(level: &Level) -> Option<LogCallback> {
+    let logger = LOGGER.read().expect("Logger lock is poisoned");
+
+    logger
+        .filter(|(_, max_level)| ZKNeuralLogLevel::from(level) <= *max_level)
+        .map(|(callback, _)| callback)
+}
+
+fn emit(level: &Level, target: &str, message: String) {
+    let Some(callback) = enabled(level) else {
+        return;
+    };
+
+    let target = CString::new(target.replace('\0', "")).unwrap_or_default();
+    let message = CString::new(message.replace('\0', "")).unwrap_or_default();
+
+    unsafe { callback(level.into(), target.as_ptr(), message.as_ptr()) }
+}
+
+struct SpanState {
+    name: &'static str,
+    target: &'static str,
+    level: Level,
+    fields: String,
+    start: Instant,
+    references: usize,
+}
+
+/// Formats spans and events like `drain_generic_inputs:fire: model invoked outputs=1`, and
+/// logs `<span>{fields}: completed in <duration>` when a span closes.
+#[derive(Default)]
+struct CallbackSubscriber {
+    next_id: AtomicU64,
+    spans: Mutex<HashMap<u64, SpanState>>,
+}
+
+impl CallbackSubscriber {
+    fn spans(&self) -> std::sync::MutexGuard<'_, HashMap<u64, SpanState>> {
+        self.spans.lock().expect("Span lock is poisoned")
+    }
+}
+
+impl Subscriber for CallbackSubscriber {
+    fn register_callsite(&self, _metadata: &'static Metadata<'static>) -> Interest {
+        // The level filter changes at runtime, so `enabled` is asked on every call.
+        Interest::sometimes()
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        enabled(metadata.level()).is_some()
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+
+        let mut visitor = FieldVisitor::default();
+        span.record(&mut visitor);
+
+        let metadata = span.metadata();
+
+        self.spans().insert(
+            id,
+            SpanState {
+                name: metadata.name(),
+                target: metadata.target(),
+                level: *metadata.level(),
+                fields: visitor.fields,
+                start: Instant::now(),
+                references: 1,
+            },
+        );
+
+        Id::from_u64(id)
+    }
+
+    fn record(&self, span: &Id, values: &Record<'_>) {
+        let mut visitor = FieldVisitor::default();
+        values.record(&mut visitor);
+
+        if let Some(state) = self.spans().get_mut(&span.into_u64()) {
+            state.fields.push_str(&visitor.fields);
+        }
+    }
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+
+        let mut message = ENTERED_SPANS.with_borrow(|entered| {
+            let spans = self.spans();
+
+            let mut path = String::new();
+
+            for state in entered.iter().filter_map(|id| spans.get(id)) {
+                let _ = write!(path, "{}:", state.name);
+            }
+
+            path
+        });
+
+        if !message.is_empty() {
+            message.replace_range(message.len() - 1.., ": ");
+        }
+
+        message.push_str(&visitor.message);
+        message.push_str(&visitor.fields);
+
+        let metadata = event.metadata();
+
+        emit(metadata.level(), metadata.target(), message);
+    }
+
+    fn enter(&self, span: &Id) {
+        ENTERED_SPANS.with_borrow_mut(|entered| entered.push(span.into_u64()));
+    }
+
+    fn exit(&self, span: &Id) {
+        ENTERED_SPANS.with_borrow_mut(|entered| {
+            if let Some(position) = entered.iter().rposition(|id| *id == span.into_u64()) {
+                entered.remove(position);
+            }
+        });
+    }
+
+    fn clone_span(&self, span: &Id) -> Id {
+        if let Some(state) = self.spans().get_mut(&span.into_u64()) {
+            state.references += 1;
+        }
+
+        span.clone()
+    }
+
+    fn try_close(&self, span: Id) -> bool {
+        let mut spans = self.spans();
+
+        let Some(state) = spans.get_mut(&span.into_u64()) else {
+            return false;
+        };
+
+        state.references -= 1;
+
+        if state.references > 0 {
+            return false;
+        }
+
+        let state = spans
+            .remove(&span.into_u64())
+            .expect("Closed span is tracked");
+
+        drop(spans);
+
+        let fields = state.fields.trim_start();
+        let fields = if fields.is_empty() {
+            String::new()
+        } else {
+            format!("{{{fields}}}")
+        };
+
+        emit(
+            &state.level,
+            state.target,
+            format!(
+                "{}{fields}: completed in {:.2?}",
+                state.name,
+                state.start.elapsed()
+            ),
+        );
+
+        true
+    }
+}
+
+#[derive(Default)]
+struct FieldVisitor {
+    message: String,
+    fields: String,
+}
+
+impl Visit for FieldVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{value:?}");
+        } else {
+            let _ = write!(self.fields, " {}={value:?}", field.name());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        ffi::{CStr, c_char},
+        sync::Mutex,
+    };
+
+    use crate::core::logging::{ZKNeuralLogLevel, set_log_callback};
+
+    static MESSAGES: Mutex<Vec<(ZKNeuralLogLevel, String, String)>> = Mutex::new(vec![]);
+
+    unsafe extern "C" fn collect(
+        level: ZKNeuralLogLevel,
+        target: *const c_char,
+        message: *const c_char,
+    ) {
+        let target = unsafe { CStr::from_ptr(target) }.to_string_lossy();
+        let message = unsafe { CStr::from_ptr(message) }.to_string_lossy();
+
+        MESSAGES
+            .lock()
+            .unwrap()
+            .push((level, target.into_owned(), message.into_owned()));
+    }
+
+    #[test]
+    fn test_log_callback() {
+        assert!(set_log_callback(Some(collect), ZKNeuralLogLevel::Debug));
+
+        {
+            let _span = tracing::info_span!("test_stage", size = 3).entered();
+
+            tracing::debug!(shape = ?[1, 2], "test event");
+            tracing::trace!("test filtered");
+        }
+
+        assert!(set_log_callback(None, ZKNeuralLogLevel::Off));
+
+        tracing::error!("test after");
+
+        let messages = MESSAGES.lock().unwrap();
+
+        assert!(messages.contains(&(
+            ZKNeuralLogLevel::Debug,
+            "zk_neural_rust_core::core::logging::tests".to_string(),
+            "test_stage: test event shape=[1, 2]".to_string()
+        )));
+        assert!(messages.iter().any(|(level, _, message)| {
+            *level == ZKNeuralLogLevel::Info
+                && message.starts_with("test_stage{size=3}: completed in")
+        }));
+        assert!(
+            !messages
+                .iter()
+                .any(|(_, _, message)| message.contains("test filtered")
+                    || message.contains("test after"))
+        );
+    }
+}
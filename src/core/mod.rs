@@ -1,30 +1,67 @@
 pub mod callbacks;
+pub mod circuit_reference;
 pub mod constants;
+pub mod debug_dump;
 pub mod errors;
 pub mod face_anchors;
 pub mod face_detection;
+pub mod face_quality;
+pub mod image_source;
+pub mod inference;
+pub mod input_schema;
+pub mod liveness;
+pub mod logging;
 pub mod math;
+pub mod model_bundle;
+pub mod model_integrity;
+pub mod preprocessing;
+pub mod public_inputs;
+pub mod signal_encoding;
+pub mod similarity;
 pub mod tensor;
+#[cfg(feature = "tflite")]
+pub mod tflite;
 pub mod zk_proof;
 
 use callbacks::{GenerateProofCallback, GenerateWitnessCallback};
 use constants::{PROOF_SIZE, PUB_SIGNALS_SIZE, WITNESS_ERROR_MSG_MAXSIZE, WITNESS_SIZE};
 use errors::ZKNeuralError;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::{debug, info_span};
 
-use crate::core::zk_proof::{
-    GrothZkProof, GrothZkProofPoints, UltraGrothProof, UltraGrothProofPoints, ZkProofPubSignals,
+use crate::core::{
+    inference::{InferenceBackend, default_backend},
+    model_bundle::ModelBundle,
+    tensor::TensorInvoker,
+    zk_proof::assemble_proof,
 };
 
 #[repr(C)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
 pub enum ZKNeuralProvingType {
     Groth,
     UltraGroth,
 }
 
+impl ZKNeuralProvingType {
+    /// Value of the `ultra_groth` circuit input selecting this proving type.
+    pub fn ultra_groth_signal(&self) -> &'static str {
+        match self {
+            ZKNeuralProvingType::Groth => "0",
+            ZKNeuralProvingType::UltraGroth => "1",
+        }
+    }
+}
+
 pub struct ZKNeuralCore {
     generate_witness_callback: Option<GenerateWitnessCallback>,
     generate_proof_callback: Option<GenerateProofCallback>,
     proving_type: Option<ZKNeuralProvingType>,
+    bundle: Option<Arc<ModelBundle>>,
 }
 
 impl ZKNeuralCore {
@@ -33,6 +70,7 @@ impl ZKNeuralCore {
             generate_witness_callback: None,
             generate_proof_callback: None,
             proving_type: None,
+            bundle: None,
         }
     }
 
@@ -48,11 +86,75 @@ impl ZKNeuralCore {
         self.proving_type = Some(proving_type);
     }
 
+    pub fn proving_type(&self) -> Result<ZKNeuralProvingType, ZKNeuralError> {
+        self.proving_type.ok_or(ZKNeuralError::ProvingTypeNotSet)
+    }
+
+    /// Loads a bundle after checking its model against the bundle preprocessing spec with the
+    /// default backend. The proving type becomes the one of the bundle.
+    pub fn load_bundle(&mut self, bundle: ModelBundle) -> Result<(), ZKNeuralError> {
+        self.load_bundle_with_backend(bundle, default_backend()?)
+    }
+
+    pub fn load_bundle_with_backend(
+        &mut self,
+        bundle: ModelBundle,
+        backend: Arc<dyn InferenceBackend>,
+    ) -> Result<(), ZKNeuralError> {
+        bundle.check_model(&backend)?;
+
+        self.proving_type = Some(bundle.manifest.proving_type);
+        self.bundle = Some(Arc::new(bundle));
+
+        Ok(())
+    }
+
+    pub fn bundle(&self) -> Result<&Arc<ModelBundle>, ZKNeuralError> {
+        self.bundle
+            .as_ref()
+            .ok_or(ZKNeuralError::ModelBundleNotLoaded)
+    }
+
+    /// Creates an invoker of the bundle model, see `ModelBundle::tensor_invoker`.
+    pub fn bundle_tensor_invoker(
+        &self,
+        should_process: bool,
+    ) -> Result<TensorInvoker, ZKNeuralError> {
+        self.bundle()?.tensor_invoker(should_process)
+    }
+
+    /// Generates the witness and the proof of the JSON-encoded inputs with the circuit and the
+    /// zkey of the bundle.
+    ///
+    /// Fails if the proving type was changed from the one of the bundle, or the inputs do not
+    /// match the manifest, see `ModelBundle::check_inputs`.
+    pub fn prove_with_bundle(&self, json_buffer: &[u8]) -> Result<Vec<u8>, ZKNeuralError> {
+        let bundle = self.bundle()?;
+
+        let proving_type = self.proving_type()?;
+        if proving_type != bundle.manifest.proving_type {
+            return Err(ZKNeuralError::ModelBundleProvingTypeMismatch(proving_type));
+        }
+
+        bundle.check_inputs(json_buffer)?;
+
+        let witness = self.generate_witness(&bundle.circuit, json_buffer)?;
+
+        self.generate_proof(&bundle.read_zkey()?, &witness)
+    }
+
     pub fn generate_witness(
         &self,
         circuit_buffer: &[u8],
         json_buffer: &[u8],
     ) -> Result<Vec<u8>, ZKNeuralError> {
+        let _span = info_span!(
+            "generate_witness",
+            circuit_size = circuit_buffer.len(),
+            inputs_size = json_buffer.len()
+        )
+        .entered();
+
         if let Some(callback) = self.generate_witness_callback {
             let mut wtns_buffer = vec![0u8; WITNESS_SIZE];
             let mut wtns_size = 0;
@@ -72,6 +174,8 @@ impl ZKNeuralCore {
             };
 
             if result != 0 {
+                debug!(result, "witness callback failed");
+
                 let error_message = String::from_utf8_lossy(&error_msg)
                     .trim_end_matches('\0')
                     .to_string();
@@ -79,6 +183,8 @@ impl ZKNeuralCore {
                 return Err(ZKNeuralError::WitnessGenerationFailed(error_message));
             }
 
+            debug!(witness_size = wtns_size, "witness generated");
+
             wtns_buffer.truncate(wtns_size);
             Ok(wtns_buffer)
         } else {
@@ -91,10 +197,15 @@ impl ZKNeuralCore {
         zkey_buffer: &[u8],
         wtns_buffer: &[u8],
     ) -> Result<Vec<u8>, ZKNeuralError> {
-        let proving_type = self
-            .proving_type
-            .as_ref()
-            .ok_or(ZKNeuralError::ProvingTypeNotSet)?;
+        let proving_type = self.proving_type()?;
+
+        let _span = info_span!(
+            "generate_proof",
+            proving_type = ?proving_type,
+            zkey_size = zkey_buffer.len(),
+            witness_size = wtns_buffer.len()
+        )
+        .entered();
 
         if let Some(callback) = self.generate_proof_callback {
             let mut proof_buffer = vec![0u8; PROOF_SIZE];
@@ -118,6 +229,10 @@ impl ZKNeuralCore {
                 )
             };
 
+            if result != 0 {
+                debug!(result, "proof callback failed");
+            }
+
             if result == 2 {
                 return Err(ZKNeuralError::ProofGenerationFailed(
                     "Proof or public signals buffer is too short".to_string(),
@@ -132,27 +247,12 @@ impl ZKNeuralCore {
                 return Err(ZKNeuralError::ProofGenerationFailed(error_message));
             }
 
+            debug!(proof_size, public_size, "proof generated");
+
             proof_buffer.truncate(proof_size);
             public_buffer.truncate(public_size);
 
-            let proof = match proving_type {
-                ZKNeuralProvingType::Groth => {
-                    let proof = serde_json::from_slice::<GrothZkProofPoints>(&proof_buffer)?;
-                    let pub_signals = serde_json::from_slice::<ZkProofPubSignals>(&public_buffer)?;
-                    let groth_proof = GrothZkProof { proof, pub_signals };
-
-                    serde_json::to_vec(&groth_proof)
-                }
-                ZKNeuralProvingType::UltraGroth => {
-                    let proof = serde_json::from_slice::<UltraGrothProofPoints>(&proof_buffer)?;
-                    let pub_signals = serde_json::from_slice::<ZkProofPubSignals>(&public_buffer)?;
-                    let groth_proof = UltraGrothProof { proof, pub_signals };
-
-                    serde_json::to_vec(&groth_proof)
-                }
-            }?;
-
-            Ok(proof)
+            assemble_proof(proving_type, &proof_buffer, &public_buffer)
         } else {
             Err(ZKNeuralError::ProofCallbackNotSet)
         }
@@ -0,0 +1,482 @@
+//! Versioned bundle of the artifacts one circuit release is built from, so the model, its
+//! preprocessing, the circuit and the zkey cannot drift apart on the host.
+//!
+//! A bundle is a directory with a `manifest.json` and the files it declares, e.g.
+//!
+//! ```json
+//! {
+//!   "format_version": 1,
+//!   "name": "bionetta-arcface",
+//!   "version": "1.0.0",
+//!   "proving_type": "groth",
+//!   "threshold": "1000",
+//!   "model": { "path": "arcface.tflite", "sha256": "700ea787..." },
+//!   "circuit": { "path": "circuit.dat", "sha256": "..." },
+//!   "zkey": { "path": "circuit.zkey", "sha256": "..." },
+//!   "preprocessing_spec": { "scale": 0.00784313725490196, "mean": [1.0] },
+//!   "signal_encoding": { "precision_bits": 15 }
+//! }
+//! ```
+//!
+//! `preprocessing_spec` and `signal_encoding` take the defaults of their types when omitted.
+//!
+//! The zkey is usually the largest file of a bundle, so it is only read, and checked against
+//! its checksum, when a proof is generated.
+
+use std::{
+    fmt, fs,
+    path::{Component, Path},
+    sync::Arc,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tracing::debug;
+
+use crate::{
+    ZKNeuralError,
+    core::{
+        ZKNeuralProvingType,
+        inference::{InferenceBackend, default_backend},
+        model_integrity::{model_hash, parse_model_hash},
+        preprocessing::PreprocessingSpec,
+        public_inputs::{parse_threshold, parse_threshold_value},
+        signal_encoding::SignalEncoding,
+        tensor::TensorInvoker,
+    },
+};
+
+/// Version of the manifest format this library reads.
+pub const MODEL_BUNDLE_FORMAT_VERSION: u32 = 1;
+
+pub const MODEL_BUNDLE_MANIFEST: &str = "manifest.json";
+
+/// A file of the bundle, `path` is relative to the bundle root and `sha256` is hex encoded.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BundleFile {
+    pub path: String,
+    pub sha256: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BundleManifest {
+    pub format_version: u32,
+    pub name: String,
+    pub version: String,
+    pub proving_type: ZKNeuralProvingType,
+    #[serde(default)]
+    pub threshold: Option<String>,
+    pub model: BundleFile,
+    pub circuit: BundleFile,
+    pub zkey: BundleFile,
+    #[serde(default)]
+    pub preprocessing_spec: PreprocessingSpec,
+    #[serde(default)]
+    pub signal_encoding: SignalEncoding,
+}
+
+impl BundleManifest {
+    /// Checks everything that does not need the files: the format version, the file entries,
+    /// the preprocessing spec, the signal encoding and the threshold.
+    pub fn validate(&self) -> Result<(), ZKNeuralError> {
+        if self.format_version != MODEL_BUNDLE_FORMAT_VERSION {
+            return Err(ZKNeuralError::UnsupportedModelBundleVersion(
+                self.format_version,
+            ));
+        }
+
+        for file in [&self.model, &self.circuit, &self.zkey] {
+            let path = Path::new(&file.path);
+
+            if file.path.is_empty() || !path.components().all(|c| matches!(c, Component::Normal(_)))
+            {
+                return Err(ZKNeuralError::InvalidModelBundle(format!(
+                    "`{}` is not a relative path inside the bundle",
+                    file.path
+                )));
+            }
+
+            parse_model_hash(&file.sha256).map_err(|_| {
+                ZKNeuralError::InvalidModelBundle(format!(
+                    "`{}` has an invalid sha256 `{}`",
+                    file.path, file.sha256
+                ))
+            })?;
+        }
+
+        self.preprocessing_spec.validate()?;
+        self.signal_encoding.validate()?;
+
+        if let Some(threshold) = &self.threshold {
+            parse_threshold(threshold)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Reads a bundle file by its path relative to the bundle root.
+pub type BundleReader = dyn Fn(&str) -> Result<Vec<u8>, ZKNeuralError> + Send + Sync;
+
+/// A validated bundle with the contents of its model and circuit.
+pub struct ModelBundle {
+    pub manifest: BundleManifest,
+    pub model: Vec<u8>,
+    pub circuit: Vec<u8>,
+    read: Box<BundleReader>,
+}
+
+impl fmt::Debug for ModelBundle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ModelBundle")
+            .field("manifest", &self.manifest)
+            .field("model_size", &self.model.len())
+            .field("circuit_size", &self.circuit.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl ModelBundle {
+    /// Reads the manifest, the model and the circuit of a bundle directory.
+    pub fn load(directory: &Path) -> Result<Self, ZKNeuralError> {
+        let directory = directory.to_path_buf();
+
+        let read = move |path: &str| {
+            fs::read(directory.join(path)).map_err(|e| {
+                ZKNeuralError::InvalidModelBundle(format!("failed to read `{path}`: {e}"))
+            })
+        };
+
+        let manifest = read(MODEL_BUNDLE_MANIFEST)?;
+
+        Self::from_manifest(&manifest, read)
+    }
+
+    /// Parses a JSON-encoded manifest and reads the model and the circuit it declares with
+    /// `read`, e.g. from an archive or the host assets. The bundle keeps `read` for the zkey.
+    ///
+    /// Fails if the manifest is invalid or a file does not match its checksum.
+    pub fn from_manifest(
+        manifest: &[u8],
+        read: impl Fn(&str) -> Result<Vec<u8>, ZKNeuralError> + Send + Sync + 'static,
+    ) -> Result<Self, ZKNeuralError> {
+        let manifest: BundleManifest = serde_json::from_slice(manifest)?;
+
+        manifest.validate()?;
+
+        let model = read_file(&read, &manifest.model)?;
+        let circuit = read_file(&read, &manifest.circuit)?;
+
+        debug!(
+            name = manifest.name,
+            version = manifest.version,
+            model_size = model.len(),
+            circuit_size = circuit.len(),
+            "model bundle loaded"
+        );
+
+        Ok(ModelBundle {
+            manifest,
+            model,
+            circuit,
+            read: Box::new(read),
+        })
+    }
+
+    /// Reads the zkey, failing if it does not match its checksum.
+    pub fn read_zkey(&self) -> Result<Vec<u8>, ZKNeuralError> {
+        let zkey = read_file(&self.read, &self.manifest.zkey)?;
+
+        debug!(zkey_size = zkey.len(), "model bundle zkey read");
+
+        Ok(zkey)
+    }
+
+    /// Checks the JSON-encoded circuit inputs against the manifest: the `threshold` must be
+    /// the one of the manifest when it declares one, and the `ultra_groth` signal, when
+    /// present, must select the proving type of the manifest.
+    pub fn check_inputs(&self, json_buffer: &[u8]) -> Result<(), ZKNeuralError> {
+        let inputs: Map<String, Value> = serde_json::from_slice(json_buffer)?;
+
+        let signal = |name: &str| {
+            inputs.get(name).map(|value| match value {
+                Value::String(value) => value.clone(),
+                value => value.to_string(),
+            })
+        };
+
+        if let Some(expected) = &self.manifest.threshold {
+            let threshold = signal("threshold").ok_or_else(|| {
+                ZKNeuralError::ModelBundleInputMismatch("`threshold` is missing".to_string())
+            })?;
+
+            if parse_threshold_value(&threshold)? != parse_threshold_value(expected)? {
+                return Err(ZKNeuralError::ModelBundleInputMismatch(format!(
+                    "`threshold` is {threshold}, the bundle requires {expected}"
+                )));
+            }
+        }
+
+        let expected = self.manifest.proving_type.ultra_groth_signal();
+
+        match signal("ultra_groth") {
+            Some(ultra_groth) if ultra_groth != expected => {
+                Err(ZKNeuralError::ModelBundleInputMismatch(format!(
+                    "`ultra_groth` is {ultra_groth}, the bundle requires {expected}"
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks that the model input can be prepared with the bundle preprocessing spec.
+    pub fn check_model(&self, backend: &Arc<dyn InferenceBackend>) -> Result<(), ZKNeuralError> {
+        let input = backend.session(&self.model, |session| session.input())?;
+
+        self.manifest.preprocessing_spec.check_input(&input)
+    }
+
+    pub fn tensor_invoker(&self, should_process: bool) -> Result<TensorInvoker, ZKNeuralError> {
+        self.tensor_invoker_with_backend(should_process, default_backend()?)
+    }
+
+    /// Creates an invoker of the bundle model with the bundle preprocessing spec and signal
+    /// encoding, restricted to the model hash of the manifest.
+    pub fn tensor_invoker_with_backend(
+        &self,
+        should_process: bool,
+        backend: Arc<dyn InferenceBackend>,
+    ) -> Result<TensorInvoker, ZKNeuralError> {
+        let mut invoker = TensorInvoker::with_backend(&self.model, should_process, backend)?;
+
+        invoker.preprocessing_spec = self.manifest.preprocessing_spec.clone();
+        invoker.signal_encoding = self.manifest.signal_encoding.clone();
+        invoker.set_allowed_model_hashes(Some(vec![self.manifest.model.sha256.clone()]))?;
+
+        Ok(invoker)
+    }
+}
+
+fn read_file(read: &BundleReader, file: &BundleFile) -> Result<Vec<u8>, ZKNeuralError> {
+    let data = read(&file.path)?;
+
+    if model_hash(&data) != parse_model_hash(&file.sha256)? {
+        return Err(ZKNeuralError::ModelBundleChecksumMismatch(
+            file.path.clone(),
+        ));
+    }
+
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::Arc};
+
+    use serde_json::{Value, json};
+
+    use crate::{
+        ZKNeuralError,
+        core::{
+            ZKNeuralCore, ZKNeuralProvingType,
+            inference::{DataType, InferenceBackend, InferenceSession, Shape, Tensor, TensorSpec},
+            model_bundle::ModelBundle,
+            model_integrity::model_hash,
+        },
+    };
+
+    /// Backend of a model with a `[1, 112, 112, 3]` float input.
+    struct InputOnlyBackend;
+
+    impl InferenceBackend for InputOnlyBackend {
+        fn with_session(
+            &self,
+            _model: &[u8],
+            f: &mut dyn FnMut(&dyn InferenceSession) -> Result<(), ZKNeuralError>,
+        ) -> Result<(), ZKNeuralError> {
+            f(&InputOnlyBackend)
+        }
+    }
+
+    impl InferenceSession for InputOnlyBackend {
+        fn input(&self) -> Result<TensorSpec, ZKNeuralError> {
+            Ok(TensorSpec {
+                shape: Shape::new(vec![1, 112, 112, 3]),
+                data_type: DataType::Float32,
+            })
+        }
+
+        fn invoke(&self, _input: &[u8]) -> Result<Vec<Tensor>, ZKNeuralError> {
+            Err(ZKNeuralError::InferenceFailed(
+                "the model is never invoked".to_string(),
+            ))
+        }
+    }
+
+    fn files() -> HashMap<&'static str, Vec<u8>> {
+        HashMap::from([
+            ("model.tflite", b"model".to_vec()),
+            ("circuit.dat", b"circuit".to_vec()),
+            ("circuit.zkey", b"zkey".to_vec()),
+        ])
+    }
+
+    fn manifest() -> Value {
+        json!({
+            "format_version": 1,
+            "name": "test",
+            "version": "1.0.0",
+            "proving_type": "ultra_groth",
+            "threshold": "1000",
+            "model": { "path": "model.tflite", "sha256": model_hash(b"model") },
+            "circuit": { "path": "circuit.dat", "sha256": model_hash(b"circuit") },
+            "zkey": { "path": "circuit.zkey", "sha256": model_hash(b"zkey") },
+            "preprocessing_spec": { "mean": [0.5, 0.5, 0.5] }
+        })
+    }
+
+    fn load(
+        manifest: &Value,
+        files: &HashMap<&'static str, Vec<u8>>,
+    ) -> Result<ModelBundle, ZKNeuralError> {
+        let files = files.clone();
+
+        ModelBundle::from_manifest(&serde_json::to_vec(manifest).unwrap(), move |path| {
+            files
+                .get(path)
+                .cloned()
+                .ok_or_else(|| ZKNeuralError::InvalidModelBundle(path.to_string()))
+        })
+    }
+
+    #[test]
+    fn test_load_bundle() {
+        let bundle = load(&manifest(), &files()).unwrap();
+
+        assert_eq!(bundle.read_zkey().unwrap(), b"zkey");
+        assert_eq!(bundle.manifest.preprocessing_spec.mean, [0.5, 0.5, 0.5]);
+
+        let backend: Arc<dyn InferenceBackend> = Arc::new(InputOnlyBackend);
+        bundle.check_model(&backend).unwrap();
+
+        let invoker = bundle.tensor_invoker_with_backend(false, backend).unwrap();
+
+        assert_eq!(invoker.preprocessing_spec.mean, [0.5, 0.5, 0.5]);
+        assert_eq!(
+            invoker.allowed_model_hashes(),
+            Some([model_hash(b"model")].as_slice())
+        );
+    }
+
+    #[test]
+    fn test_invalid_bundle() {
+        let mut files = files();
+        files.insert("circuit.zkey", b"other".to_vec());
+
+        // The zkey is only checked when it is read.
+        let bundle = load(&manifest(), &files).unwrap();
+
+        assert!(matches!(
+            bundle.read_zkey(),
+            Err(ZKNeuralError::ModelBundleChecksumMismatch(path)) if path == "circuit.zkey"
+        ));
+
+        let mut files = self::files();
+        files.insert("circuit.dat", b"other".to_vec());
+
+        assert!(matches!(
+            load(&manifest(), &files),
+            Err(ZKNeuralError::ModelBundleChecksumMismatch(path)) if path == "circuit.dat"
+        ));
+
+        let mut version = manifest();
+        version["format_version"] = json!(2);
+
+        assert!(matches!(
+            load(&version, &self::files()),
+            Err(ZKNeuralError::UnsupportedModelBundleVersion(2))
+        ));
+
+        let mut path = manifest();
+        path["model"]["path"] = json!("../model.tflite");
+
+        assert!(matches!(
+            load(&path, &self::files()),
+            Err(ZKNeuralError::InvalidModelBundle(_))
+        ));
+
+        let mut threshold = manifest();
+        threshold["threshold"] = json!("-1");
+
+        assert!(matches!(
+            load(&threshold, &self::files()),
+            Err(ZKNeuralError::InvalidThreshold(_))
+        ));
+    }
+
+    #[test]
+    fn test_prove_with_bundle_checks() {
+        let mut core = ZKNeuralCore::new();
+
+        core.load_bundle_with_backend(
+            load(&manifest(), &files()).unwrap(),
+            Arc::new(InputOnlyBackend),
+        )
+        .unwrap();
+
+        let prove = |core: &ZKNeuralCore, inputs: Value| {
+            core.prove_with_bundle(&serde_json::to_vec(&inputs).unwrap())
+        };
+
+        // Inputs matching the bundle only fail on the missing witness callback.
+        assert!(matches!(
+            prove(&core, json!({"threshold": "1000", "ultra_groth": "1"})),
+            Err(ZKNeuralError::WitnessCallbackNotSet)
+        ));
+
+        assert!(matches!(
+            prove(&core, json!({"threshold": "999", "ultra_groth": "1"})),
+            Err(ZKNeuralError::ModelBundleInputMismatch(_))
+        ));
+        assert!(matches!(
+            prove(&core, json!({"ultra_groth": "1"})),
+            Err(ZKNeuralError::ModelBundleInputMismatch(_))
+        ));
+        assert!(matches!(
+            prove(&core, json!({"threshold": 1000, "ultra_groth": "0"})),
+            Err(ZKNeuralError::ModelBundleInputMismatch(_))
+        ));
+
+        core.set_proving_type(ZKNeuralProvingType::Groth);
+
+        assert!(matches!(
+            prove(&core, json!({"threshold": "1000", "ultra_groth": "1"})),
+            Err(ZKNeuralError::ModelBundleProvingTypeMismatch(
+                ZKNeuralProvingType::Groth
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_load_directory() {
+        let directory = std::env::temp_dir().join("zkneural_test_load_directory");
+        std::fs::create_dir_all(&directory).unwrap();
+
+        for (path, data) in files() {
+            std::fs::write(directory.join(path), data).unwrap();
+        }
+
+        std::fs::write(
+            directory.join("manifest.json"),
+            serde_json::to_vec(&manifest()).unwrap(),
+        )
+        .unwrap();
+
+        let bundle = ModelBundle::load(&directory).unwrap();
+
+        assert_eq!(bundle.manifest.name, "test");
+        assert_eq!(bundle.circuit, b"circuit");
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,64 @@
+//! Model identities, so a swapped or corrupted model is caught before it produces inputs for
+//! a circuit built around another network.
+//!
+//! Hashes are lowercase hex SHA-256 digests of the serialized model file.
+
+use sha2::{Digest, Sha256};
+
+use crate::ZKNeuralError;
+
+/// SHA-256 of `assets/blaze_face_short_range.tflite`, the face detector built into the library.
+pub const BLAZE_FACE_MODEL_SHA256: &str =
+    "b4578f35940bf5a1a655214a1cce5cab13eba73c1297cd78e1a04c2380b0152f";
+
+/// SHA-256 of `assets/arcface.tflite`, the embedding model the Bionetta circuit is built for.
+pub const ARCFACE_MODEL_SHA256: &str =
+    "700ea787ab479a13ddf9c8225215569f88065748aa2b8f2b384735c5e6521bef";
+
+pub fn model_hash(model: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(model))
+}
+
+/// Normalizes a hex SHA-256 hash, with or without `0x` prefix, to the form `model_hash`
+/// returns.
+pub fn parse_model_hash(hash: &str) -> Result<String, ZKNeuralError> {
+    let hex = hash.trim();
+    let hex = hex.strip_prefix("0x").unwrap_or(hex);
+
+    if hex.len() != 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(ZKNeuralError::InvalidModelHash(hash.to_string()));
+    }
+
+    Ok(hex.to_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::model_integrity::{model_hash, parse_model_hash};
+
+    #[test]
+    fn test_model_hash() {
+        assert_eq!(
+            model_hash(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            model_hash(&[]),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn test_parse_model_hash() {
+        assert_eq!(
+            parse_model_hash(
+                " 0xBA7816BF8F01CFEA414140DE5DAE2223B00361A396177A9CB410FF61F20015AD "
+            )
+            .unwrap(),
+            model_hash(b"abc")
+        );
+
+        assert!(parse_model_hash("ba7816bf").is_err());
+        assert!(parse_model_hash(&"g".repeat(64)).is_err());
+    }
+}
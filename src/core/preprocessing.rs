@@ -0,0 +1,527 @@
+use image::{DynamicImage, EncodableLayout, Rgb, RgbImage, imageops, imageops::FilterType};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ZKNeuralError,
+    core::{
+        inference::{DataType, Shape, TensorSpec},
+        signal_encoding::SignalEncoding,
+        tensor::{prepare_data_by_type, prepare_normalized_data_by_float_type},
+    },
+};
+
+const SUPPORTED_BATCH_SIZE: usize = 1;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelOrder {
+    Rgb,
+    Bgr,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TensorLayout {
+    Nhwc,
+    Nchw,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ResizeFilter {
+    Nearest,
+    Triangle,
+    CatmullRom,
+    Gaussian,
+    Lanczos3,
+}
+
+impl From<ResizeFilter> for FilterType {
+    fn from(filter: ResizeFilter) -> Self {
+        match filter {
+            ResizeFilter::Nearest => FilterType::Nearest,
+            ResizeFilter::Triangle => FilterType::Triangle,
+            ResizeFilter::CatmullRom => FilterType::CatmullRom,
+            ResizeFilter::Gaussian => FilterType::Gaussian,
+            ResizeFilter::Lanczos3 => FilterType::Lanczos3,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ResizeMode {
+    /// Resizes to the model input ignoring the aspect ratio.
+    Stretch,
+    /// Keeps the aspect ratio and pads the borders with `letterbox_color`.
+    Letterbox,
+}
+
+/// Spatial and channel sizes of a four-dimensional image input tensor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputGeometry {
+    pub batch: usize,
+    pub height: usize,
+    pub width: usize,
+    pub channels: usize,
+}
+
+impl InputGeometry {
+    /// Reads the input tensor dimensions, `[N, H, W, C]` for NHWC and `[N, C, H, W]` for NCHW.
+    pub fn from_dimensions(
+        dimensions: &[usize],
+        layout: TensorLayout,
+    ) -> Result<Self, ZKNeuralError> {
+        if dimensions.len() != 4 {
+            return Err(ZKNeuralError::ModelNotFourDimensional);
+        }
+
+        let geometry = match layout {
+            TensorLayout::Nhwc => InputGeometry {
+                batch: dimensions[0],
+                height: dimensions[1],
+                width: dimensions[2],
+                channels: dimensions[3],
+            },
+            TensorLayout::Nchw => InputGeometry {
+                batch: dimensions[0],
+                channels: dimensions[1],
+                height: dimensions[2],
+                width: dimensions[3],
+            },
+        };
+
+        if geometry.batch != SUPPORTED_BATCH_SIZE {
+            return Err(ZKNeuralError::InvalidModelBatchSize(geometry.batch));
+        }
+
+        Ok(geometry)
+    }
+}
+
+/// Describes how an image is turned into the model input tensor.
+///
+/// Float inputs are normalized per channel as `(pixel * scale - mean) / std`, where `mean`
+/// and `std` hold either one value for all channels or one value per channel, in the order
+/// given by `channel_order`. Integer inputs receive the raw pixel values. The default spec
+/// maps pixels to `[0, 1]` in RGB NHWC order with a Catmull-Rom stretch resize.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct PreprocessingSpec {
+    pub scale: f64,
+    pub mean: Vec<f64>,
+    pub std: Vec<f64>,
+    pub channel_order: ChannelOrder,
+    pub layout: TensorLayout,
+    pub resize_filter: ResizeFilter,
+    pub resize_mode: ResizeMode,
+    pub letterbox_color: [u8; 3],
+}
+
+impl Default for PreprocessingSpec {
+    fn default() -> Self {
+        PreprocessingSpec {
+            scale: 1.0 / 255.0,
+            mean: vec![0.0],
+            std: vec![1.0],
+            channel_order: ChannelOrder::Rgb,
+            layout: TensorLayout::Nhwc,
+            resize_filter: ResizeFilter::CatmullRom,
+            resize_mode: ResizeMode::Stretch,
+            letterbox_color: [0, 0, 0],
+        }
+    }
+}
+
+impl PreprocessingSpec {
+    pub fn from_json(json: &[u8]) -> Result<Self, ZKNeuralError> {
+        let spec: PreprocessingSpec = serde_json::from_slice(json)?;
+
+        spec.validate()?;
+
+        Ok(spec)
+    }
+
+    pub fn validate(&self) -> Result<(), ZKNeuralError> {
+        for (name, values) in [("mean", &self.mean), ("std", &self.std)] {
+            if values.len() != 1 && values.len() != 3 {
+                return Err(ZKNeuralError::InvalidPreprocessingSpec(format!(
+                    "`{name}` must have one value or one value per channel"
+                )));
+            }
+        }
+
+        if self.std.iter().any(|&std| std == 0.0) {
+            return Err(ZKNeuralError::InvalidPreprocessingSpec(
+                "`std` must not contain zeros".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Checks that images can be prepared for a model input with this spec, so an incompatible
+    /// model fails when it is loaded rather than on the first image.
+    pub fn check_input(&self, input: &TensorSpec) -> Result<(), ZKNeuralError> {
+        let geometry = InputGeometry::from_dimensions(input.shape.dimensions(), self.layout)?;
+
+        if !matches!(geometry.channels, 1 | 3) {
+            return Err(ZKNeuralError::InvalidModelChannel);
+        }
+
+        for (name, values) in [("mean", &self.mean), ("std", &self.std)] {
+            if values.len() != 1 && values.len() != geometry.channels {
+                return Err(ZKNeuralError::InvalidPreprocessingSpec(format!(
+                    "`{name}` has {} values for a model with {} channels",
+                    values.len(),
+                    geometry.channels
+                )));
+            }
+        }
+
+        match input.data_type {
+            DataType::Uint8
+            | DataType::Int16
+            | DataType::Int32
+            | DataType::Int64
+            | DataType::Float32
+            | DataType::Float64 => Ok(()),
+            _ => Err(ZKNeuralError::InvalidModelDataType),
+        }
+    }
+
+    /// Converts an image to the input tensor data of a model and its circuit signals.
+    ///
+    /// Float inputs are encoded with `signal_encoding`, integer inputs are already field
+    /// elements and are emitted as is.
+    pub fn prepare_tensor(
+        &self,
+        image: &DynamicImage,
+        input_shape: &Shape,
+        input_data_type: DataType,
+        signal_encoding: &SignalEncoding,
+    ) -> Result<(Vec<u8>, Vec<String>), ZKNeuralError> {
+        let geometry = InputGeometry::from_dimensions(input_shape.dimensions(), self.layout)?;
+
+        let loaded_image = self.resize(image, geometry.width as u32, geometry.height as u32);
+
+        let prepared_image = self.arrange_pixels(&loaded_image, geometry.channels)?;
+
+        match input_data_type {
+            DataType::Uint8 => Ok(prepare_data_by_type::<u8>(prepared_image)),
+            DataType::Int16 => Ok(prepare_data_by_type::<i16>(prepared_image)),
+            DataType::Int32 => Ok(prepare_data_by_type::<i32>(prepared_image)),
+            DataType::Int64 => Ok(prepare_data_by_type::<i64>(prepared_image)),
+            DataType::Float32 => prepare_normalized_data_by_float_type::<f32>(
+                self.normalize(&prepared_image, geometry.channels),
+                signal_encoding,
+            ),
+            DataType::Float64 => prepare_normalized_data_by_float_type::<f64>(
+                self.normalize(&prepared_image, geometry.channels),
+                signal_encoding,
+            ),
+            _ => Err(ZKNeuralError::InvalidModelDataType),
+        }
+    }
+
+    pub fn resize(&self, image: &DynamicImage, width: u32, height: u32) -> DynamicImage {
+        let filter = FilterType::from(self.resize_filter);
+
+        match self.resize_mode {
+            ResizeMode::Stretch => image.resize_exact(width, height, filter),
+            ResizeMode::Letterbox => {
+                let resized = image.resize(width, height, filter).to_rgb8();
+
+                let mut canvas = RgbImage::from_pixel(width, height, Rgb(self.letterbox_color));
+
+                let x = (width - resized.width()) / 2;
+                let y = (height - resized.height()) / 2;
+                imageops::overlay(&mut canvas, &resized, x as i64, y as i64);
+
+                DynamicImage::ImageRgb8(canvas)
+            }
+        }
+    }
+
+    /// Returns the pixel values of an already resized image in tensor order.
+    pub fn arrange_pixels(
+        &self,
+        image: &DynamicImage,
+        channels: usize,
+    ) -> Result<Vec<u8>, ZKNeuralError> {
+        let mut pixels: Vec<u8> = match channels {
+            1 => image.to_luma8().as_bytes().to_vec(),
+            3 => image.to_rgb8().as_bytes().to_vec(),
+            _ => return Err(ZKNeuralError::InvalidModelChannel),
+        };
+
+        if channels == 3 && self.channel_order == ChannelOrder::Bgr {
+            pixels
+                .chunks_exact_mut(3)
+                .for_each(|pixel| pixel.swap(0, 2));
+        }
+
+        if channels == 1 || self.layout == TensorLayout::Nhwc {
+            return Ok(pixels);
+        }
+
+        let plane_size = pixels.len() / channels;
+
+        Ok((0..channels)
+            .flat_map(|channel| {
+                pixels
+                    .iter()
+                    .skip(channel)
+                    .step_by(channels)
+                    .take(plane_size)
+            })
+            .cloned()
+            .collect())
+    }
+
+    /// Normalizes pixel values arranged by `arrange_pixels`.
+    pub fn normalize(&self, pixels: &[u8], channels: usize) -> Vec<f64> {
+        let plane_size = pixels.len() / channels.max(1);
+
+        pixels
+            .iter()
+            .enumerate()
+            .map(|(index, &pixel)| {
+                let channel = match self.layout {
+                    TensorLayout::Nhwc => index % channels,
+                    TensorLayout::Nchw => index / plane_size,
+                };
+
+                let mean = self.mean[channel.min(self.mean.len() - 1)];
+                let std = self.std[channel.min(self.std.len() - 1)];
+
+                (pixel as f64 * self.scale - mean) / std
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{DynamicImage, GenericImageView, Rgb, RgbImage};
+
+    use crate::{
+        ZKNeuralError,
+        core::{
+            inference::{DataType, Shape, TensorSpec},
+            preprocessing::{
+                ChannelOrder, InputGeometry, PreprocessingSpec, ResizeFilter, ResizeMode,
+                TensorLayout,
+            },
+            signal_encoding::SignalEncoding,
+        },
+    };
+
+    fn two_pixels() -> DynamicImage {
+        DynamicImage::ImageRgb8(RgbImage::from_fn(2, 1, |x, _| {
+            if x == 0 {
+                Rgb([10, 20, 30])
+            } else {
+                Rgb([40, 50, 60])
+            }
+        }))
+    }
+
+    #[test]
+    fn test_default_spec() {
+        let spec = PreprocessingSpec::from_json(b"{}").unwrap();
+        assert_eq!(spec, PreprocessingSpec::default());
+
+        let pixels = spec.arrange_pixels(&two_pixels(), 3).unwrap();
+        assert_eq!(pixels, vec![10, 20, 30, 40, 50, 60]);
+
+        let normalized = spec.normalize(&pixels, 3);
+        assert!((normalized[5] - 60.0 / 255.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_bgr_nchw_spec() {
+        let spec = PreprocessingSpec::from_json(
+            br#"{"scale": 1.0, "mean": [127.5], "std": [127.5], "channel_order": "bgr", "layout": "nchw"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(spec.channel_order, ChannelOrder::Bgr);
+        assert_eq!(spec.layout, TensorLayout::Nchw);
+
+        let pixels = spec.arrange_pixels(&two_pixels(), 3).unwrap();
+        assert_eq!(pixels, vec![30, 60, 20, 50, 10, 40]);
+
+        let normalized = spec.normalize(&pixels, 3);
+        assert!((normalized[0] - (30.0 - 127.5) / 127.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_per_channel_normalization() {
+        let spec = PreprocessingSpec {
+            scale: 1.0,
+            mean: vec![10.0, 20.0, 30.0],
+            std: vec![1.0, 2.0, 3.0],
+            ..PreprocessingSpec::default()
+        };
+
+        let pixels = spec.arrange_pixels(&two_pixels(), 3).unwrap();
+        let normalized = spec.normalize(&pixels, 3);
+
+        assert_eq!(normalized, vec![0.0, 0.0, 0.0, 30.0, 15.0, 10.0]);
+    }
+
+    #[test]
+    fn test_letterbox() {
+        let spec = PreprocessingSpec {
+            resize_mode: ResizeMode::Letterbox,
+            letterbox_color: [0, 0, 255],
+            ..PreprocessingSpec::default()
+        };
+
+        let image = DynamicImage::ImageRgb8(RgbImage::from_pixel(8, 4, Rgb([255, 0, 0])));
+        let resized = spec.resize(&image, 4, 4);
+
+        assert_eq!(resized.dimensions(), (4, 4));
+        assert_eq!(resized.get_pixel(0, 0).0, [0, 0, 255, 255]);
+        assert_eq!(resized.get_pixel(2, 2).0, [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_invalid_spec() {
+        assert!(PreprocessingSpec::from_json(br#"{"std": [0.0]}"#).is_err());
+        assert!(PreprocessingSpec::from_json(br#"{"mean": []}"#).is_err());
+        assert!(PreprocessingSpec::from_json(br#"{"mean": [0.5, 0.5]}"#).is_err());
+        assert!(PreprocessingSpec::from_json(br#"{"layout": "nhcw"}"#).is_err());
+    }
+
+    /// A 3x2 image where every channel value encodes its own position: `10 * (x + 3 * y) + c`.
+    fn positional_image() -> DynamicImage {
+        DynamicImage::ImageRgb8(RgbImage::from_fn(3, 2, |x, y| {
+            let base = 10 * (x + 3 * y) as u8;
+            Rgb([base, base + 1, base + 2])
+        }))
+    }
+
+    #[test]
+    fn test_non_square_nhwc_tensor() {
+        let spec = PreprocessingSpec {
+            resize_filter: ResizeFilter::Nearest,
+            ..PreprocessingSpec::default()
+        };
+
+        // Toy model input with height 2 and width 3.
+        let shape = Shape::new(vec![1, 2, 3, 3]);
+
+        let (_, signals) = spec
+            .prepare_tensor(
+                &positional_image(),
+                &shape,
+                DataType::Uint8,
+                &SignalEncoding::default(),
+            )
+            .unwrap();
+
+        let expected: Vec<String> = (0..2)
+            .flat_map(|y| (0..3).flat_map(move |x| (0..3).map(move |c| 10 * (x + 3 * y) + c)))
+            .map(|value| value.to_string())
+            .collect();
+
+        assert_eq!(signals, expected);
+
+        let (tensor, _) = spec
+            .prepare_tensor(
+                &positional_image(),
+                &shape,
+                DataType::Float32,
+                &SignalEncoding::default(),
+            )
+            .unwrap();
+
+        assert_eq!(tensor.len(), 2 * 3 * 3 * 4);
+
+        // Element [0, 1, 2, 0] is the red channel of the pixel at x = 2, y = 1.
+        let offset = (3 + 2) * 3 * 4;
+        let value = f32::from_le_bytes(tensor[offset..offset + 4].try_into().unwrap());
+        assert_eq!(value, 50.0 / 255.0);
+    }
+
+    #[test]
+    fn test_non_square_nchw_tensor() {
+        let spec = PreprocessingSpec {
+            resize_filter: ResizeFilter::Nearest,
+            layout: TensorLayout::Nchw,
+            ..PreprocessingSpec::default()
+        };
+
+        let shape = Shape::new(vec![1, 3, 2, 3]);
+
+        let (_, signals) = spec
+            .prepare_tensor(
+                &positional_image(),
+                &shape,
+                DataType::Uint8,
+                &SignalEncoding::default(),
+            )
+            .unwrap();
+
+        let expected: Vec<String> = (0..3)
+            .flat_map(|c| (0..2).flat_map(move |y| (0..3).map(move |x| 10 * (x + 3 * y) + c)))
+            .map(|value| value.to_string())
+            .collect();
+
+        assert_eq!(signals, expected);
+    }
+
+    #[test]
+    fn test_input_geometry() {
+        let geometry =
+            InputGeometry::from_dimensions(&[1, 112, 96, 3], TensorLayout::Nhwc).unwrap();
+        assert_eq!((geometry.height, geometry.width), (112, 96));
+
+        let geometry =
+            InputGeometry::from_dimensions(&[1, 3, 112, 96], TensorLayout::Nchw).unwrap();
+        assert_eq!(
+            (geometry.height, geometry.width, geometry.channels),
+            (112, 96, 3)
+        );
+
+        assert!(matches!(
+            InputGeometry::from_dimensions(&[4, 112, 112, 3], TensorLayout::Nhwc),
+            Err(ZKNeuralError::InvalidModelBatchSize(4))
+        ));
+        assert!(matches!(
+            InputGeometry::from_dimensions(&[112, 112, 3], TensorLayout::Nhwc),
+            Err(ZKNeuralError::ModelNotFourDimensional)
+        ));
+    }
+
+    #[test]
+    fn test_check_input() {
+        let input = |dimensions: Vec<usize>, data_type| TensorSpec {
+            shape: Shape::new(dimensions),
+            data_type,
+        };
+
+        let spec = PreprocessingSpec {
+            mean: vec![0.5, 0.5, 0.5],
+            ..Default::default()
+        };
+
+        spec.check_input(&input(vec![1, 112, 112, 3], DataType::Float32))
+            .unwrap();
+
+        assert!(matches!(
+            spec.check_input(&input(vec![1, 112, 112, 1], DataType::Float32)),
+            Err(ZKNeuralError::InvalidPreprocessingSpec(_))
+        ));
+        assert!(matches!(
+            spec.check_input(&input(vec![1, 3, 112, 112], DataType::Float32)),
+            Err(ZKNeuralError::InvalidModelChannel)
+        ));
+        assert!(matches!(
+            spec.check_input(&input(vec![1, 112, 112, 3], DataType::Bool)),
+            Err(ZKNeuralError::InvalidModelDataType)
+        ));
+    }
+}
@@ -0,0 +1,161 @@
+use num_bigint::BigUint;
+
+use crate::{
+    ZKNeuralError,
+    core::{constants::COMPARATOR_BITS, signal_encoding::FieldModulus},
+};
+
+const ADDRESS_HEX_LENGTH: usize = 40;
+
+/// Parses an Ethereum address in `0x` hex form or as a decimal field element, returning its
+/// decimal representation.
+pub fn parse_address(address: &str, field_modulus: &FieldModulus) -> Result<String, ZKNeuralError> {
+    let invalid = || ZKNeuralError::InvalidAddress(address.to_string());
+
+    let decimal = if let Some(hex) = address
+        .strip_prefix("0x")
+        .or_else(|| address.strip_prefix("0X"))
+    {
+        if hex.len() != ADDRESS_HEX_LENGTH || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(invalid());
+        }
+
+        BigUint::parse_bytes(hex.as_bytes(), 16)
+            .ok_or_else(invalid)?
+            .to_string()
+    } else if is_decimal(address) {
+        address.to_string()
+    } else {
+        return Err(invalid());
+    };
+
+    field_modulus
+        .parse_element(&decimal)
+        .map(|value| value.to_string())
+        .map_err(|_| invalid())
+}
+
+/// Parses a decimal threshold that fits `COMPARATOR_BITS`.
+pub fn parse_threshold(threshold: &str) -> Result<String, ZKNeuralError> {
+    parse_threshold_value(threshold).map(|value| value.to_string())
+}
+
+/// `parse_threshold` returning the value itself.
+pub fn parse_threshold_value(threshold: &str) -> Result<BigUint, ZKNeuralError> {
+    let invalid = || ZKNeuralError::InvalidThreshold(threshold.to_string());
+
+    if !is_decimal(threshold) {
+        return Err(invalid());
+    }
+
+    let value: BigUint = threshold.parse().map_err(|_| invalid())?;
+
+    if value.bits() > COMPARATOR_BITS {
+        return Err(invalid());
+    }
+
+    Ok(value)
+}
+
+/// Parses a decimal nonce that is a field element.
+pub fn parse_nonce(nonce: &str, field_modulus: &FieldModulus) -> Result<String, ZKNeuralError> {
+    let invalid = || ZKNeuralError::InvalidNonce(nonce.to_string());
+
+    if !is_decimal(nonce) {
+        return Err(invalid());
+    }
+
+    field_modulus
+        .parse_element(nonce)
+        .map(|value| value.to_string())
+        .map_err(|_| invalid())
+}
+
+fn is_decimal(value: &str) -> bool {
+    !value.is_empty() && value.chars().all(|c| c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{
+        ZKNeuralError,
+        core::{
+            public_inputs::{parse_address, parse_nonce, parse_threshold},
+            signal_encoding::FieldModulus,
+        },
+    };
+
+    #[test]
+    fn test_parse_address() {
+        let field_modulus = FieldModulus::bn254();
+
+        assert_eq!(
+            parse_address("0x00000000000000000000000000000000000000fF", &field_modulus).unwrap(),
+            "255"
+        );
+        assert_eq!(parse_address("3123123", &field_modulus).unwrap(), "3123123");
+
+        for address in [
+            "",
+            "0x",
+            "0x1234",
+            "0xZZ00000000000000000000000000000000000000",
+            "-1",
+            "+1",
+            "1 ",
+            "21888242871839275222246405745257275088548364400416034343698204186575808495617",
+        ] {
+            assert!(
+                matches!(
+                    parse_address(address, &field_modulus),
+                    Err(ZKNeuralError::InvalidAddress(_))
+                ),
+                "{address}"
+            );
+        }
+
+        let small = FieldModulus::from_str("2305843009213693951").unwrap();
+        assert!(matches!(
+            parse_address("0x00000000000000000000000100000000000000fF", &small),
+            Err(ZKNeuralError::InvalidAddress(_))
+        ));
+        assert_eq!(
+            parse_address("0x00000000000000000000000000000000000000fF", &small).unwrap(),
+            "255"
+        );
+    }
+
+    #[test]
+    fn test_parse_threshold_and_nonce() {
+        let field_modulus = FieldModulus::bn254();
+
+        assert_eq!(parse_threshold("0").unwrap(), "0");
+        assert_eq!(
+            parse_threshold("18446744073709551615").unwrap(),
+            "18446744073709551615"
+        );
+        assert!(matches!(
+            parse_threshold("18446744073709551616"),
+            Err(ZKNeuralError::InvalidThreshold(_))
+        ));
+        assert!(matches!(
+            parse_threshold("0.5"),
+            Err(ZKNeuralError::InvalidThreshold(_))
+        ));
+
+        assert_eq!(parse_nonce("0042", &field_modulus).unwrap(), "42");
+        assert!(matches!(
+            parse_nonce("", &field_modulus),
+            Err(ZKNeuralError::InvalidNonce(_))
+        ));
+        assert!(matches!(
+            parse_nonce(
+                "21888242871839275222246405745257275088548364400416034343698204186575808495617",
+                &field_modulus
+            ),
+            Err(ZKNeuralError::InvalidNonce(_))
+        ));
+    }
+}
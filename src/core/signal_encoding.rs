@@ -0,0 +1,495 @@
+use std::{str::FromStr, sync::LazyLock};
+
+use num_bigint::BigUint;
+use num_traits::{One, ToPrimitive, Zero};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+
+use crate::ZKNeuralError;
+
+const DEFAULT_PRECISION_BITS: u32 = 15;
+const MAX_PRECISION_BITS: u32 = 64;
+
+// Model outputs were divided by 255 before the `32768` multiplier in the original encoding.
+const DEFAULT_FEATURE_SCALE: f64 = 1.0 / 255.0;
+
+// Twice the size of the BN254 modulus keeps the bias of the modular reduction negligible.
+const RANDOM_ELEMENT_BYTES: usize = 64;
+
+// Miller-Rabin witnesses, the test is deterministic below 3.3 * 10^24 with the first thirteen.
+const MILLER_RABIN_BASES: [u32; 20] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71,
+];
+
+const BN254_SCALAR_FIELD_MODULUS: &str =
+    "21888242871839275222246405745257275088548364400416034343698204186575808495617";
+
+static BN254_MODULUS: LazyLock<BigUint> = LazyLock::new(|| {
+    BN254_SCALAR_FIELD_MODULUS
+        .parse()
+        .expect("Failed to parse BN254 modulus")
+});
+
+/// Prime modulus of the field the circuit signals live in, serialized as a decimal string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldModulus(BigUint);
+
+impl FieldModulus {
+    pub fn bn254() -> Self {
+        FieldModulus(BN254_MODULUS.clone())
+    }
+
+    pub fn value(&self) -> &BigUint {
+        &self.0
+    }
+
+    /// Parses a decimal field element, rejecting values outside of `[0, p)`.
+    pub fn parse_element(&self, element: &str) -> Result<BigUint, ZKNeuralError> {
+        let value: BigUint = element
+            .parse()
+            .map_err(|_| ZKNeuralError::InvalidFieldElement(element.to_string()))?;
+
+        if value >= self.0 {
+            return Err(ZKNeuralError::InvalidFieldElement(element.to_string()));
+        }
+
+        Ok(value)
+    }
+
+    /// Draws a field element from the OS CSPRNG.
+    pub fn random_element(&self) -> Result<BigUint, ZKNeuralError> {
+        let mut bytes = [0u8; RANDOM_ELEMENT_BYTES];
+
+        getrandom::fill(&mut bytes)
+            .map_err(|e| ZKNeuralError::RandomnessUnavailable(e.to_string()))?;
+
+        Ok(BigUint::from_bytes_le(&bytes) % &self.0)
+    }
+}
+
+impl Default for FieldModulus {
+    fn default() -> Self {
+        FieldModulus::bn254()
+    }
+}
+
+impl FromStr for FieldModulus {
+    type Err = ZKNeuralError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let modulus: BigUint = s.parse().map_err(|_| {
+            ZKNeuralError::InvalidSignalEncoding(format!("`{s}` is not a decimal field modulus"))
+        })?;
+
+        if modulus <= BigUint::from(2u8) {
+            return Err(ZKNeuralError::InvalidSignalEncoding(
+                "field modulus must be greater than 2".to_string(),
+            ));
+        }
+
+        if !is_probable_prime(&modulus) {
+            return Err(ZKNeuralError::InvalidSignalEncoding(format!(
+                "field modulus {modulus} is not prime"
+            )));
+        }
+
+        Ok(FieldModulus(modulus))
+    }
+}
+
+/// Miller-Rabin test of `n` against `MILLER_RABIN_BASES`.
+fn is_probable_prime(n: &BigUint) -> bool {
+    for base in MILLER_RABIN_BASES {
+        let base = BigUint::from(base);
+
+        if *n == base {
+            return true;
+        }
+        if (n % &base).is_zero() {
+            return false;
+        }
+    }
+
+    let n_minus_one = n - 1u8;
+    let s = n_minus_one.trailing_zeros().unwrap_or(0);
+    let d = &n_minus_one >> s;
+
+    'bases: for base in MILLER_RABIN_BASES {
+        let mut x = BigUint::from(base).modpow(&d, n);
+
+        if x.is_one() || x == n_minus_one {
+            continue;
+        }
+
+        for _ in 1..s {
+            x = &x * &x % n;
+
+            if x == n_minus_one {
+                continue 'bases;
+            }
+        }
+
+        return false;
+    }
+
+    true
+}
+
+impl Serialize for FieldModulus {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for FieldModulus {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+/// How a scaled value is turned into an integer.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RoundingMode {
+    Truncate,
+    Floor,
+    Ceil,
+    Nearest,
+}
+
+/// How negative fixed-point values are represented.
+///
+/// `FieldWrap` maps `-x` to `p - x` over the configured field, `Reject` fails the encoding.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NegativeHandling {
+    FieldWrap,
+    Reject,
+}
+
+/// Fixed-point encoding of real values into circuit signals.
+///
+/// A value `v` is encoded as `round(v * 2^precision_bits)` and emitted as a decimal field
+/// element. The default of 15 bits matches the `32768` multiplier the circuits were built with,
+/// the default field is the BN254 scalar field.
+///
+/// Model features are multiplied by `feature_scale` before the encoding. Its default of
+/// `1 / 255` keeps the features at the `x / 255 * 32768` scale of the original encoding, the
+/// signals are the integer part of what it emitted.
+///
+/// Field elements above `(p - 1) / 2` are treated as negative, so encoded values must stay
+/// below half of the modulus in magnitude to decode back to the same sign.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct SignalEncoding {
+    pub precision_bits: u32,
+    pub feature_scale: f64,
+    pub rounding: RoundingMode,
+    pub negative_handling: NegativeHandling,
+    pub field_modulus: FieldModulus,
+}
+
+impl Default for SignalEncoding {
+    fn default() -> Self {
+        SignalEncoding {
+            precision_bits: DEFAULT_PRECISION_BITS,
+            feature_scale: DEFAULT_FEATURE_SCALE,
+            rounding: RoundingMode::Truncate,
+            negative_handling: NegativeHandling::FieldWrap,
+            field_modulus: FieldModulus::default(),
+        }
+    }
+}
+
+impl SignalEncoding {
+    pub fn from_json(json: &[u8]) -> Result<Self, ZKNeuralError> {
+        let encoding: SignalEncoding = serde_json::from_slice(json)?;
+
+        encoding.validate()?;
+
+        Ok(encoding)
+    }
+
+    pub fn validate(&self) -> Result<(), ZKNeuralError> {
+        if self.precision_bits > MAX_PRECISION_BITS {
+            return Err(ZKNeuralError::InvalidSignalEncoding(format!(
+                "`precision_bits` must not exceed {MAX_PRECISION_BITS}"
+            )));
+        }
+
+        if !self.feature_scale.is_finite() || self.feature_scale <= 0.0 {
+            return Err(ZKNeuralError::InvalidSignalEncoding(
+                "`feature_scale` must be a positive number".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Scales and rounds the value to its fixed-point integer.
+    pub fn fixed_point(&self, value: f64) -> Result<i128, ZKNeuralError> {
+        let scaled = value * 2f64.powi(self.precision_bits as i32);
+
+        let rounded = match self.rounding {
+            RoundingMode::Truncate => scaled.trunc(),
+            RoundingMode::Floor => scaled.floor(),
+            RoundingMode::Ceil => scaled.ceil(),
+            RoundingMode::Nearest => scaled.round(),
+        };
+
+        if !rounded.is_finite() || rounded.abs() >= 2f64.powi(i128::BITS as i32 - 1) {
+            return Err(ZKNeuralError::SignalOutOfRange(value));
+        }
+
+        Ok(rounded as i128)
+    }
+
+    /// Encodes the value as a decimal field element.
+    pub fn encode(&self, value: f64) -> Result<String, ZKNeuralError> {
+        let fixed_point = self.fixed_point(value)?;
+
+        let magnitude = BigUint::from(fixed_point.unsigned_abs());
+        if magnitude > self.half_modulus() {
+            return Err(ZKNeuralError::SignalOutOfRange(value));
+        }
+
+        if fixed_point >= 0 {
+            return Ok(magnitude.to_string());
+        }
+
+        match self.negative_handling {
+            NegativeHandling::FieldWrap => Ok((self.field_modulus.value() - magnitude).to_string()),
+            NegativeHandling::Reject => Err(ZKNeuralError::NegativeSignal(value)),
+        }
+    }
+
+    pub fn encode_all(&self, values: &[f64]) -> Result<Vec<String>, ZKNeuralError> {
+        values.iter().map(|&value| self.encode(value)).collect()
+    }
+
+    /// Encodes model features, scaled by `feature_scale`.
+    pub fn encode_features(&self, features: &[f64]) -> Result<Vec<String>, ZKNeuralError> {
+        features
+            .iter()
+            .map(|&feature| self.encode(feature * self.feature_scale))
+            .collect()
+    }
+
+    /// Decodes a decimal field element back to the value it encodes, up to the precision lost
+    /// by rounding. Intended for debugging circuit inputs.
+    pub fn decode(&self, signal: &str) -> Result<f64, ZKNeuralError> {
+        let element = self.field_modulus.parse_element(signal)?;
+
+        let modulus = self.field_modulus.value();
+
+        let fixed_point = if element > self.half_modulus() {
+            -(modulus - element)
+                .to_f64()
+                .ok_or_else(|| ZKNeuralError::InvalidFieldElement(signal.to_string()))?
+        } else {
+            element
+                .to_f64()
+                .ok_or_else(|| ZKNeuralError::InvalidFieldElement(signal.to_string()))?
+        };
+
+        Ok(fixed_point / 2f64.powi(self.precision_bits as i32))
+    }
+
+    pub fn decode_all(&self, signals: &[String]) -> Result<Vec<f64>, ZKNeuralError> {
+        signals.iter().map(|signal| self.decode(signal)).collect()
+    }
+
+    /// Decodes signals of `encode_features` back to the model features.
+    pub fn decode_features(&self, signals: &[String]) -> Result<Vec<f64>, ZKNeuralError> {
+        signals
+            .iter()
+            .map(|signal| Ok(self.decode(signal)? / self.feature_scale))
+            .collect()
+    }
+
+    fn half_modulus(&self) -> BigUint {
+        (self.field_modulus.value() - 1u8) >> 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::{
+        ZKNeuralError,
+        core::signal_encoding::{FieldModulus, NegativeHandling, RoundingMode, SignalEncoding},
+    };
+
+    #[test]
+    fn test_default_encoding() {
+        let encoding = SignalEncoding::from_json(b"{}").unwrap();
+        assert_eq!(encoding, SignalEncoding::default());
+
+        assert_eq!(encoding.encode(1.0).unwrap(), "32768");
+        assert_eq!(encoding.encode(128.0 / 255.0).unwrap(), "16448");
+        assert_eq!(encoding.encode(0.0).unwrap(), "0");
+    }
+
+    #[test]
+    fn test_default_feature_encoding() {
+        let encoding = SignalEncoding::default();
+
+        // Integer parts of `x / 255 * 32768`, the features the original encoding emitted.
+        assert_eq!(
+            encoding.encode_features(&[255.0, 128.0, 0.5]).unwrap(),
+            ["32768", "16448", "64"]
+        );
+        assert_eq!(
+            encoding.encode_features(&[-0.25]).unwrap(),
+            ["21888242871839275222246405745257275088548364400416034343698204186575808495585"]
+        );
+
+        let signals = encoding.encode_features(&[255.0, -127.5]).unwrap();
+        assert_eq!(encoding.decode_features(&signals).unwrap(), [255.0, -127.5]);
+
+        let unscaled = SignalEncoding::from_json(br#"{"feature_scale": 1.0}"#).unwrap();
+        assert_eq!(unscaled.encode_features(&[0.5]).unwrap(), ["16384"]);
+
+        assert!(SignalEncoding::from_json(br#"{"feature_scale": 0.0}"#).is_err());
+    }
+
+    #[test]
+    fn test_rounding_modes() {
+        let encode = |rounding, value| {
+            SignalEncoding {
+                precision_bits: 2,
+                feature_scale: 1.0,
+                rounding,
+                negative_handling: NegativeHandling::Reject,
+                field_modulus: FieldModulus::default(),
+            }
+            .fixed_point(value)
+            .unwrap()
+        };
+
+        assert_eq!(encode(RoundingMode::Truncate, 1.6), 6);
+        assert_eq!(encode(RoundingMode::Floor, -1.6), -7);
+        assert_eq!(encode(RoundingMode::Truncate, -1.6), -6);
+        assert_eq!(encode(RoundingMode::Ceil, 1.1), 5);
+        assert_eq!(encode(RoundingMode::Nearest, 1.6), 6);
+        assert_eq!(encode(RoundingMode::Nearest, 1.7), 7);
+    }
+
+    #[test]
+    fn test_negative_handling() {
+        let encoding = SignalEncoding::default();
+
+        assert_eq!(
+            encoding.encode(-1.0 / 32768.0).unwrap(),
+            "21888242871839275222246405745257275088548364400416034343698204186575808495616"
+        );
+
+        let rejecting = SignalEncoding {
+            negative_handling: NegativeHandling::Reject,
+            ..SignalEncoding::default()
+        };
+        assert!(matches!(
+            rejecting.encode(-0.5),
+            Err(ZKNeuralError::NegativeSignal(_))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_values() {
+        let encoding = SignalEncoding::default();
+
+        assert!(matches!(
+            encoding.encode(f64::NAN),
+            Err(ZKNeuralError::SignalOutOfRange(_))
+        ));
+        assert!(matches!(
+            encoding.encode(1e40),
+            Err(ZKNeuralError::SignalOutOfRange(_))
+        ));
+
+        assert!(SignalEncoding::from_json(br#"{"precision_bits": 65}"#).is_err());
+    }
+
+    #[test]
+    fn test_mixed_sign_features_round_trip() {
+        let encoding = SignalEncoding::default();
+
+        let features = [0.25, -0.5, 0.0, -0.031_25, 1.5, -1.0];
+
+        let signals = encoding.encode_all(&features).unwrap();
+
+        assert_eq!(signals[0], "8192");
+        assert_eq!(
+            signals[1],
+            "21888242871839275222246405745257275088548364400416034343698204186575808479233"
+        );
+        assert_eq!(signals[2], "0");
+
+        assert_eq!(encoding.decode_all(&signals).unwrap(), features);
+    }
+
+    #[test]
+    fn test_custom_field() {
+        let encoding =
+            SignalEncoding::from_json(br#"{"precision_bits": 4, "field_modulus": "2147483647"}"#)
+                .unwrap();
+
+        assert_eq!(encoding.encode(-1.0).unwrap(), "2147483631");
+        assert_eq!(encoding.decode("2147483631").unwrap(), -1.0);
+        assert_eq!(encoding.decode("16").unwrap(), 1.0);
+
+        assert!(matches!(
+            encoding.encode(1e9),
+            Err(ZKNeuralError::SignalOutOfRange(_))
+        ));
+        assert!(matches!(
+            encoding.decode("2147483647"),
+            Err(ZKNeuralError::InvalidFieldElement(_))
+        ));
+        assert!(matches!(
+            encoding.decode("-5"),
+            Err(ZKNeuralError::InvalidFieldElement(_))
+        ));
+
+        assert!(SignalEncoding::from_json(br#"{"field_modulus": "0x11"}"#).is_err());
+    }
+
+    #[test]
+    fn test_composite_field_modulus() {
+        for modulus in ["3", "97", "2305843009213693951"] {
+            assert!(FieldModulus::from_str(modulus).is_ok());
+        }
+        assert_eq!(
+            FieldModulus::from_str(&FieldModulus::bn254().value().to_string()).unwrap(),
+            FieldModulus::bn254()
+        );
+
+        for modulus in ["4", "15", "561", "4951760154835678088235319297"] {
+            assert!(matches!(
+                FieldModulus::from_str(modulus),
+                Err(ZKNeuralError::InvalidSignalEncoding(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_random_element() {
+        let modulus = FieldModulus::bn254();
+
+        let first = modulus.random_element().unwrap();
+        let second = modulus.random_element().unwrap();
+
+        assert!(&first < modulus.value());
+        assert_ne!(first, second);
+
+        let small = FieldModulus::from_str("2147483647").unwrap();
+        for _ in 0..16 {
+            assert!(&small.random_element().unwrap() < small.value());
+        }
+
+        assert!(modulus.parse_element(&first.to_string()).is_ok());
+        assert!(modulus.parse_element(&modulus.value().to_string()).is_err());
+    }
+}
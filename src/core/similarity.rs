@@ -0,0 +1,147 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ZKNeuralError,
+    core::{circuit_reference, signal_encoding::SignalEncoding},
+};
+
+/// Comparison of two face embeddings.
+///
+/// `distance` is the squared L2 distance of the fixed-point encoded embeddings computed by
+/// `circuit_reference::evaluate`, so it is scaled by `(feature_scale * 2^precision_bits)^2`.
+/// The faces match when it is strictly below `threshold`.
+///
+/// `matched` is a local estimate for an on-device pre-check. It follows the unverified model
+/// of the circuit comparison in `circuit_reference` and does not guarantee that a proof with
+/// the same threshold verifies.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
+pub struct FaceMatch {
+    pub cosine_similarity: f64,
+    pub l2_distance: f64,
+    pub distance: String,
+    pub threshold: String,
+    pub matched: bool,
+}
+
+impl FaceMatch {
+    pub fn compare(
+        embedding_a: &[f64],
+        embedding_b: &[f64],
+        threshold: &str,
+        signal_encoding: &SignalEncoding,
+    ) -> Result<Self, ZKNeuralError> {
+        let cosine_similarity = cosine_similarity(embedding_a, embedding_b)?;
+        let l2_distance = l2_distance(embedding_a, embedding_b)?;
+
+        let reference = circuit_reference::evaluate(
+            &signal_encoding.encode_features(embedding_a)?,
+            &signal_encoding.encode_features(embedding_b)?,
+            threshold,
+            &signal_encoding.field_modulus,
+        )?;
+
+        Ok(FaceMatch {
+            cosine_similarity,
+            l2_distance,
+            distance: reference.score,
+            threshold: reference.threshold,
+            matched: reference.passed,
+        })
+    }
+}
+
+pub fn cosine_similarity(a: &[f64], b: &[f64]) -> Result<f64, ZKNeuralError> {
+    check_sizes(a.len(), b.len())?;
+
+    let dot: f64 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+
+    let norm_a = a.iter().map(|x| x * x).sum::<f64>().sqrt();
+    let norm_b = b.iter().map(|y| y * y).sum::<f64>().sqrt();
+
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return Err(ZKNeuralError::InvalidEmbedding(
+            "embedding has zero norm".to_string(),
+        ));
+    }
+
+    Ok(dot / (norm_a * norm_b))
+}
+
+pub fn l2_distance(a: &[f64], b: &[f64]) -> Result<f64, ZKNeuralError> {
+    check_sizes(a.len(), b.len())?;
+
+    Ok(a.iter()
+        .zip(b)
+        .map(|(x, y)| (x - y) * (x - y))
+        .sum::<f64>()
+        .sqrt())
+}
+
+fn check_sizes(size_a: usize, size_b: usize) -> Result<(), ZKNeuralError> {
+    if size_a != size_b {
+        return Err(ZKNeuralError::EmbeddingSizeMismatch(size_a, size_b));
+    }
+
+    if size_a == 0 {
+        return Err(ZKNeuralError::InvalidEmbedding(
+            "embedding is empty".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ZKNeuralError,
+        core::{
+            signal_encoding::SignalEncoding,
+            similarity::{FaceMatch, cosine_similarity, l2_distance},
+        },
+    };
+
+    #[test]
+    fn test_float_similarity() {
+        let a = [0.6, 0.8, 0.0];
+        let b = [0.0, 0.6, -0.8];
+
+        assert!((cosine_similarity(&a, &a).unwrap() - 1.0).abs() < 1e-12);
+        assert!((cosine_similarity(&a, &b).unwrap() - 0.48).abs() < 1e-12);
+        assert!((l2_distance(&a, &b).unwrap() - 1.04f64.sqrt()).abs() < 1e-12);
+
+        assert!(matches!(
+            cosine_similarity(&a, &[1.0]),
+            Err(ZKNeuralError::EmbeddingSizeMismatch(3, 1))
+        ));
+        assert!(matches!(
+            cosine_similarity(&a, &[0.0, 0.0, 0.0]),
+            Err(ZKNeuralError::InvalidEmbedding(_))
+        ));
+    }
+
+    #[test]
+    fn test_face_match() {
+        let encoding = SignalEncoding {
+            feature_scale: 1.0,
+            ..SignalEncoding::default()
+        };
+
+        let a = [0.6, 0.8, 0.0];
+        let b = [0.0, 0.6, -0.8];
+
+        // Squared distance of 1.04 at a scale of 2^30, truncated per element.
+        let face_match = FaceMatch::compare(&a, &b, "1200000000", &encoding).unwrap();
+        assert_eq!(face_match.distance, "1116644312");
+        assert!(face_match.matched);
+
+        let face_match = FaceMatch::compare(&a, &b, "1116644312", &encoding).unwrap();
+        assert!(!face_match.matched);
+
+        assert!(matches!(
+            FaceMatch::compare(&a, &b, "-1", &encoding),
+            Err(ZKNeuralError::InvalidThreshold(_))
+        ));
+    }
+}
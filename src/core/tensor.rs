@@ -1,23 +1,58 @@
-use image::{EncodableLayout, imageops::FilterType};
-use num_traits::{Float, FromBytes, PrimInt, ToBytes, ToPrimitive};
-use tflitec::{
-    interpreter::Interpreter,
-    model::Model,
-    tensor::{DataType, Shape},
+use std::{
+    collections::HashMap,
+    sync::{Arc, OnceLock},
 };
 
-use crate::{ZKNeuralError, core::face_detection::FaceDetector};
+use image::DynamicImage;
+use num_traits::{Float, FromBytes, PrimInt, ToBytes};
+
+use crate::{
+    ZKNeuralError,
+    core::{
+        ZKNeuralProvingType,
+        circuit_reference::{self, CircuitReferenceResult},
+        debug_dump::DebugDump,
+        face_detection::FaceDetector,
+        face_quality::{FaceQuality, FaceQualityThresholds},
+        image_source::ImageSource,
+        inference::{
+            self, DataType, InferenceBackend, InferenceSession, Shape, TensorSpec, default_backend,
+        },
+        input_schema::{BIONETTA_GENERIC_SCHEMA, InputSchema, SignalValues},
+        liveness::LivenessChecker,
+        model_integrity::{model_hash, parse_model_hash},
+        preprocessing::PreprocessingSpec,
+        public_inputs::{parse_address, parse_nonce, parse_threshold},
+        signal_encoding::SignalEncoding,
+        similarity::FaceMatch,
+    },
+};
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{Map, Value};
+use tracing::{debug, debug_span, info_span, warn};
 
+/// Runs a model on images, optionally recording the face crop and the model input and output
+/// tensors to `debug_dump` on top of the detector artifacts.
 pub struct TensorInvoker {
-    pub model_data: Vec<u8>,
+    model_data: Vec<u8>,
     pub input_shape: Shape,
     pub input_data_type: DataType,
     pub should_process: bool,
+    pub apply_exif_orientation: bool,
+    pub face_quality_thresholds: Option<FaceQualityThresholds>,
+    pub liveness_checker: Option<Box<LivenessChecker>>,
+    pub preprocessing_spec: PreprocessingSpec,
+    pub signal_encoding: SignalEncoding,
+    input_schemas: HashMap<String, InputSchema>,
+    pub backend: Arc<dyn InferenceBackend>,
+    pub debug_dump: Option<Arc<DebugDump>>,
+    model_hash: OnceLock<String>,
+    allowed_model_hashes: Option<Vec<String>>,
+    face_detector: OnceLock<FaceDetector>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
 pub struct BionettaGenericInputs {
     pub ultra_groth: String,
     pub address: String,
@@ -28,96 +63,309 @@ pub struct BionettaGenericInputs {
     pub rand: String,
 }
 
+/// Caller-provided values of `BionettaGenericInputs`.
+///
+/// `address` is an Ethereum address in `0x` hex form or a decimal field element, `threshold`
+/// a decimal integer of at most 64 bits and `nonce` a decimal field element.
+///
+/// `rand` is the blinding value, when `None` a fresh one is drawn from the OS CSPRNG. Either
+/// way it is returned in the drained inputs so the host can persist it.
+pub struct GenericInputsParams {
+    pub address: String,
+    pub threshold: String,
+    pub nonce: String,
+    pub rand: Option<String>,
+    pub proving_type: ZKNeuralProvingType,
+}
+
+/// Caller-provided values of a registered `InputSchema`.
+///
+/// `values` holds the caller signals by name, `rand` and `proving_type` feed the `rand` and
+/// `ultra_groth` sources the same way as in `GenericInputsParams`.
+pub struct SchemaInputsParams {
+    pub values: Map<String, Value>,
+    pub rand: Option<String>,
+    pub proving_type: Option<ZKNeuralProvingType>,
+}
+
+/// Result of a single image of `TensorInvoker::fire_batch`, holding either the same output
+/// `TensorInvoker::fire` produces or the error that image failed with.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BatchFireResult {
+    pub output: Option<Value>,
+    pub error: Option<String>,
+}
+
 #[repr(C)]
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Enum))]
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
 pub enum ImagePreprocessing {
     None,
     FaceRecognition,
 }
 
-const NEURAL_SIGNAL_MULTIPLIER: f64 = 32768.0;
-
 impl TensorInvoker {
     pub fn new(model_data: &[u8], should_process: bool) -> Result<Self, ZKNeuralError> {
-        let model = Model::from_bytes(&model_data)?;
-
-        let interpreter = Interpreter::new(&model, None)?;
-
-        interpreter.allocate_tensors()?;
-
-        let input = interpreter.input(0)?;
-
-        let input_shape = input.shape().clone();
+        Self::with_backend(model_data, should_process, default_backend()?)
+    }
 
-        let input_data_type = input.data_type();
+    /// Creates an invoker running the model with `backend` instead of the default one.
+    pub fn with_backend(
+        model_data: &[u8],
+        should_process: bool,
+        backend: Arc<dyn InferenceBackend>,
+    ) -> Result<Self, ZKNeuralError> {
+        let input = backend.session(model_data, |session| session.input())?;
+
+        debug!(
+            model_size = model_data.len(),
+            input_shape = ?input.shape.dimensions(),
+            input_data_type = ?input.data_type,
+            "model loaded"
+        );
 
         Ok(TensorInvoker {
             model_data: model_data.to_vec(),
-            input_shape,
-            input_data_type,
+            input_shape: input.shape,
+            input_data_type: input.data_type,
             should_process,
+            apply_exif_orientation: true,
+            face_quality_thresholds: None,
+            liveness_checker: None,
+            preprocessing_spec: PreprocessingSpec::default(),
+            signal_encoding: SignalEncoding::default(),
+            input_schemas: HashMap::from([(
+                BIONETTA_GENERIC_SCHEMA.to_string(),
+                InputSchema::bionetta_generic(),
+            )]),
+            backend,
+            debug_dump: None,
+            model_hash: OnceLock::new(),
+            allowed_model_hashes: None,
+            face_detector: OnceLock::new(),
         })
     }
 
-    pub fn prepare_image_by_spec(
+    /// The detector of `FaceRecognition` preprocessing, created on first use and kept so
+    /// BlazeFace is loaded once per invoker.
+    fn face_detector(&self) -> &FaceDetector {
+        self.face_detector
+            .get_or_init(|| FaceDetector::with_backend(Default::default(), self.backend.clone()))
+    }
+
+    /// The serialized model, fixed at creation so it always matches `model_hash`.
+    pub fn model_data(&self) -> &[u8] {
+        &self.model_data
+    }
+
+    /// SHA-256 of the model, hashed on first use.
+    ///
+    /// Check it against the model hash of the circuit configuration, e.g.
+    /// `ARCFACE_MODEL_SHA256`, before trusting the generated inputs.
+    pub fn model_hash(&self) -> &str {
+        self.model_hash
+            .get_or_init(|| model_hash(self.model_data()))
+    }
+
+    /// Restricts the invoker to models with one of the given hex SHA-256 hashes, `None` lifts
+    /// the restriction.
+    ///
+    /// The allow-list is kept even when the loaded model is not on it, so every following
+    /// invocation fails with `ModelHashNotAllowed` rather than producing unprovable inputs.
+    pub fn set_allowed_model_hashes(
+        &mut self,
+        hashes: Option<Vec<String>>,
+    ) -> Result<(), ZKNeuralError> {
+        self.allowed_model_hashes = hashes
+            .map(|hashes| hashes.iter().map(|hash| parse_model_hash(hash)).collect())
+            .transpose()?;
+
+        self.verify_model_hash()
+    }
+
+    pub fn allowed_model_hashes(&self) -> Option<&[String]> {
+        self.allowed_model_hashes.as_deref()
+    }
+
+    /// Fails with `ModelHashNotAllowed` if an allow-list is set and the model is not on it.
+    pub fn verify_model_hash(&self) -> Result<(), ZKNeuralError> {
+        let Some(allowed_model_hashes) = &self.allowed_model_hashes else {
+            return Ok(());
+        };
+
+        let model_hash = self.model_hash();
+
+        if !allowed_model_hashes.iter().any(|hash| hash == model_hash) {
+            warn!(model_hash, "model is not on the allow-list");
+
+            return Err(ZKNeuralError::ModelHashNotAllowed(model_hash.to_string()));
+        }
+
+        Ok(())
+    }
+
+    pub fn prepare_image_by_spec<'a>(
         &self,
-        image_data: &[u8],
+        image: impl Into<ImageSource<'a>>,
         image_preprocessing: ImagePreprocessing,
     ) -> Result<(Vec<u8>, Vec<String>), ZKNeuralError> {
-        let preprocessed_image_data = match image_preprocessing {
-            ImagePreprocessing::FaceRecognition => {
-                FaceDetector::detect_face(image_data)?.as_bytes().to_vec()
-            }
-            ImagePreprocessing::None => image_data.to_vec(),
-        };
+        let preprocessed_image = self.preprocess_image(image, image_preprocessing)?;
 
-        let input_dimentions = self.input_shape.dimensions();
-        if input_dimentions.len() != 4 {
-            return Err(ZKNeuralError::ModelNotFourDimensional);
-        }
+        self.prepare_loaded_image(&preprocessed_image)
+    }
+
+    /// Decodes the image and, for `FaceRecognition`, crops it to the highest scoring face
+    /// after checking the face quality thresholds.
+    pub fn preprocess_image<'a>(
+        &self,
+        image: impl Into<ImageSource<'a>>,
+        image_preprocessing: ImagePreprocessing,
+    ) -> Result<DynamicImage, ZKNeuralError> {
+        let _span = debug_span!("preprocess_image", preprocessing = ?image_preprocessing).entered();
 
-        let width = input_dimentions[1];
-        let height = input_dimentions[2];
-        let channels = input_dimentions[3];
+        let decoded_image = image.into().decode(self.apply_exif_orientation)?;
 
-        let loaded_image = image::load_from_memory(&preprocessed_image_data)?.resize_exact(
-            width as u32,
-            height as u32,
-            FilterType::CatmullRom,
+        debug!(
+            width = decoded_image.width(),
+            height = decoded_image.height(),
+            "image decoded"
         );
 
-        let prepared_image: Vec<u8> = match channels {
-            1 => loaded_image.grayscale().as_bytes().to_vec(),
-            3 => loaded_image.to_rgb8().as_bytes().to_vec(),
-            _ => return Err(ZKNeuralError::InvalidModelChannel),
-        };
+        match image_preprocessing {
+            ImagePreprocessing::FaceRecognition => {
+                let detection = self
+                    .face_detector()
+                    .detect_best_face_recorded(&decoded_image, self.debug_dump.as_deref())?;
+
+                if let Some(thresholds) = &self.face_quality_thresholds {
+                    let quality = FaceQuality::assess(&decoded_image, &detection);
+
+                    debug!(
+                        face_size = quality.face_size,
+                        sharpness = quality.sharpness,
+                        brightness = quality.brightness,
+                        yaw = quality.yaw,
+                        roll = quality.roll,
+                        "face quality assessed"
+                    );
+
+                    quality.check(thresholds)?;
+                }
+
+                let face_image = FaceDetector::crop_detection(&decoded_image, &detection);
+
+                if let Some(debug_dump) = &self.debug_dump {
+                    debug_dump.record_image("face_crop.png", &face_image);
+                }
+
+                Ok(face_image)
+            }
+            ImagePreprocessing::None => {
+                if let Some(debug_dump) = &self.debug_dump {
+                    debug_dump.record_image("decoded_image.png", &decoded_image);
+                }
 
-        match self.input_data_type {
-            DataType::Uint8 => Ok(prepare_data_by_type::<u8>(prepared_image)),
-            DataType::Int16 => Ok(prepare_data_by_type::<i16>(prepared_image)),
-            DataType::Int32 => Ok(prepare_data_by_type::<i32>(prepared_image)),
-            DataType::Int64 => Ok(prepare_data_by_type::<i64>(prepared_image)),
-            DataType::Float32 => Ok(prepare_data_by_float_type::<f32>(prepared_image)),
-            DataType::Float64 => Ok(prepare_data_by_float_type::<f64>(prepared_image)),
-            _ => Err(ZKNeuralError::InvalidModelDataType),
+                Ok(decoded_image)
+            }
         }
     }
 
+    /// Resizes an already preprocessed image to the model input and converts it to the
+    /// input tensor data and its circuit signals.
+    pub fn prepare_loaded_image(
+        &self,
+        preprocessed_image: &DynamicImage,
+    ) -> Result<(Vec<u8>, Vec<String>), ZKNeuralError> {
+        let (data, signal_data) = self.preprocessing_spec.prepare_tensor(
+            preprocessed_image,
+            &self.input_shape,
+            self.input_data_type,
+            &self.signal_encoding,
+        )?;
+
+        if let Some(debug_dump) = &self.debug_dump {
+            let spec = TensorSpec {
+                shape: self.input_shape.clone(),
+                data_type: self.input_data_type,
+            };
+
+            debug_dump.record_json("model_input.json", &spec);
+            debug_dump.record("model_input.bin", data.clone());
+        }
+
+        Ok((data, signal_data))
+    }
+
     pub fn fire(&self, data: &[u8]) -> Result<Vec<u8>, ZKNeuralError> {
-        let model = Model::from_bytes(&self.model_data)?;
+        let _span = debug_span!("fire", input_size = data.len()).entered();
 
-        let interpreter = Interpreter::new(&model, None)?;
+        self.verify_model_hash()?;
 
-        interpreter.allocate_tensors()?;
+        self.backend
+            .session(&self.model_data, |session| self.fire_with(session, data))
+    }
 
-        interpreter.copy(data, 0)?;
+    /// Prepares and invokes every image on a single session.
+    ///
+    /// Failures are reported per image, so one unusable frame does not discard the others.
+    pub fn fire_batch(
+        &self,
+        images: &[ImageSource],
+        image_preprocessing: ImagePreprocessing,
+    ) -> Result<Vec<BatchFireResult>, ZKNeuralError> {
+        let _span = info_span!("fire_batch", images = images.len()).entered();
+
+        self.verify_model_hash()?;
+
+        self.backend.session(&self.model_data, |session| {
+            let results = images
+                .iter()
+                .map(|image| {
+                    self.prepare_image_by_spec(image, image_preprocessing)
+                        .and_then(|(data, _)| self.fire_with(session, &data))
+                        .and_then(|output| Ok(serde_json::from_slice::<Value>(&output)?))
+                })
+                .map(|result| match result {
+                    Ok(output) => BatchFireResult {
+                        output: Some(output),
+                        error: None,
+                    },
+                    Err(e) => BatchFireResult {
+                        output: None,
+                        error: Some(e.to_string()),
+                    },
+                })
+                .collect();
+
+            Ok(results)
+        })
+    }
 
-        interpreter.invoke()?;
+    fn fire_with(
+        &self,
+        session: &dyn InferenceSession,
+        data: &[u8],
+    ) -> Result<Vec<u8>, ZKNeuralError> {
+        let outputs = session.invoke(data)?;
 
-        let output_tensor = interpreter.output(0)?;
+        let output_tensor = inference::output(&outputs, 0)?;
 
-        let output_data = output_tensor.data::<u8>().to_vec();
+        debug!(
+            outputs = outputs.len(),
+            output_shape = ?output_tensor.spec.shape.dimensions(),
+            output_data_type = ?output_tensor.spec.data_type,
+            "model invoked"
+        );
+
+        if let Some(debug_dump) = &self.debug_dump {
+            debug_dump.record_json("model_output.json", &output_tensor.spec);
+            debug_dump.record("model_output.bin", output_tensor.data.clone());
+        }
 
-        match output_tensor.data_type() {
+        let output_data = output_tensor.data.clone();
+
+        match output_tensor.spec.data_type {
             DataType::Uint8 => {
                 let collected = collect_processed_data_to::<u8>(output_data);
 
@@ -156,35 +404,244 @@ impl TensorInvoker {
         }
     }
 
-    pub fn drain_generic_inputs(
+    /// Runs the model on the image and returns its output as an embedding.
+    pub fn embed<'a>(
+        &self,
+        image: impl Into<ImageSource<'a>>,
+        image_preprocessing: ImagePreprocessing,
+    ) -> Result<Vec<f64>, ZKNeuralError> {
+        let (data, _) = self.prepare_image_by_spec(image, image_preprocessing)?;
+
+        let serialized_embedding = self.fire(&data)?;
+
+        Ok(serde_json::from_slice(&serialized_embedding)?)
+    }
+
+    /// Compares the embeddings of the best faces of two images against `threshold`, `matched`
+    /// is a local estimate, see `FaceMatch`.
+    pub fn match_faces<'a, 'b>(
+        &self,
+        image_a: impl Into<ImageSource<'a>>,
+        image_b: impl Into<ImageSource<'b>>,
+        threshold: &str,
+    ) -> Result<FaceMatch, ZKNeuralError> {
+        let _span = info_span!("match_faces").entered();
+
+        let embedding_a = self.embed(image_a, ImagePreprocessing::FaceRecognition)?;
+        let embedding_b = self.embed(image_b, ImagePreprocessing::FaceRecognition)?;
+
+        let face_match = self.compare_embeddings(&embedding_a, &embedding_b, threshold)?;
+
+        debug!(matched = face_match.matched, "faces compared");
+
+        Ok(face_match)
+    }
+
+    pub fn compare_embeddings(
         &self,
-        address: String,
-        threshold: String,
-        nonce: String,
-        image_data: &[u8],
+        embedding_a: &[f64],
+        embedding_b: &[f64],
+        threshold: &str,
+    ) -> Result<FaceMatch, ZKNeuralError> {
+        FaceMatch::compare(embedding_a, embedding_b, threshold, &self.signal_encoding)
+    }
+
+    /// Checks drained inputs against a reference template with the local model of the
+    /// threshold comparison, see `circuit_reference`, before spending time on witness
+    /// generation.
+    pub fn evaluate_circuit_reference(
+        &self,
+        inputs: &BionettaGenericInputs,
+        template: &[String],
+    ) -> Result<CircuitReferenceResult, ZKNeuralError> {
+        circuit_reference::evaluate_inputs(inputs, template, &self.signal_encoding.field_modulus)
+    }
+
+    /// Scores the preprocessed image with the liveness model, higher is more likely live.
+    pub fn liveness_score<'a>(
+        &self,
+        image: impl Into<ImageSource<'a>>,
+        image_preprocessing: ImagePreprocessing,
+    ) -> Result<f32, ZKNeuralError> {
+        let liveness_checker = self
+            .liveness_checker
+            .as_ref()
+            .ok_or(ZKNeuralError::LivenessModelNotSet)?;
+
+        let preprocessed_image = self.preprocess_image(image, image_preprocessing)?;
+
+        liveness_checker.score(&preprocessed_image)
+    }
+
+    pub fn drain_generic_inputs<'a>(
+        &self,
+        params: GenericInputsParams,
+        image: impl Into<ImageSource<'a>>,
         image_preprocessing: ImagePreprocessing,
     ) -> Result<Vec<u8>, ZKNeuralError> {
-        let (data, signal_data) = self.prepare_image_by_spec(image_data, image_preprocessing)?;
+        let _span =
+            info_span!("drain_generic_inputs", proving_type = ?params.proving_type).entered();
 
-        let serialized_features = self.fire(&data)?;
+        let field_modulus = &self.signal_encoding.field_modulus;
 
-        let features = parse_json_numbers_to_strings_unchecked(&serialized_features);
+        let address = parse_address(&params.address, field_modulus)?;
+        let threshold = parse_threshold(&params.threshold)?;
+        let nonce = parse_nonce(&params.nonce, field_modulus)?;
+
+        let (features, signal_data) = self.drain_signals(image, image_preprocessing)?;
+
+        let rand = self.blinding(params.rand.as_deref())?;
 
         let inputs = BionettaGenericInputs {
-            ultra_groth: "1".to_string(),
+            ultra_groth: params.proving_type.ultra_groth_signal().to_string(),
             address,
             threshold,
             nonce,
-            features: features,
+            features,
+            image: signal_data,
+            rand,
+        };
+
+        Ok(serde_json::to_vec(&inputs)?)
+    }
+
+    pub fn register_input_schema(
+        &mut self,
+        name: impl Into<String>,
+        schema: InputSchema,
+    ) -> Result<(), ZKNeuralError> {
+        let name = name.into();
+
+        if name == BIONETTA_GENERIC_SCHEMA {
+            return Err(ZKNeuralError::InvalidInputSchema(format!(
+                "`{BIONETTA_GENERIC_SCHEMA}` is the built-in schema and cannot be replaced"
+            )));
+        }
+
+        schema.validate()?;
+
+        self.input_schemas.insert(name, schema);
+
+        Ok(())
+    }
+
+    /// Drains the inputs of the registered schema `schema_name`, failing if a required
+    /// signal is missing.
+    ///
+    /// The address, threshold and nonce of the built-in `bionetta_generic` schema are checked
+    /// and normalized as in `drain_generic_inputs`.
+    pub fn drain_inputs<'a>(
+        &self,
+        schema_name: &str,
+        params: SchemaInputsParams,
+        image: impl Into<ImageSource<'a>>,
+        image_preprocessing: ImagePreprocessing,
+    ) -> Result<Vec<u8>, ZKNeuralError> {
+        let _span = info_span!("drain_inputs", schema = schema_name).entered();
+
+        let schema = self
+            .input_schemas
+            .get(schema_name)
+            .ok_or_else(|| ZKNeuralError::InputSchemaNotFound(schema_name.to_string()))?;
+
+        let mut caller = params.values;
+        if schema_name == BIONETTA_GENERIC_SCHEMA {
+            self.parse_generic_values(&mut caller)?;
+        }
+
+        let (features, signal_data) = self.drain_signals(image, image_preprocessing)?;
+
+        let values = SignalValues {
+            features,
             image: signal_data,
-            rand: "0".to_string(),
+            caller,
+            rand: self.blinding(params.rand.as_deref())?,
+            ultra_groth: params
+                .proving_type
+                .map(|proving_type| proving_type.ultra_groth_signal().to_string()),
         };
 
+        let inputs = schema.build(values, &self.signal_encoding.field_modulus)?;
+
         Ok(serde_json::to_vec(&inputs)?)
     }
+
+    /// Replaces the address, threshold and nonce among the caller values with their parsed
+    /// form. Missing values are left to the schema.
+    fn parse_generic_values(&self, values: &mut Map<String, Value>) -> Result<(), ZKNeuralError> {
+        let field_modulus = &self.signal_encoding.field_modulus;
+
+        let value = |values: &Map<String, Value>, name: &str| {
+            values.get(name).map(|value| match value {
+                Value::String(value) => value.clone(),
+                value => value.to_string(),
+            })
+        };
+
+        if let Some(address) = value(values, "address") {
+            let address = parse_address(&address, field_modulus)?;
+            values.insert("address".to_string(), Value::from(address));
+        }
+
+        if let Some(threshold) = value(values, "threshold") {
+            let threshold = parse_threshold(&threshold)?;
+            values.insert("threshold".to_string(), Value::from(threshold));
+        }
+
+        if let Some(nonce) = value(values, "nonce") {
+            let nonce = parse_nonce(&nonce, field_modulus)?;
+            values.insert("nonce".to_string(), Value::from(nonce));
+        }
+
+        Ok(())
+    }
+
+    /// Runs the model on the image and returns the encoded features and image signals.
+    fn drain_signals<'a>(
+        &self,
+        image: impl Into<ImageSource<'a>>,
+        image_preprocessing: ImagePreprocessing,
+    ) -> Result<(Vec<String>, Vec<String>), ZKNeuralError> {
+        let preprocessed_image = self.preprocess_image(image, image_preprocessing)?;
+
+        if let Some(liveness_checker) = &self.liveness_checker {
+            liveness_checker.check(&preprocessed_image)?;
+        }
+
+        let (data, signal_data) = self.prepare_loaded_image(&preprocessed_image)?;
+
+        let serialized_features = self.fire(&data)?;
+
+        let features: Vec<f64> = serde_json::from_slice(&serialized_features)?;
+
+        debug!(
+            features = features.len(),
+            image_signals = signal_data.len(),
+            "signals encoded"
+        );
+
+        Ok((
+            self.signal_encoding.encode_features(&features)?,
+            signal_data,
+        ))
+    }
+
+    fn blinding(&self, rand: Option<&str>) -> Result<String, ZKNeuralError> {
+        let field_modulus = &self.signal_encoding.field_modulus;
+
+        let rand = match rand {
+            Some(rand) => field_modulus.parse_element(rand)?,
+            None => field_modulus.random_element()?,
+        };
+
+        Ok(rand.to_string())
+    }
 }
 
-pub fn prepare_data_by_float_type<T: Float + ToBytes>(data: Vec<u8>) -> (Vec<u8>, Vec<String>) {
+pub fn prepare_data_by_float_type<T: Float + ToBytes>(
+    data: Vec<u8>,
+    signal_encoding: &SignalEncoding,
+) -> Result<(Vec<u8>, Vec<String>), ZKNeuralError> {
     let mut float_data: Vec<T> = vec![];
     for &byte in data.iter() {
         let float_value = T::from(byte).expect("Failed to convert byte to float type");
@@ -194,24 +651,37 @@ pub fn prepare_data_by_float_type<T: Float + ToBytes>(data: Vec<u8>) -> (Vec<u8>
         float_data.push(float_value / multiplier);
     }
 
-    let result_data = float_data
-        .clone()
+    collect_float_data(float_data, signal_encoding)
+}
+
+pub fn prepare_normalized_data_by_float_type<T: Float + ToBytes>(
+    data: Vec<f64>,
+    signal_encoding: &SignalEncoding,
+) -> Result<(Vec<u8>, Vec<String>), ZKNeuralError> {
+    let float_data: Vec<T> = data
         .into_iter()
+        .map(|value| T::from(value).expect("Failed to convert value to float type"))
+        .collect();
+
+    collect_float_data(float_data, signal_encoding)
+}
+
+fn collect_float_data<T: Float + ToBytes>(
+    float_data: Vec<T>,
+    signal_encoding: &SignalEncoding,
+) -> Result<(Vec<u8>, Vec<String>), ZKNeuralError> {
+    let result_data = float_data
+        .iter()
         .map(|f| f.to_le_bytes().as_ref().to_vec())
         .flatten()
         .collect();
 
-    let result_signal_data: Vec<String> = float_data
+    let result_signal_data = float_data
         .into_iter()
-        .map(|f| {
-            (f.to_f64().unwrap() * NEURAL_SIGNAL_MULTIPLIER)
-                .to_i64()
-                .unwrap()
-                .to_string()
-        })
-        .collect();
+        .map(|f| signal_encoding.encode(f.to_f64().unwrap()))
+        .collect::<Result<Vec<String>, ZKNeuralError>>()?;
 
-    (result_data, result_signal_data)
+    Ok((result_data, result_signal_data))
 }
 
 pub fn prepare_data_by_type<T: PrimInt + ToBytes + ToString>(
@@ -271,37 +741,246 @@ where
         .collect()
 }
 
-pub fn parse_json_numbers_to_strings_unchecked(json_bytes: &[u8]) -> Vec<String> {
-    let value: Value = serde_json::from_slice(json_bytes).expect("Failed to parse JSON");
+#[cfg(test)]
+mod tests {
+    use std::{fs::File, io::Read, sync::Arc};
+
+    use serde_json::{Value, json};
+    #[cfg(feature = "tflite")]
+    use tflitec::{interpreter::Interpreter, model::Model};
 
-    let Value::Array(arr) = value else {
-        panic!("Expected a JSON array");
+    use crate::{
+        ZKNeuralError,
+        core::{
+            ZKNeuralProvingType,
+            debug_dump::{DebugDump, DebugDumpOptions},
+            image_source::ImageSource,
+            inference::{DataType, InferenceBackend, InferenceSession, Shape, Tensor, TensorSpec},
+            input_schema::{BIONETTA_GENERIC_SCHEMA, InputSchema},
+            model_integrity::model_hash,
+            signal_encoding::SignalEncoding,
+            tensor::{
+                BionettaGenericInputs, GenericInputsParams, ImagePreprocessing, SchemaInputsParams,
+                TensorInvoker,
+            },
+        },
     };
 
-    arr.iter()
-        .map(|v| {
-            let Value::Number(number) = v else {
-                panic!("Expected a JSON number");
+    /// Backend of a model with a 2x2 RGB input that always outputs `[0.5, -0.25]`.
+    struct FixedOutputBackend;
+
+    impl InferenceBackend for FixedOutputBackend {
+        fn with_session(
+            &self,
+            _model: &[u8],
+            f: &mut dyn FnMut(&dyn InferenceSession) -> Result<(), ZKNeuralError>,
+        ) -> Result<(), ZKNeuralError> {
+            f(&FixedOutputBackend)
+        }
+    }
+
+    impl InferenceSession for FixedOutputBackend {
+        fn input(&self) -> Result<TensorSpec, ZKNeuralError> {
+            Ok(TensorSpec {
+                shape: Shape::new(vec![1, 2, 2, 3]),
+                data_type: DataType::Float32,
+            })
+        }
+
+        fn invoke(&self, input: &[u8]) -> Result<Vec<Tensor>, ZKNeuralError> {
+            assert_eq!(input.len(), 12 * size_of::<f32>());
+
+            Ok(vec![Tensor {
+                spec: TensorSpec {
+                    shape: Shape::new(vec![1, 2]),
+                    data_type: DataType::Float32,
+                },
+                data: [0.5f32, -0.25]
+                    .iter()
+                    .flat_map(|value| value.to_le_bytes())
+                    .collect(),
+            }])
+        }
+    }
+
+    #[test]
+    fn test_custom_backend() {
+        let invoker =
+            TensorInvoker::with_backend(b"model", false, Arc::new(FixedOutputBackend)).unwrap();
+
+        let image_data = std::fs::read("assets/face_cropped_resized.jpg").unwrap();
+
+        let inputs = invoker
+            .drain_generic_inputs(
+                GenericInputsParams {
+                    address: "1".to_string(),
+                    threshold: "2".to_string(),
+                    nonce: "3".to_string(),
+                    rand: Some("42".to_string()),
+                    proving_type: ZKNeuralProvingType::Groth,
+                },
+                &image_data,
+                ImagePreprocessing::None,
+            )
+            .unwrap();
+
+        let inputs: BionettaGenericInputs = serde_json::from_slice(&inputs).unwrap();
+
+        assert_eq!(
+            inputs.features,
+            SignalEncoding::default()
+                .encode_features(&[0.5, -0.25])
+                .unwrap()
+        );
+        assert_eq!(inputs.image.len(), 12);
+        assert_eq!(inputs.rand, "42");
+    }
+
+    #[test]
+    fn test_generic_schema_inputs() {
+        let mut invoker =
+            TensorInvoker::with_backend(b"model", false, Arc::new(FixedOutputBackend)).unwrap();
+
+        let image_data = std::fs::read("assets/face_cropped_resized.jpg").unwrap();
+
+        let drain = |invoker: &TensorInvoker, values: Value| {
+            let Value::Object(values) = values else {
+                panic!("Expected a JSON object");
             };
 
-            if let Some(float_value) = number.as_f64() {
-                let signal = (float_value / 255.0) * NEURAL_SIGNAL_MULTIPLIER;
+            invoker.drain_inputs(
+                BIONETTA_GENERIC_SCHEMA,
+                SchemaInputsParams {
+                    values,
+                    rand: Some("42".to_string()),
+                    proving_type: Some(ZKNeuralProvingType::Groth),
+                },
+                &image_data,
+                ImagePreprocessing::None,
+            )
+        };
 
-                return signal.to_string();
-            }
+        let address = format!("0x{}", "0".repeat(39) + "a");
 
-            number.to_string()
-        })
-        .collect()
-}
+        let inputs = drain(
+            &invoker,
+            json!({"address": address, "threshold": "2", "nonce": 3}),
+        )
+        .unwrap();
+        let inputs: BionettaGenericInputs = serde_json::from_slice(&inputs).unwrap();
 
-#[cfg(test)]
-mod tests {
-    use std::{fs::File, io::Read};
-    use tflitec::{interpreter::Interpreter, model::Model};
+        let generic_inputs = invoker
+            .drain_generic_inputs(
+                GenericInputsParams {
+                    address,
+                    threshold: "2".to_string(),
+                    nonce: "3".to_string(),
+                    rand: Some("42".to_string()),
+                    proving_type: ZKNeuralProvingType::Groth,
+                },
+                &image_data,
+                ImagePreprocessing::None,
+            )
+            .unwrap();
+
+        assert_eq!(inputs.address, "10");
+        assert_eq!(
+            serde_json::to_vec(&inputs).unwrap(),
+            generic_inputs.as_slice()
+        );
+
+        let threshold = format!("{}", 1u128 << 100);
+        assert!(matches!(
+            drain(
+                &invoker,
+                json!({"address": "1", "threshold": threshold, "nonce": "3"})
+            ),
+            Err(ZKNeuralError::InvalidThreshold(_))
+        ));
+
+        assert!(matches!(
+            invoker.register_input_schema(BIONETTA_GENERIC_SCHEMA, InputSchema { signals: vec![] }),
+            Err(ZKNeuralError::InvalidInputSchema(_))
+        ));
+    }
+
+    #[test]
+    fn test_debug_dump() {
+        let mut invoker =
+            TensorInvoker::with_backend(b"model", false, Arc::new(FixedOutputBackend)).unwrap();
+
+        let debug_dump = Arc::new(DebugDump::new(DebugDumpOptions::default()).unwrap());
+        invoker.debug_dump = Some(debug_dump.clone());
+
+        let image_data = std::fs::read("assets/face_cropped_resized.jpg").unwrap();
+
+        invoker
+            .embed(&image_data, ImagePreprocessing::None)
+            .unwrap();
+
+        let bundle = debug_dump.take_bundle();
+
+        let names: Vec<&str> = bundle
+            .artifacts
+            .iter()
+            .map(|artifact| artifact.name.as_str())
+            .collect();
+
+        assert_eq!(
+            names,
+            [
+                "decoded_image.png",
+                "model_input.json",
+                "model_input.bin",
+                "model_output.json",
+                "model_output.bin"
+            ]
+        );
+
+        let model_output_spec: serde_json::Value =
+            serde_json::from_slice(&bundle.artifacts[3].data).unwrap();
+
+        assert_eq!(
+            model_output_spec,
+            serde_json::json!({ "shape": [1, 2], "data_type": "float32" })
+        );
+        assert_eq!(bundle.artifacts[2].data.len(), 12 * size_of::<f32>());
+    }
+
+    #[test]
+    fn test_allowed_model_hashes() {
+        let mut invoker =
+            TensorInvoker::with_backend(b"model", false, Arc::new(FixedOutputBackend)).unwrap();
+
+        let image_data = std::fs::read("assets/face_cropped_resized.jpg").unwrap();
+
+        assert_eq!(invoker.model_hash(), model_hash(b"model"));
 
-    use crate::core::tensor::{ImagePreprocessing, TensorInvoker};
+        let result = invoker.set_allowed_model_hashes(Some(vec![model_hash(b"other")]));
+        assert!(matches!(result, Err(ZKNeuralError::ModelHashNotAllowed(_))));
+
+        let result = invoker.embed(&image_data, ImagePreprocessing::None);
+        assert!(matches!(result, Err(ZKNeuralError::ModelHashNotAllowed(_))));
+
+        let result = invoker.set_allowed_model_hashes(Some(vec!["0xmodel".to_string()]));
+        assert!(matches!(result, Err(ZKNeuralError::InvalidModelHash(_))));
+
+        invoker
+            .set_allowed_model_hashes(Some(vec![
+                model_hash(b"other"),
+                format!("0x{}", model_hash(b"model").to_uppercase()),
+            ]))
+            .unwrap();
+
+        invoker
+            .embed(&image_data, ImagePreprocessing::None)
+            .unwrap();
+
+        invoker.set_allowed_model_hashes(None).unwrap();
+        assert!(invoker.allowed_model_hashes().is_none());
+    }
 
+    #[cfg(feature = "tflite")]
     #[test]
     fn compute() {
         let model_data = File::open("assets/mnist.tflite")
@@ -375,9 +1054,13 @@ mod tests {
 
         let result = invoker
             .drain_generic_inputs(
-                "3123123".to_string(),
-                "1".to_string(),
-                "1".to_string(),
+                GenericInputsParams {
+                    address: "3123123".to_string(),
+                    threshold: "1".to_string(),
+                    nonce: "1".to_string(),
+                    rand: None,
+                    proving_type: ZKNeuralProvingType::UltraGroth,
+                },
                 &image_data,
                 ImagePreprocessing::None,
             )
@@ -385,4 +1068,55 @@ mod tests {
 
         println!("Result: {:?}", String::from_utf8(result).unwrap());
     }
+
+    #[test]
+    fn test_batch_fire() {
+        let mut file = File::open("assets/arcface.tflite").unwrap();
+        let mut model_data = Vec::new();
+        file.read_to_end(&mut model_data).unwrap();
+
+        let invoker = TensorInvoker::new(&model_data, true).unwrap();
+
+        let image_data = File::open("assets/face.jpeg")
+            .unwrap()
+            .bytes()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        let images = [
+            ImageSource::Encoded(&image_data),
+            ImageSource::Encoded(b"not an image"),
+            ImageSource::Encoded(&image_data),
+        ];
+
+        let results = invoker
+            .fire_batch(&images, ImagePreprocessing::None)
+            .unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert!(results[0].output.is_some());
+        assert!(results[1].error.is_some());
+        assert_eq!(results[0].output, results[2].output);
+    }
+
+    #[test]
+    fn test_match_faces() {
+        let mut file = File::open("assets/arcface.tflite").unwrap();
+        let mut model_data = Vec::new();
+        file.read_to_end(&mut model_data).unwrap();
+
+        let invoker = TensorInvoker::new(&model_data, true).unwrap();
+
+        let image_data = File::open("assets/face.jpeg")
+            .unwrap()
+            .bytes()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        let face_match = invoker.match_faces(&image_data, &image_data, "1").unwrap();
+
+        assert_eq!(face_match.distance, "0");
+        assert!(face_match.matched);
+        assert!((face_match.cosine_similarity - 1.0).abs() < 1e-6);
+    }
 }
@@ -0,0 +1,112 @@
+use tflitec::{interpreter::Interpreter, model::Model, tensor};
+
+use crate::{
+    ZKNeuralError,
+    core::inference::{DataType, InferenceBackend, InferenceSession, Shape, Tensor, TensorSpec},
+};
+
+/// Native TensorFlow Lite runtime.
+pub struct TfliteBackend;
+
+impl InferenceBackend for TfliteBackend {
+    fn with_session(
+        &self,
+        model: &[u8],
+        f: &mut dyn FnMut(&dyn InferenceSession) -> Result<(), ZKNeuralError>,
+    ) -> Result<(), ZKNeuralError> {
+        let model = Model::from_bytes(model)?;
+
+        let interpreter = Interpreter::new(&model, None)?;
+
+        interpreter.allocate_tensors()?;
+
+        f(&TfliteSession { interpreter })
+    }
+
+    fn load(
+        &self,
+        model: &'static [u8],
+    ) -> Result<Option<Box<dyn InferenceSession + Send>>, ZKNeuralError> {
+        let model = Box::new(Model::from_bytes(model)?);
+
+        // The interpreter borrows the boxed model, which does not move and outlives it, see
+        // `OwnedTfliteSession`.
+        let model_ref: &'static Model<'static> = unsafe { &*(model.as_ref() as *const _) };
+
+        let interpreter = Interpreter::new(model_ref, None)?;
+
+        interpreter.allocate_tensors()?;
+
+        Ok(Some(Box::new(OwnedTfliteSession {
+            session: TfliteSession { interpreter },
+            _model: model,
+        })))
+    }
+}
+
+/// A session owning its model, kept by long-lived handles such as `FaceDetector`.
+struct OwnedTfliteSession {
+    // Declared first, so the interpreter is dropped before the model it borrows.
+    session: TfliteSession<'static>,
+    _model: Box<Model<'static>>,
+}
+
+// The model and the interpreter are only moved together and are not tied to the thread that
+// created them, holders serialize the calls with a `Mutex`.
+unsafe impl Send for OwnedTfliteSession {}
+
+impl InferenceSession for OwnedTfliteSession {
+    fn input(&self) -> Result<TensorSpec, ZKNeuralError> {
+        self.session.input()
+    }
+
+    fn invoke(&self, input: &[u8]) -> Result<Vec<Tensor>, ZKNeuralError> {
+        self.session.invoke(input)
+    }
+}
+
+struct TfliteSession<'a> {
+    interpreter: Interpreter<'a>,
+}
+
+impl InferenceSession for TfliteSession<'_> {
+    fn input(&self) -> Result<TensorSpec, ZKNeuralError> {
+        let input = self.interpreter.input(0)?;
+
+        Ok(tensor_spec(&input))
+    }
+
+    fn invoke(&self, input: &[u8]) -> Result<Vec<Tensor>, ZKNeuralError> {
+        self.interpreter.copy(input, 0)?;
+
+        self.interpreter.invoke()?;
+
+        (0..self.interpreter.output_tensor_count())
+            .map(|index| {
+                let output = self.interpreter.output(index)?;
+
+                Ok(Tensor {
+                    spec: tensor_spec(&output),
+                    data: output.data::<u8>().to_vec(),
+                })
+            })
+            .collect()
+    }
+}
+
+fn tensor_spec(tensor: &tensor::Tensor) -> TensorSpec {
+    TensorSpec {
+        shape: Shape::new(tensor.shape().dimensions().clone()),
+        data_type: match tensor.data_type() {
+            tensor::DataType::Bool => DataType::Bool,
+            tensor::DataType::Uint8 => DataType::Uint8,
+            tensor::DataType::Int8 => DataType::Int8,
+            tensor::DataType::Int16 => DataType::Int16,
+            tensor::DataType::Int32 => DataType::Int32,
+            tensor::DataType::Int64 => DataType::Int64,
+            tensor::DataType::Float16 => DataType::Float16,
+            tensor::DataType::Float32 => DataType::Float32,
+            tensor::DataType::Float64 => DataType::Float64,
+        },
+    }
+}
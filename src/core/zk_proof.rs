@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::{ZKNeuralError, core::ZKNeuralProvingType};
+
 pub type ZkProofPubSignals = Vec<String>;
 
 #[derive(Serialize, Deserialize)]
@@ -29,3 +31,69 @@ pub struct UltraGrothProof {
     pub proof: UltraGrothProofPoints,
     pub pub_signals: ZkProofPubSignals,
 }
+
+/// Combines the proof points and public signals returned by a prover into the proof JSON of
+/// the proving type.
+pub fn assemble_proof(
+    proving_type: ZKNeuralProvingType,
+    proof_buffer: &[u8],
+    public_buffer: &[u8],
+) -> Result<Vec<u8>, ZKNeuralError> {
+    let pub_signals = serde_json::from_slice::<ZkProofPubSignals>(public_buffer)?;
+
+    let proof = match proving_type {
+        ZKNeuralProvingType::Groth => {
+            let proof = serde_json::from_slice::<GrothZkProofPoints>(proof_buffer)?;
+
+            serde_json::to_vec(&GrothZkProof { proof, pub_signals })
+        }
+        ZKNeuralProvingType::UltraGroth => {
+            let proof = serde_json::from_slice::<UltraGrothProofPoints>(proof_buffer)?;
+
+            serde_json::to_vec(&UltraGrothProof { proof, pub_signals })
+        }
+    }?;
+
+    Ok(proof)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{Value, json};
+
+    use crate::{
+        ZKNeuralError,
+        core::{ZKNeuralProvingType, zk_proof::assemble_proof},
+    };
+
+    #[test]
+    fn test_assemble_proof() {
+        let proof = json!({
+            "pi_a": ["1", "2"],
+            "pi_b": [["3", "4"]],
+            "pi_c": ["5"],
+            "proof_protocol": "groth16"
+        });
+
+        let assembled = assemble_proof(
+            ZKNeuralProvingType::Groth,
+            proof.to_string().as_bytes(),
+            br#"["6"]"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            serde_json::from_slice::<Value>(&assembled).unwrap(),
+            json!({"proof": proof, "pub_signals": ["6"]})
+        );
+
+        assert!(matches!(
+            assemble_proof(
+                ZKNeuralProvingType::UltraGroth,
+                proof.to_string().as_bytes(),
+                br#"["6"]"#
+            ),
+            Err(ZKNeuralError::JsonError(_))
+        ));
+    }
+}
@@ -1,17 +1,98 @@
 use crate::core::ZKNeuralProvingType;
-use crate::core::tensor::ImagePreprocessing;
+use crate::core::debug_dump::{DebugDump, DebugDumpOptions};
+use crate::core::face_detection::{FaceDetectionOptions, FaceDetector};
+use crate::core::face_quality::FaceQualityThresholds;
+use crate::core::image_source::{ImageSource, RawFrameDescriptor};
+use crate::core::input_schema::InputSchema;
+use crate::core::liveness::LivenessChecker;
+use crate::core::logging::{self, ZKNeuralLogLevel};
+use crate::core::model_bundle::ModelBundle;
+use crate::core::model_integrity::ARCFACE_MODEL_SHA256;
+use crate::core::preprocessing::PreprocessingSpec;
+use crate::core::signal_encoding::SignalEncoding;
+use crate::core::tensor::{
+    BionettaGenericInputs, GenericInputsParams, ImagePreprocessing, SchemaInputsParams,
+};
 
 use super::core::ZKNeuralCore;
 use super::core::tensor::TensorInvoker;
 
 use super::core::{
-    callbacks::{GenerateProofCallback, GenerateWitnessCallback},
+    callbacks::{GenerateProofCallback, GenerateWitnessCallback, LogCallback},
     errors::ZKNeuralError,
 };
 
+use serde_json::Map;
 use std::alloc::{self, Layout};
-use std::ffi::{CString, c_char};
+use std::ffi::{CStr, CString, c_char};
 use std::mem;
+use std::sync::Arc;
+
+/// Version of the C ABI described by `headers/zk_neural_rust_core.h`.
+///
+/// Bumped whenever an exported function signature or a `#[repr(C)]` type changes, so hosts
+/// built against another header can detect the mismatch at runtime.
+pub const ZKNEURAL_ABI_VERSION: u32 = 1;
+
+const ZKNEURAL_VERSION: &str = concat!(env!("CARGO_PKG_VERSION"), "\0");
+
+/// Returns the version of the library.
+///
+/// # Returns
+///
+/// Returns a pointer to a static null-terminated string, it must not be freed.
+#[unsafe(no_mangle)]
+pub extern "C" fn rs_zkneural_version() -> *const c_char {
+    ZKNEURAL_VERSION.as_ptr() as *const c_char
+}
+
+/// Returns the C ABI version the library was built with.
+///
+/// # Returns
+///
+/// Returns the `ZKNEURAL_ABI_VERSION` of the library.
+#[unsafe(no_mangle)]
+pub extern "C" fn rs_zkneural_abi_version() -> u32 {
+    ZKNEURAL_ABI_VERSION
+}
+
+/// Checks that the library implements the C ABI a host was built against.
+///
+/// Hosts call it with the `ZKNEURAL_ABI_VERSION` of their header, or use the
+/// `RS_ZKNEURAL_CHECK_ABI()` macro which aborts on a mismatch.
+///
+/// # Arguments
+/// * `header_abi_version` - The `ZKNEURAL_ABI_VERSION` of the header the host was built with.
+///
+/// # Returns
+///
+/// Returns `true` if the versions match, `false` otherwise.
+#[unsafe(no_mangle)]
+pub extern "C" fn rs_zkneural_check_abi_version(header_abi_version: u32) -> bool {
+    header_abi_version == ZKNEURAL_ABI_VERSION
+}
+
+/// Registers the callback receiving the library logs, e.g. to route them to os_log or logcat.
+///
+/// Messages cover stage timings, buffer sizes, model shapes and detection scores, never image
+/// content or embeddings. The callback may be called from any thread the library is used on.
+///
+/// # Arguments
+/// * `callback` - The log callback, null stops the logging without installing the library
+///   `tracing` subscriber.
+/// * `max_level` - The most verbose level forwarded to the callback.
+///
+/// # Returns
+///
+/// Returns `false` if the host installed its own `tracing` subscriber before a callback was
+/// set, `true` otherwise.
+#[unsafe(no_mangle)]
+pub extern "C" fn rs_zkneural_set_log_callback(
+    callback: Option<LogCallback>,
+    max_level: ZKNeuralLogLevel,
+) -> bool {
+    logging::set_log_callback(callback, max_level)
+}
 
 #[repr(C)]
 pub struct ZkNeuralCoreResult {
@@ -41,7 +122,10 @@ impl ZkNeuralCoreResult {
             }
 
             Err(e) => {
-                let error_msg = CString::new(e.to_string()).unwrap();
+                tracing::warn!(error = %e, "call failed");
+
+                // Messages can carry caller input, e.g. an unknown signal name.
+                let error_msg = CString::new(e.to_string().replace('\0', "")).unwrap_or_default();
                 Box::into_raw(Box::new(ZkNeuralCoreResult {
                     value: std::ptr::null_mut(),
                     value_size: 0,
@@ -223,6 +307,77 @@ pub extern "C" fn rs_zkneural_generate_proof(
     ZkNeuralCoreResult::from_rust_result(result)
 }
 
+/// Loads the model bundle of a directory into the ZKNeural core.
+///
+/// Reads `manifest.json`, the model and the circuit, checks their checksums and the model
+/// input against the bundle preprocessing spec, and sets the proving type of the bundle. The
+/// zkey is read and checked when a proof is generated. The result value is the JSON-encoded
+/// manifest, e.g. to read the bundle threshold.
+///
+/// # Arguments
+///
+/// * `core` - A pointer to the `ZKNeuralCore` instance.
+/// * `directory` - A null-terminated path to the bundle directory.
+///
+/// # Returns
+///
+/// Returns a pointer to a `ZkNeuralCoreResult` containing the manifest.
+#[unsafe(no_mangle)]
+pub extern "C" fn rs_zkneural_load_bundle(
+    core: *mut ZKNeuralCore,
+    directory: *const c_char,
+) -> *mut ZkNeuralCoreResult {
+    if core.is_null() {
+        return std::ptr::null_mut();
+    }
+
+    let core = unsafe { &mut *core };
+
+    let result = parse_c_string(directory, "directory")
+        .and_then(|directory| ModelBundle::load(directory.as_ref()))
+        .and_then(|bundle| {
+            let manifest = serde_json::to_vec(&bundle.manifest)?;
+
+            core.load_bundle(bundle)?;
+
+            Ok(manifest)
+        });
+
+    ZkNeuralCoreResult::from_rust_result(result)
+}
+
+/// Generates the witness and the proof of the JSON inputs with the circuit and the zkey of the
+/// loaded bundle.
+///
+/// Fails if the proving type was changed after loading the bundle, the `threshold` input
+/// differs from the bundle threshold or the `ultra_groth` input from its proving type.
+///
+/// # Arguments
+///
+/// * `core` - A pointer to the `ZKNeuralCore` instance.
+/// * `json_buffer` - A pointer to the buffer containing the JSON data.
+/// * `json_len` - The length of the JSON buffer in bytes.
+///
+/// # Returns
+///
+/// Returns a pointer to a `ZkNeuralCoreResult` containing the JSON-encoded proof.
+#[unsafe(no_mangle)]
+pub extern "C" fn rs_zkneural_prove_with_bundle(
+    core: *mut ZKNeuralCore,
+    json_buffer: *const u8,
+    json_len: usize,
+) -> *mut ZkNeuralCoreResult {
+    if core.is_null() {
+        return std::ptr::null_mut();
+    }
+
+    let json_slice = unsafe { std::slice::from_raw_parts(json_buffer, json_len) };
+
+    let core = unsafe { &*core };
+
+    ZkNeuralCoreResult::from_rust_result(core.prove_with_bundle(json_slice))
+}
+
 /// Creates a new `TensorInvoker` instance from the provided model buffer slice.
 ///
 /// # Panics
@@ -249,6 +404,36 @@ pub extern "C" fn rs_zkneural_tensor_invoker_new(
     Box::into_raw(Box::new(invoker))
 }
 
+/// Creates a `TensorInvoker` of the model of the loaded bundle.
+///
+/// The invoker uses the bundle preprocessing spec and signal encoding, and only accepts the
+/// model hash of the bundle manifest.
+///
+/// # Arguments
+///
+/// * `core` - A pointer to the `ZKNeuralCore` instance with a loaded bundle.
+/// * `should_process` - Whether the model output is processed.
+///
+/// # Returns
+///
+/// Returns a pointer to the `TensorInvoker`, or null if no bundle is loaded or the model could not be loaded.
+#[unsafe(no_mangle)]
+pub extern "C" fn rs_zkneural_tensor_invoker_new_from_bundle(
+    core: *mut ZKNeuralCore,
+    should_process: bool,
+) -> *mut TensorInvoker {
+    if core.is_null() {
+        return std::ptr::null_mut();
+    }
+
+    let core = unsafe { &*core };
+
+    match core.bundle_tensor_invoker(should_process) {
+        Ok(invoker) => Box::into_raw(Box::new(invoker)),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
 #[unsafe(no_mangle)]
 pub extern "C" fn rs_zkneural_tensor_invoker_free(invoker: *mut TensorInvoker) {
     if invoker.is_null() {
@@ -259,120 +444,1494 @@ pub extern "C" fn rs_zkneural_tensor_invoker_free(invoker: *mut TensorInvoker) {
     }
 }
 
-/// Invokes the TensorInvoker with the provided image buffer.
+/// Sets whether the TensorInvoker applies the EXIF orientation of encoded images.
 ///
-/// This function prepares the image data according to the specifications of the TensorInvoker
+/// The orientation is applied by default, so that photos straight from phone cameras
+/// are upright before face detection and inference.
 ///
 /// # Arguments
 /// * `invoker` - A pointer to the `TensorInvoker` instance.
-/// * `image_buffer` - A pointer to the image data buffer.
-/// * `image_len` - The length of the image buffer in bytes.
+/// * `apply_exif_orientation` - Whether to apply the EXIF orientation.
+#[unsafe(no_mangle)]
+pub extern "C" fn rs_zkneural_tensor_invoker_set_apply_exif_orientation(
+    invoker: *mut TensorInvoker,
+    apply_exif_orientation: bool,
+) {
+    if invoker.is_null() {
+        return;
+    }
+    unsafe {
+        let invoker = &mut *invoker;
+        invoker.apply_exif_orientation = apply_exif_orientation;
+    }
+}
+
+/// Sets the preprocessing spec used to turn images into the model input tensor.
+///
+/// # Arguments
+/// * `invoker` - A pointer to the `TensorInvoker` instance.
+/// * `spec_buffer` - A pointer to a JSON-encoded `PreprocessingSpec`, may be null.
+/// * `spec_len` - The length of the spec buffer in bytes, `0` restores the default spec.
 ///
 /// # Returns
 ///
-/// Returns a pointer to a `ZkNeuralCoreResult` containing the result of the invocation.
+/// Returns `false` if the spec could not be parsed or is invalid, `true` otherwise.
 #[unsafe(no_mangle)]
-pub extern "C" fn rs_zkneural_tensor_invoker_image_fire(
+pub extern "C" fn rs_zkneural_tensor_invoker_set_preprocessing_spec(
     invoker: *mut TensorInvoker,
-    image_buffer: *const u8,
-    image_len: usize,
-    image_preprocessing: ImagePreprocessing,
-) -> *mut ZkNeuralCoreResult {
+    spec_buffer: *const u8,
+    spec_len: usize,
+) -> bool {
     if invoker.is_null() {
-        return std::ptr::null_mut();
+        return false;
     }
 
-    let image_data = unsafe { std::slice::from_raw_parts(image_buffer, image_len) };
+    let invoker = unsafe { &mut *invoker };
+
+    if spec_buffer.is_null() || spec_len == 0 {
+        invoker.preprocessing_spec = PreprocessingSpec::default();
+        return true;
+    }
+
+    let spec_slice = unsafe { std::slice::from_raw_parts(spec_buffer, spec_len) };
+
+    match PreprocessingSpec::from_json(spec_slice) {
+        Ok(spec) => {
+            invoker.preprocessing_spec = spec;
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+/// Sets the fixed-point encoding used to turn image pixels and features into circuit signals.
+///
+/// # Arguments
+/// * `invoker` - A pointer to the `TensorInvoker` instance.
+/// * `encoding_buffer` - A pointer to a JSON-encoded `SignalEncoding`, may be null.
+/// * `encoding_len` - The length of the encoding buffer in bytes, `0` restores the default encoding.
+///
+/// # Returns
+///
+/// Returns `false` if the encoding could not be parsed or is invalid, `true` otherwise.
+#[unsafe(no_mangle)]
+pub extern "C" fn rs_zkneural_tensor_invoker_set_signal_encoding(
+    invoker: *mut TensorInvoker,
+    encoding_buffer: *const u8,
+    encoding_len: usize,
+) -> bool {
+    if invoker.is_null() {
+        return false;
+    }
 
     let invoker = unsafe { &mut *invoker };
 
-    let (prepared_image_data, _) =
-        match invoker.prepare_image_by_spec(image_data, image_preprocessing) {
-            Ok(data) => data,
-            Err(e) => {
-                return ZkNeuralCoreResult::from_rust_result(Err(e));
-            }
-        };
+    if encoding_buffer.is_null() || encoding_len == 0 {
+        invoker.signal_encoding = SignalEncoding::default();
+        return true;
+    }
 
-    let result = invoker.fire(&prepared_image_data);
+    let encoding_slice = unsafe { std::slice::from_raw_parts(encoding_buffer, encoding_len) };
 
-    ZkNeuralCoreResult::from_rust_result(result)
+    match SignalEncoding::from_json(encoding_slice) {
+        Ok(encoding) => {
+            invoker.signal_encoding = encoding;
+            true
+        }
+        Err(_) => false,
+    }
 }
-/// Drains generic inputs from the TensorInvoker.
+
+/// Decodes circuit signals back to the values they encode with the invoker's signal encoding.
+///
+/// Intended for debugging generated inputs, the result value is a JSON array of numbers.
+/// Features come back multiplied by `feature_scale`, decode them with `decode_features`.
 ///
 /// # Arguments
 /// * `invoker` - A pointer to the `TensorInvoker` instance.
-/// * `image_buffer` - A pointer to the image data buffer.
-/// * `image_len` - The length of the image buffer in bytes.
-/// * `image_preprocessing` - The preprocessing type for the image.
-/// * `address` - A pointer to a C-style string containing the address.
-/// * `threshold` - A pointer to a C-style string containing the threshold.
-/// * `nonce` - A pointer to a C-style string containing the nonce.
+/// * `signals_buffer` - A pointer to a JSON array of decimal field element strings.
+/// * `signals_len` - The length of the signals buffer in bytes.
 ///
 /// # Returns
 ///
-/// Returns a pointer to a `ZkNeuralCoreResult` containing the result of the operation.
+/// Returns a pointer to a `ZkNeuralCoreResult` containing the decoded values.
 #[unsafe(no_mangle)]
-pub extern "C" fn rs_zkneural_tensor_invoker_drain_generic_inputs(
+pub extern "C" fn rs_zkneural_tensor_invoker_decode_signals(
     invoker: *mut TensorInvoker,
-    image_buffer: *const u8,
-    image_len: usize,
-    image_preprocessing: ImagePreprocessing,
-    address: *mut c_char,
-    threshold: *mut c_char,
-    nonce: *mut c_char,
+    signals_buffer: *const u8,
+    signals_len: usize,
 ) -> *mut ZkNeuralCoreResult {
     if invoker.is_null() {
         return std::ptr::null_mut();
     }
 
-    let image_data = unsafe { std::slice::from_raw_parts(image_buffer, image_len) };
+    let signals_data = unsafe { std::slice::from_raw_parts(signals_buffer, signals_len) };
 
-    let invoker = unsafe { &mut *invoker };
+    let invoker = unsafe { &*invoker };
 
-    let address = unsafe { CString::from_raw(address) }
-        .into_string()
-        .unwrap_or_default();
+    let result = serde_json::from_slice::<Vec<String>>(signals_data)
+        .map_err(ZKNeuralError::from)
+        .and_then(|signals| invoker.signal_encoding.decode_all(&signals))
+        .and_then(|values| Ok(serde_json::to_vec(&values)?));
 
-    let threshold = unsafe { CString::from_raw(threshold) }
-        .into_string()
-        .unwrap_or_default();
+    ZkNeuralCoreResult::from_rust_result(result)
+}
 
-    let nonce = unsafe { CString::from_raw(nonce) }
-        .into_string()
-        .unwrap_or_default();
+/// Decodes the `features` signals of generated inputs back to the model output, undoing the
+/// `feature_scale` of the invoker's signal encoding that `decode_signals` keeps.
+///
+/// # Arguments
+/// * `invoker` - A pointer to the `TensorInvoker` instance.
+/// * `signals_buffer` - A pointer to a JSON array of decimal field element strings.
+/// * `signals_len` - The length of the signals buffer in bytes.
+///
+/// # Returns
+///
+/// Returns a pointer to a `ZkNeuralCoreResult` containing the decoded features.
+#[unsafe(no_mangle)]
+pub extern "C" fn rs_zkneural_tensor_invoker_decode_features(
+    invoker: *mut TensorInvoker,
+    signals_buffer: *const u8,
+    signals_len: usize,
+) -> *mut ZkNeuralCoreResult {
+    if invoker.is_null() {
+        return std::ptr::null_mut();
+    }
 
-    let result =
-        invoker.drain_generic_inputs(address, threshold, nonce, image_data, image_preprocessing);
+    let signals_data = unsafe { std::slice::from_raw_parts(signals_buffer, signals_len) };
+
+    let invoker = unsafe { &*invoker };
+
+    let result = serde_json::from_slice::<Vec<String>>(signals_data)
+        .map_err(ZKNeuralError::from)
+        .and_then(|signals| invoker.signal_encoding.decode_features(&signals))
+        .and_then(|values| Ok(serde_json::to_vec(&values)?));
 
     ZkNeuralCoreResult::from_rust_result(result)
 }
 
-/// Allocates a buffer of the specified length.
+/// Sets the face quality thresholds checked before inference with `FaceRecognition` preprocessing.
+///
+/// Faces that are too small, blurry, badly exposed or turned away are rejected with a
+/// specific error instead of producing an embedding.
 ///
 /// # Arguments
-/// * `len` - The length of the buffer to allocate in bytes.
+/// * `invoker` - A pointer to the `TensorInvoker` instance.
+/// * `thresholds_buffer` - A pointer to a JSON-encoded `FaceQualityThresholds`, may be null.
+/// * `thresholds_len` - The length of the thresholds buffer in bytes, `0` disables the quality gate.
 ///
 /// # Returns
 ///
-/// Returns a pointer to the allocated buffer. If allocation fails, it returns a null pointer.
+/// Returns `false` if the thresholds could not be parsed, `true` otherwise.
 #[unsafe(no_mangle)]
-pub extern "C" fn rs_zkneural_alloc(len: usize) -> *mut u8 {
-    unsafe {
-        let layout = Layout::from_size_align_unchecked(len, mem::align_of::<u8>());
-        alloc::alloc(layout)
+pub extern "C" fn rs_zkneural_tensor_invoker_set_face_quality_thresholds(
+    invoker: *mut TensorInvoker,
+    thresholds_buffer: *const u8,
+    thresholds_len: usize,
+) -> bool {
+    if invoker.is_null() {
+        return false;
+    }
+
+    let invoker = unsafe { &mut *invoker };
+
+    if thresholds_buffer.is_null() || thresholds_len == 0 {
+        invoker.face_quality_thresholds = None;
+        return true;
+    }
+
+    let thresholds_slice = unsafe { std::slice::from_raw_parts(thresholds_buffer, thresholds_len) };
+
+    match serde_json::from_slice::<FaceQualityThresholds>(thresholds_slice) {
+        Ok(thresholds) => {
+            invoker.face_quality_thresholds = Some(thresholds);
+            true
+        }
+        Err(_) => false,
     }
 }
 
-/// Deallocates a buffer previously allocated with `rs_zkneural_alloc`.
+/// Sets the liveness model checked by `rs_zkneural_tensor_invoker_drain_generic_inputs`.
+///
+/// The model is a user-supplied TFLite anti-spoofing model that runs on the preprocessed
+/// face crop. Inputs scoring below the threshold are rejected.
 ///
 /// # Arguments
-/// * `ptr` - A pointer to the buffer to deallocate.
-/// * `len` - The length of the buffer in bytes.
+/// * `invoker` - A pointer to the `TensorInvoker` instance.
+/// * `model_buffer` - A pointer to the serialized TFLite liveness model, may be null.
+/// * `model_len` - The length of the model buffer in bytes, `0` disables the liveness stage.
+/// * `threshold` - The minimum liveness score accepted.
+///
+/// # Returns
+///
+/// Returns `false` if the model could not be loaded, `true` otherwise.
 #[unsafe(no_mangle)]
-pub extern "C" fn rs_zkneural_dealloc(ptr: *mut u8, len: usize) {
-    unsafe {
-        let layout = Layout::from_size_align_unchecked(len, mem::align_of::<u8>());
-        alloc::dealloc(ptr, layout);
+pub extern "C" fn rs_zkneural_tensor_invoker_set_liveness_model(
+    invoker: *mut TensorInvoker,
+    model_buffer: *const u8,
+    model_len: usize,
+    threshold: f32,
+) -> bool {
+    if invoker.is_null() {
+        return false;
+    }
+
+    let invoker = unsafe { &mut *invoker };
+
+    if model_buffer.is_null() || model_len == 0 {
+        invoker.liveness_checker = None;
+        return true;
+    }
+
+    let model_slice = unsafe { std::slice::from_raw_parts(model_buffer, model_len) };
+
+    match LivenessChecker::new(model_slice, threshold) {
+        Ok(liveness_checker) => {
+            invoker.liveness_checker = Some(Box::new(liveness_checker));
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+/// Scores the provided image buffer with the liveness model of the TensorInvoker.
+///
+/// The result value is the JSON-encoded liveness score, higher is more likely a live face.
+///
+/// # Arguments
+/// * `invoker` - A pointer to the `TensorInvoker` instance.
+/// * `image_buffer` - A pointer to the image data buffer.
+/// * `image_len` - The length of the image buffer in bytes.
+/// * `image_preprocessing` - The preprocessing type for the image.
+///
+/// # Returns
+///
+/// Returns a pointer to a `ZkNeuralCoreResult` containing the liveness score.
+#[unsafe(no_mangle)]
+pub extern "C" fn rs_zkneural_tensor_invoker_liveness_score(
+    invoker: *mut TensorInvoker,
+    image_buffer: *const u8,
+    image_len: usize,
+    image_preprocessing: ImagePreprocessing,
+) -> *mut ZkNeuralCoreResult {
+    if invoker.is_null() {
+        return std::ptr::null_mut();
+    }
+
+    let image_data = unsafe { std::slice::from_raw_parts(image_buffer, image_len) };
+
+    let invoker = unsafe { &*invoker };
+
+    let result = invoker
+        .liveness_score(image_data, image_preprocessing)
+        .and_then(|score| Ok(serde_json::to_vec(&score)?));
+
+    ZkNeuralCoreResult::from_rust_result(result)
+}
+
+/// Enables or disables the debug artifact dump of the TensorInvoker.
+///
+/// Every following call records the decoded image, the detector input and scores, the face
+/// box and crop, and the model input and output tensors. The artifacts contain the user's
+/// face, only enable the dump with their consent.
+///
+/// # Arguments
+/// * `invoker` - A pointer to the `TensorInvoker` instance.
+/// * `options_buffer` - A pointer to a JSON-encoded `DebugDumpOptions`, may be null.
+/// * `options_len` - The length of the options buffer in bytes, `0` disables the dump.
+///
+/// # Returns
+///
+/// Returns `false` if the options could not be parsed or the directory could not be created, `true` otherwise.
+#[unsafe(no_mangle)]
+pub extern "C" fn rs_zkneural_tensor_invoker_set_debug_dump(
+    invoker: *mut TensorInvoker,
+    options_buffer: *const u8,
+    options_len: usize,
+) -> bool {
+    if invoker.is_null() {
+        return false;
+    }
+
+    let invoker = unsafe { &mut *invoker };
+
+    match parse_debug_dump(options_buffer, options_len) {
+        Ok(debug_dump) => {
+            invoker.debug_dump = debug_dump;
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+/// Takes the debug artifacts the TensorInvoker kept in memory.
+///
+/// The result value is a JSON-encoded `DebugBundle` with base64-encoded artifact data, the
+/// artifacts are cleared. It is empty when the dump is disabled or writes to a directory.
+///
+/// # Arguments
+/// * `invoker` - A pointer to the `TensorInvoker` instance.
+///
+/// # Returns
+///
+/// Returns a pointer to a `ZkNeuralCoreResult` containing the bundle.
+#[unsafe(no_mangle)]
+pub extern "C" fn rs_zkneural_tensor_invoker_take_debug_bundle(
+    invoker: *mut TensorInvoker,
+) -> *mut ZkNeuralCoreResult {
+    if invoker.is_null() {
+        return std::ptr::null_mut();
+    }
+
+    let invoker = unsafe { &*invoker };
+
+    take_debug_bundle(&invoker.debug_dump)
+}
+
+/// Returns the hash of the TensorInvoker model.
+///
+/// The result value is the JSON-encoded lowercase hex SHA-256 of the model bytes, to be
+/// checked against the model hash of the circuit configuration.
+///
+/// # Arguments
+/// * `invoker` - A pointer to the `TensorInvoker` instance.
+///
+/// # Returns
+///
+/// Returns a pointer to a `ZkNeuralCoreResult` containing the model hash.
+#[unsafe(no_mangle)]
+pub extern "C" fn rs_zkneural_tensor_invoker_model_hash(
+    invoker: *mut TensorInvoker,
+) -> *mut ZkNeuralCoreResult {
+    if invoker.is_null() {
+        return std::ptr::null_mut();
+    }
+
+    let invoker = unsafe { &*invoker };
+
+    ZkNeuralCoreResult::from_rust_result(
+        serde_json::to_vec(invoker.model_hash()).map_err(Into::into),
+    )
+}
+
+/// Returns the hash of the ArcFace model the Bionetta circuit is built for.
+///
+/// # Returns
+///
+/// Returns a pointer to a `ZkNeuralCoreResult` containing the JSON-encoded hex SHA-256 hash.
+#[unsafe(no_mangle)]
+pub extern "C" fn rs_zkneural_arcface_model_hash() -> *mut ZkNeuralCoreResult {
+    ZkNeuralCoreResult::from_rust_result(
+        serde_json::to_vec(ARCFACE_MODEL_SHA256).map_err(Into::into),
+    )
+}
+
+/// Sets the model hashes the TensorInvoker accepts.
+///
+/// While set, every invocation fails unless the model hash is on the list. The list is kept
+/// even when the loaded model is not on it.
+///
+/// # Arguments
+/// * `invoker` - A pointer to the `TensorInvoker` instance.
+/// * `hashes_buffer` - A pointer to a JSON array of hex SHA-256 hashes, may be null.
+/// * `hashes_len` - The length of the hashes buffer in bytes, `0` accepts any model.
+///
+/// # Returns
+///
+/// Returns `false` if the hashes could not be parsed or the loaded model is not allowed, `true` otherwise.
+#[unsafe(no_mangle)]
+pub extern "C" fn rs_zkneural_tensor_invoker_set_allowed_model_hashes(
+    invoker: *mut TensorInvoker,
+    hashes_buffer: *const u8,
+    hashes_len: usize,
+) -> bool {
+    if invoker.is_null() {
+        return false;
+    }
+
+    let invoker = unsafe { &mut *invoker };
+
+    if hashes_buffer.is_null() || hashes_len == 0 {
+        return invoker.set_allowed_model_hashes(None).is_ok();
+    }
+
+    let hashes_slice = unsafe { std::slice::from_raw_parts(hashes_buffer, hashes_len) };
+
+    serde_json::from_slice::<Vec<String>>(hashes_slice)
+        .map_err(Into::into)
+        .and_then(|hashes| invoker.set_allowed_model_hashes(Some(hashes)))
+        .is_ok()
+}
+
+/// Invokes the TensorInvoker with the provided image buffer.
+///
+/// This function prepares the image data according to the specifications of the TensorInvoker
+///
+/// # Arguments
+/// * `invoker` - A pointer to the `TensorInvoker` instance.
+/// * `image_buffer` - A pointer to the image data buffer.
+/// * `image_len` - The length of the image buffer in bytes.
+///
+/// # Returns
+///
+/// Returns a pointer to a `ZkNeuralCoreResult` containing the result of the invocation.
+#[unsafe(no_mangle)]
+pub extern "C" fn rs_zkneural_tensor_invoker_image_fire(
+    invoker: *mut TensorInvoker,
+    image_buffer: *const u8,
+    image_len: usize,
+    image_preprocessing: ImagePreprocessing,
+) -> *mut ZkNeuralCoreResult {
+    if invoker.is_null() {
+        return std::ptr::null_mut();
+    }
+
+    let image_data = unsafe { std::slice::from_raw_parts(image_buffer, image_len) };
+
+    let invoker = unsafe { &mut *invoker };
+
+    image_fire(
+        invoker,
+        ImageSource::Encoded(image_data),
+        image_preprocessing,
+    )
+}
+
+/// Invokes the TensorInvoker with the provided raw camera frame.
+///
+/// # Arguments
+/// * `invoker` - A pointer to the `TensorInvoker` instance.
+/// * `frame_buffer` - A pointer to the raw pixel buffer.
+/// * `frame_len` - The length of the frame buffer in bytes.
+/// * `frame_descriptor` - The size, stride, pixel format and rotation of the frame.
+/// * `image_preprocessing` - The preprocessing type for the image.
+///
+/// # Returns
+///
+/// Returns a pointer to a `ZkNeuralCoreResult` containing the result of the invocation.
+#[unsafe(no_mangle)]
+pub extern "C" fn rs_zkneural_tensor_invoker_frame_fire(
+    invoker: *mut TensorInvoker,
+    frame_buffer: *const u8,
+    frame_len: usize,
+    frame_descriptor: RawFrameDescriptor,
+    image_preprocessing: ImagePreprocessing,
+) -> *mut ZkNeuralCoreResult {
+    if invoker.is_null() {
+        return std::ptr::null_mut();
+    }
+
+    let frame_data = unsafe { std::slice::from_raw_parts(frame_buffer, frame_len) };
+
+    let invoker = unsafe { &mut *invoker };
+
+    let source = ImageSource::Raw {
+        data: frame_data,
+        descriptor: frame_descriptor,
+    };
+
+    image_fire(invoker, source, image_preprocessing)
+}
+
+fn image_fire(
+    invoker: &TensorInvoker,
+    source: ImageSource,
+    image_preprocessing: ImagePreprocessing,
+) -> *mut ZkNeuralCoreResult {
+    let (prepared_image_data, _) = match invoker.prepare_image_by_spec(source, image_preprocessing)
+    {
+        Ok(data) => data,
+        Err(e) => {
+            return ZkNeuralCoreResult::from_rust_result(Err(e));
+        }
+    };
+
+    let result = invoker.fire(&prepared_image_data);
+
+    ZkNeuralCoreResult::from_rust_result(result)
+}
+
+/// Invokes the TensorInvoker with several image buffers at once.
+///
+/// The model is loaded once for the whole batch. The result value is a JSON array with one
+/// `{"output": ..., "error": ...}` entry per image, in input order, so a single unusable image
+/// does not fail the whole batch.
+///
+/// # Arguments
+/// * `invoker` - A pointer to the `TensorInvoker` instance.
+/// * `image_buffers` - A pointer to an array of image data buffer pointers.
+/// * `image_lens` - A pointer to an array with the length of each image buffer in bytes.
+/// * `images_count` - The number of images in the batch, both arrays may be null when it is `0`.
+/// * `image_preprocessing` - The preprocessing type for the images.
+///
+/// # Returns
+///
+/// Returns a pointer to a `ZkNeuralCoreResult` containing the results of the batch.
+#[unsafe(no_mangle)]
+pub extern "C" fn rs_zkneural_tensor_invoker_batch_fire(
+    invoker: *mut TensorInvoker,
+    image_buffers: *const *const u8,
+    image_lens: *const usize,
+    images_count: usize,
+    image_preprocessing: ImagePreprocessing,
+) -> *mut ZkNeuralCoreResult {
+    if invoker.is_null() {
+        return std::ptr::null_mut();
+    }
+
+    let invoker = unsafe { &*invoker };
+
+    let result = batch_images(image_buffers, image_lens, images_count)
+        .and_then(|images| invoker.fire_batch(&images, image_preprocessing))
+        .and_then(|results| Ok(serde_json::to_vec(&results)?));
+
+    ZkNeuralCoreResult::from_rust_result(result)
+}
+
+fn batch_images<'a>(
+    image_buffers: *const *const u8,
+    image_lens: *const usize,
+    images_count: usize,
+) -> Result<Vec<ImageSource<'a>>, ZKNeuralError> {
+    let buffers = parse_slice(image_buffers, images_count, "image_buffers")?;
+    let lens = parse_slice(image_lens, images_count, "image_lens")?;
+
+    buffers
+        .iter()
+        .zip(lens.iter())
+        .map(|(&buffer, &len)| {
+            Ok(ImageSource::Encoded(parse_slice(
+                buffer,
+                len,
+                "image_buffers",
+            )?))
+        })
+        .collect()
+}
+
+/// Drains generic inputs from the TensorInvoker.
+///
+/// # Arguments
+/// * `invoker` - A pointer to the `TensorInvoker` instance.
+/// * `image_buffer` - A pointer to the image data buffer.
+/// * `image_len` - The length of the image buffer in bytes.
+/// * `image_preprocessing` - The preprocessing type for the image.
+/// * `address` - A pointer to a C-style string containing the address.
+/// * `threshold` - A pointer to a C-style string containing the threshold.
+/// * `nonce` - A pointer to a C-style string containing the nonce.
+///
+/// `ultra_groth` is drained for `ZKNeuralProvingType::Groth` and `rand` is drawn from the OS
+/// CSPRNG, use `rs_zkneural_tensor_invoker_drain_generic_inputs_with_core` to choose them.
+///
+/// # Returns
+///
+/// Returns a pointer to a `ZkNeuralCoreResult` containing the result of the operation.
+#[unsafe(no_mangle)]
+pub extern "C" fn rs_zkneural_tensor_invoker_drain_generic_inputs(
+    invoker: *mut TensorInvoker,
+    image_buffer: *const u8,
+    image_len: usize,
+    image_preprocessing: ImagePreprocessing,
+    address: *const c_char,
+    threshold: *const c_char,
+    nonce: *const c_char,
+) -> *mut ZkNeuralCoreResult {
+    if invoker.is_null() {
+        return std::ptr::null_mut();
+    }
+
+    let image_data = unsafe { std::slice::from_raw_parts(image_buffer, image_len) };
+
+    let invoker = unsafe { &mut *invoker };
+
+    drain_generic_inputs(
+        invoker,
+        ImageSource::Encoded(image_data),
+        image_preprocessing,
+        generic_inputs_params(
+            address,
+            threshold,
+            nonce,
+            Ok(ZKNeuralProvingType::Groth),
+            std::ptr::null(),
+        ),
+    )
+}
+
+/// Drains generic inputs from the TensorInvoker for the proving type of a `ZKNeuralCore`.
+///
+/// # Arguments
+/// * `invoker` - A pointer to the `TensorInvoker` instance.
+/// * `image_buffer` - A pointer to the image data buffer.
+/// * `image_len` - The length of the image buffer in bytes.
+/// * `image_preprocessing` - The preprocessing type for the image.
+/// * `address` - A pointer to a C-style string containing the address.
+/// * `threshold` - A pointer to a C-style string containing the threshold.
+/// * `nonce` - A pointer to a C-style string containing the nonce.
+/// * `core` - A pointer to the `ZKNeuralCore` instance whose proving type selects `ultra_groth`.
+/// * `rand` - A pointer to a C-style string containing the blinding value, may be null to draw
+///   a fresh one from the OS CSPRNG. The value used is returned in the drained inputs.
+///
+/// # Returns
+///
+/// Returns a pointer to a `ZkNeuralCoreResult` containing the result of the operation, an error
+/// result if `core` is null.
+#[unsafe(no_mangle)]
+pub extern "C" fn rs_zkneural_tensor_invoker_drain_generic_inputs_with_core(
+    invoker: *mut TensorInvoker,
+    image_buffer: *const u8,
+    image_len: usize,
+    image_preprocessing: ImagePreprocessing,
+    address: *const c_char,
+    threshold: *const c_char,
+    nonce: *const c_char,
+    core: *mut ZKNeuralCore,
+    rand: *const c_char,
+) -> *mut ZkNeuralCoreResult {
+    if invoker.is_null() {
+        return std::ptr::null_mut();
+    }
+
+    let image_data = unsafe { std::slice::from_raw_parts(image_buffer, image_len) };
+
+    let invoker = unsafe { &mut *invoker };
+
+    drain_generic_inputs(
+        invoker,
+        ImageSource::Encoded(image_data),
+        image_preprocessing,
+        generic_inputs_params(address, threshold, nonce, core_proving_type(core), rand),
+    )
+}
+
+/// Drains generic inputs from the TensorInvoker using a raw camera frame.
+///
+/// # Arguments
+/// * `invoker` - A pointer to the `TensorInvoker` instance.
+/// * `frame_buffer` - A pointer to the raw pixel buffer.
+/// * `frame_len` - The length of the frame buffer in bytes.
+/// * `frame_descriptor` - The size, stride, pixel format and rotation of the frame.
+/// * `image_preprocessing` - The preprocessing type for the image.
+/// * `address` - A pointer to a C-style string containing the address.
+/// * `threshold` - A pointer to a C-style string containing the threshold.
+/// * `nonce` - A pointer to a C-style string containing the nonce.
+///
+/// `ultra_groth` is drained for `ZKNeuralProvingType::Groth` and `rand` is drawn from the OS
+/// CSPRNG, use `rs_zkneural_tensor_invoker_drain_generic_inputs_from_frame_with_core` to
+/// choose them.
+///
+/// # Returns
+///
+/// Returns a pointer to a `ZkNeuralCoreResult` containing the result of the operation.
+#[unsafe(no_mangle)]
+pub extern "C" fn rs_zkneural_tensor_invoker_drain_generic_inputs_from_frame(
+    invoker: *mut TensorInvoker,
+    frame_buffer: *const u8,
+    frame_len: usize,
+    frame_descriptor: RawFrameDescriptor,
+    image_preprocessing: ImagePreprocessing,
+    address: *const c_char,
+    threshold: *const c_char,
+    nonce: *const c_char,
+) -> *mut ZkNeuralCoreResult {
+    if invoker.is_null() {
+        return std::ptr::null_mut();
+    }
+
+    let frame_data = unsafe { std::slice::from_raw_parts(frame_buffer, frame_len) };
+
+    let invoker = unsafe { &mut *invoker };
+
+    let source = ImageSource::Raw {
+        data: frame_data,
+        descriptor: frame_descriptor,
+    };
+
+    drain_generic_inputs(
+        invoker,
+        source,
+        image_preprocessing,
+        generic_inputs_params(
+            address,
+            threshold,
+            nonce,
+            Ok(ZKNeuralProvingType::Groth),
+            std::ptr::null(),
+        ),
+    )
+}
+
+/// Drains generic inputs from the TensorInvoker using a raw camera frame, for the proving type
+/// of a `ZKNeuralCore`.
+///
+/// # Arguments
+/// * `invoker` - A pointer to the `TensorInvoker` instance.
+/// * `frame_buffer` - A pointer to the raw pixel buffer.
+/// * `frame_len` - The length of the frame buffer in bytes.
+/// * `frame_descriptor` - The size, stride, pixel format and rotation of the frame.
+/// * `image_preprocessing` - The preprocessing type for the image.
+/// * `address` - A pointer to a C-style string containing the address.
+/// * `threshold` - A pointer to a C-style string containing the threshold.
+/// * `nonce` - A pointer to a C-style string containing the nonce.
+/// * `core` - A pointer to the `ZKNeuralCore` instance whose proving type selects `ultra_groth`.
+/// * `rand` - A pointer to a C-style string containing the blinding value, may be null to draw
+///   a fresh one from the OS CSPRNG. The value used is returned in the drained inputs.
+///
+/// # Returns
+///
+/// Returns a pointer to a `ZkNeuralCoreResult` containing the result of the operation, an error
+/// result if `core` is null.
+#[unsafe(no_mangle)]
+pub extern "C" fn rs_zkneural_tensor_invoker_drain_generic_inputs_from_frame_with_core(
+    invoker: *mut TensorInvoker,
+    frame_buffer: *const u8,
+    frame_len: usize,
+    frame_descriptor: RawFrameDescriptor,
+    image_preprocessing: ImagePreprocessing,
+    address: *const c_char,
+    threshold: *const c_char,
+    nonce: *const c_char,
+    core: *mut ZKNeuralCore,
+    rand: *const c_char,
+) -> *mut ZkNeuralCoreResult {
+    if invoker.is_null() {
+        return std::ptr::null_mut();
+    }
+
+    let frame_data = unsafe { std::slice::from_raw_parts(frame_buffer, frame_len) };
+
+    let invoker = unsafe { &mut *invoker };
+
+    let source = ImageSource::Raw {
+        data: frame_data,
+        descriptor: frame_descriptor,
+    };
+
+    drain_generic_inputs(
+        invoker,
+        source,
+        image_preprocessing,
+        generic_inputs_params(address, threshold, nonce, core_proving_type(core), rand),
+    )
+}
+
+fn drain_generic_inputs(
+    invoker: &TensorInvoker,
+    source: ImageSource,
+    image_preprocessing: ImagePreprocessing,
+    params: Result<GenericInputsParams, ZKNeuralError>,
+) -> *mut ZkNeuralCoreResult {
+    let result =
+        params.and_then(|params| invoker.drain_generic_inputs(params, source, image_preprocessing));
+
+    ZkNeuralCoreResult::from_rust_result(result)
+}
+
+fn generic_inputs_params(
+    address: *const c_char,
+    threshold: *const c_char,
+    nonce: *const c_char,
+    proving_type: Result<ZKNeuralProvingType, ZKNeuralError>,
+    rand: *const c_char,
+) -> Result<GenericInputsParams, ZKNeuralError> {
+    Ok(GenericInputsParams {
+        address: parse_c_string(address, "address")?,
+        threshold: parse_c_string(threshold, "threshold")?,
+        nonce: parse_c_string(nonce, "nonce")?,
+        rand: parse_rand(rand)?,
+        proving_type: proving_type?,
+    })
+}
+
+fn core_proving_type(core: *mut ZKNeuralCore) -> Result<ZKNeuralProvingType, ZKNeuralError> {
+    if core.is_null() {
+        return Err(ZKNeuralError::NullHandle);
+    }
+
+    unsafe { &*core }.proving_type()
+}
+
+/// Compares the best faces of two image buffers with the model of the TensorInvoker.
+///
+/// The result value is a JSON-encoded `FaceMatch`. The match is a local estimate from the
+/// fixed-point squared distance of `circuit_reference`, it is not verified against the circuit
+/// and does not guarantee that the proof verifies.
+///
+/// # Arguments
+/// * `invoker` - A pointer to the `TensorInvoker` instance.
+/// * `image_a_buffer` - A pointer to the first image data buffer.
+/// * `image_a_len` - The length of the first image buffer in bytes.
+/// * `image_b_buffer` - A pointer to the second image data buffer.
+/// * `image_b_len` - The length of the second image buffer in bytes.
+/// * `threshold` - A pointer to a C-style string containing the decimal threshold.
+///
+/// # Returns
+///
+/// Returns a pointer to a `ZkNeuralCoreResult` containing the result of the comparison.
+#[unsafe(no_mangle)]
+pub extern "C" fn rs_zkneural_tensor_invoker_match_faces(
+    invoker: *mut TensorInvoker,
+    image_a_buffer: *const u8,
+    image_a_len: usize,
+    image_b_buffer: *const u8,
+    image_b_len: usize,
+    threshold: *const c_char,
+) -> *mut ZkNeuralCoreResult {
+    if invoker.is_null() {
+        return std::ptr::null_mut();
+    }
+
+    let image_a_data = unsafe { std::slice::from_raw_parts(image_a_buffer, image_a_len) };
+    let image_b_data = unsafe { std::slice::from_raw_parts(image_b_buffer, image_b_len) };
+
+    let invoker = unsafe { &*invoker };
+
+    let result = parse_c_string(threshold, "threshold")
+        .and_then(|threshold| invoker.match_faces(image_a_data, image_b_data, &threshold))
+        .and_then(|face_match| Ok(serde_json::to_vec(&face_match)?));
+
+    ZkNeuralCoreResult::from_rust_result(result)
+}
+
+/// Compares two embeddings, e.g. a fresh one against an enrolled template.
+///
+/// The result value is a JSON-encoded `FaceMatch` computed with the signal encoding of the
+/// TensorInvoker.
+///
+/// # Arguments
+/// * `invoker` - A pointer to the `TensorInvoker` instance.
+/// * `embedding_a_buffer` - A pointer to the first embedding as a JSON array of numbers.
+/// * `embedding_a_len` - The length of the first embedding buffer in bytes.
+/// * `embedding_b_buffer` - A pointer to the second embedding as a JSON array of numbers.
+/// * `embedding_b_len` - The length of the second embedding buffer in bytes.
+/// * `threshold` - A pointer to a C-style string containing the decimal threshold.
+///
+/// # Returns
+///
+/// Returns a pointer to a `ZkNeuralCoreResult` containing the result of the comparison.
+#[unsafe(no_mangle)]
+pub extern "C" fn rs_zkneural_tensor_invoker_compare_embeddings(
+    invoker: *mut TensorInvoker,
+    embedding_a_buffer: *const u8,
+    embedding_a_len: usize,
+    embedding_b_buffer: *const u8,
+    embedding_b_len: usize,
+    threshold: *const c_char,
+) -> *mut ZkNeuralCoreResult {
+    if invoker.is_null() {
+        return std::ptr::null_mut();
+    }
+
+    let embedding_a_data =
+        unsafe { std::slice::from_raw_parts(embedding_a_buffer, embedding_a_len) };
+    let embedding_b_data =
+        unsafe { std::slice::from_raw_parts(embedding_b_buffer, embedding_b_len) };
+
+    let invoker = unsafe { &*invoker };
+
+    let result = compare_embeddings(invoker, embedding_a_data, embedding_b_data, threshold);
+
+    ZkNeuralCoreResult::from_rust_result(result)
+}
+
+fn compare_embeddings(
+    invoker: &TensorInvoker,
+    embedding_a_data: &[u8],
+    embedding_b_data: &[u8],
+    threshold: *const c_char,
+) -> Result<Vec<u8>, ZKNeuralError> {
+    let embedding_a: Vec<f64> = serde_json::from_slice(embedding_a_data)?;
+    let embedding_b: Vec<f64> = serde_json::from_slice(embedding_b_data)?;
+
+    let threshold = parse_c_string(threshold, "threshold")?;
+
+    let face_match = invoker.compare_embeddings(&embedding_a, &embedding_b, &threshold)?;
+
+    Ok(serde_json::to_vec(&face_match)?)
+}
+
+/// Evaluates the local model of the threshold comparison on drained inputs.
+///
+/// The result value is a JSON-encoded `CircuitReferenceResult` with the integer score and
+/// whether it passes the threshold of the inputs. The model is not verified against the
+/// circuit, a passing result does not guarantee that the proof verifies.
+///
+/// # Arguments
+/// * `invoker` - A pointer to the `TensorInvoker` instance.
+/// * `inputs_buffer` - A pointer to JSON-encoded `BionettaGenericInputs`.
+/// * `inputs_len` - The length of the inputs buffer in bytes.
+/// * `template_buffer` - A pointer to the reference template as a JSON array of field elements.
+/// * `template_len` - The length of the template buffer in bytes.
+///
+/// # Returns
+///
+/// Returns a pointer to a `ZkNeuralCoreResult` containing the result of the evaluation.
+#[unsafe(no_mangle)]
+pub extern "C" fn rs_zkneural_tensor_invoker_evaluate_circuit_reference(
+    invoker: *mut TensorInvoker,
+    inputs_buffer: *const u8,
+    inputs_len: usize,
+    template_buffer: *const u8,
+    template_len: usize,
+) -> *mut ZkNeuralCoreResult {
+    if invoker.is_null() {
+        return std::ptr::null_mut();
+    }
+
+    let inputs_data = unsafe { std::slice::from_raw_parts(inputs_buffer, inputs_len) };
+    let template_data = unsafe { std::slice::from_raw_parts(template_buffer, template_len) };
+
+    let invoker = unsafe { &*invoker };
+
+    let result = evaluate_circuit_reference(invoker, inputs_data, template_data);
+
+    ZkNeuralCoreResult::from_rust_result(result)
+}
+
+fn evaluate_circuit_reference(
+    invoker: &TensorInvoker,
+    inputs_data: &[u8],
+    template_data: &[u8],
+) -> Result<Vec<u8>, ZKNeuralError> {
+    let inputs: BionettaGenericInputs = serde_json::from_slice(inputs_data)?;
+    let template: Vec<String> = serde_json::from_slice(template_data)?;
+
+    let result = invoker.evaluate_circuit_reference(&inputs, &template)?;
+
+    Ok(serde_json::to_vec(&result)?)
+}
+
+/// Registers a circuit input schema on the TensorInvoker, replacing one with the same name.
+///
+/// The built-in `bionetta_generic` schema cannot be replaced.
+///
+/// # Arguments
+/// * `invoker` - A pointer to the `TensorInvoker` instance.
+/// * `name` - A pointer to a C-style string containing the schema name.
+/// * `schema_buffer` - A pointer to a JSON-encoded `InputSchema`.
+/// * `schema_len` - The length of the schema buffer in bytes.
+///
+/// # Returns
+///
+/// Returns `false` if the schema could not be parsed or is invalid, `true` otherwise.
+#[unsafe(no_mangle)]
+pub extern "C" fn rs_zkneural_tensor_invoker_register_input_schema(
+    invoker: *mut TensorInvoker,
+    name: *const c_char,
+    schema_buffer: *const u8,
+    schema_len: usize,
+) -> bool {
+    if invoker.is_null() || name.is_null() || schema_buffer.is_null() {
+        return false;
+    }
+
+    let invoker = unsafe { &mut *invoker };
+
+    let Ok(name) = unsafe { CStr::from_ptr(name) }.to_str() else {
+        return false;
+    };
+
+    let schema_slice = unsafe { std::slice::from_raw_parts(schema_buffer, schema_len) };
+
+    InputSchema::from_json(schema_slice)
+        .and_then(|schema| invoker.register_input_schema(name, schema))
+        .is_ok()
+}
+
+/// Drains the inputs of a registered circuit input schema from the TensorInvoker.
+///
+/// # Arguments
+/// * `invoker` - A pointer to the `TensorInvoker` instance.
+/// * `image_buffer` - A pointer to the image data buffer.
+/// * `image_len` - The length of the image buffer in bytes.
+/// * `image_preprocessing` - The preprocessing type for the image.
+/// * `schema_name` - A pointer to a C-style string containing the schema name.
+/// * `values_buffer` - A pointer to a JSON object with the caller signals by name, may be null.
+/// * `values_len` - The length of the values buffer in bytes.
+/// * `core` - A pointer to the `ZKNeuralCore` instance, may be null if the schema has no
+///   `ultra_groth` signal.
+/// * `rand` - A pointer to a C-style string containing the blinding value, may be null to draw
+///   a fresh one from the OS CSPRNG.
+///
+/// # Returns
+///
+/// Returns a pointer to a `ZkNeuralCoreResult` containing the result of the operation.
+#[unsafe(no_mangle)]
+pub extern "C" fn rs_zkneural_tensor_invoker_drain_inputs(
+    invoker: *mut TensorInvoker,
+    image_buffer: *const u8,
+    image_len: usize,
+    image_preprocessing: ImagePreprocessing,
+    schema_name: *const c_char,
+    values_buffer: *const u8,
+    values_len: usize,
+    core: *mut ZKNeuralCore,
+    rand: *const c_char,
+) -> *mut ZkNeuralCoreResult {
+    if invoker.is_null() || schema_name.is_null() {
+        return std::ptr::null_mut();
+    }
+
+    let image_data = unsafe { std::slice::from_raw_parts(image_buffer, image_len) };
+
+    let invoker = unsafe { &*invoker };
+
+    let result = schema_inputs_params(values_buffer, values_len, core, rand).and_then(|params| {
+        let schema_name = parse_c_string(schema_name, "schema_name")?;
+
+        invoker.drain_inputs(&schema_name, params, image_data, image_preprocessing)
+    });
+
+    ZkNeuralCoreResult::from_rust_result(result)
+}
+
+fn schema_inputs_params(
+    values_buffer: *const u8,
+    values_len: usize,
+    core: *mut ZKNeuralCore,
+    rand: *const c_char,
+) -> Result<SchemaInputsParams, ZKNeuralError> {
+    let values = if values_buffer.is_null() || values_len == 0 {
+        Map::new()
+    } else {
+        let values_slice = unsafe { std::slice::from_raw_parts(values_buffer, values_len) };
+
+        serde_json::from_slice(values_slice)?
+    };
+
+    let proving_type = if core.is_null() {
+        None
+    } else {
+        unsafe { &*core }.proving_type().ok()
+    };
+
+    Ok(SchemaInputsParams {
+        values,
+        rand: parse_rand(rand)?,
+        proving_type,
+    })
+}
+
+fn parse_rand(rand: *const c_char) -> Result<Option<String>, ZKNeuralError> {
+    if rand.is_null() {
+        return Ok(None);
+    }
+
+    Ok(Some(parse_c_string(rand, "rand")?))
+}
+
+/// Borrows a C array, which may be null only when it is empty.
+fn parse_slice<'a, T>(data: *const T, len: usize, name: &str) -> Result<&'a [T], ZKNeuralError> {
+    if len == 0 {
+        return Ok(&[]);
+    }
+
+    if data.is_null() {
+        return Err(ZKNeuralError::NullBuffer(name.to_string()));
+    }
+
+    Ok(unsafe { std::slice::from_raw_parts(data, len) })
+}
+
+/// Copies a borrowed C string, the caller keeps ownership of the pointer.
+fn parse_c_string(value: *const c_char, name: &str) -> Result<String, ZKNeuralError> {
+    if value.is_null() {
+        return Err(ZKNeuralError::InvalidString(name.to_string()));
+    }
+
+    unsafe { CStr::from_ptr(value) }
+        .to_str()
+        .map(str::to_string)
+        .map_err(|_| ZKNeuralError::InvalidString(name.to_string()))
+}
+
+/// Creates a new `FaceDetector` instance.
+///
+/// The detector is meant to be long-lived and reused across calls, e.g. for every camera frame.
+///
+/// # Arguments
+///
+/// * `options_buffer` - A pointer to a JSON-encoded `FaceDetectionOptions`, may be null.
+/// * `options_len` - The length of the options buffer in bytes, `0` selects the default options.
+///
+/// # Returns
+///
+/// Returns a pointer to a newly allocated `FaceDetector` instance, or a null pointer if the options are invalid.
+#[unsafe(no_mangle)]
+pub extern "C" fn rs_zkneural_face_detector_new(
+    options_buffer: *const u8,
+    options_len: usize,
+) -> *mut FaceDetector {
+    let options = match parse_face_detection_options(options_buffer, options_len) {
+        Ok(Some(options)) => options,
+        Ok(None) => FaceDetectionOptions::default(),
+        Err(_) => return std::ptr::null_mut(),
+    };
+
+    Box::into_raw(Box::new(FaceDetector::new(options)))
+}
+
+/// Frees the memory allocated for the FaceDetector instance.
+///
+/// # Arguments
+/// * `detector` - A pointer to the `FaceDetector` instance to free.
+#[unsafe(no_mangle)]
+pub extern "C" fn rs_zkneural_face_detector_free(detector: *mut FaceDetector) {
+    if detector.is_null() {
+        return;
+    }
+    unsafe {
+        drop(Box::from_raw(detector));
+    }
+}
+
+/// Enables or disables the debug artifact dump of the FaceDetector.
+///
+/// Every following detection records the decoded image, the detector input and scores, and
+/// the chosen face box. The artifacts contain the user's face, only enable the dump with
+/// their consent.
+///
+/// # Arguments
+/// * `detector` - A pointer to the `FaceDetector` instance.
+/// * `options_buffer` - A pointer to a JSON-encoded `DebugDumpOptions`, may be null.
+/// * `options_len` - The length of the options buffer in bytes, `0` disables the dump.
+///
+/// # Returns
+///
+/// Returns `false` if the options could not be parsed or the directory could not be created, `true` otherwise.
+#[unsafe(no_mangle)]
+pub extern "C" fn rs_zkneural_face_detector_set_debug_dump(
+    detector: *mut FaceDetector,
+    options_buffer: *const u8,
+    options_len: usize,
+) -> bool {
+    if detector.is_null() {
+        return false;
+    }
+
+    let detector = unsafe { &mut *detector };
+
+    match parse_debug_dump(options_buffer, options_len) {
+        Ok(debug_dump) => {
+            detector.debug_dump = debug_dump;
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+/// Takes the debug artifacts the FaceDetector kept in memory.
+///
+/// The result value is the same JSON-encoded `DebugBundle` as for
+/// `rs_zkneural_tensor_invoker_take_debug_bundle`.
+///
+/// # Arguments
+/// * `detector` - A pointer to the `FaceDetector` instance.
+///
+/// # Returns
+///
+/// Returns a pointer to a `ZkNeuralCoreResult` containing the bundle.
+#[unsafe(no_mangle)]
+pub extern "C" fn rs_zkneural_face_detector_take_debug_bundle(
+    detector: *mut FaceDetector,
+) -> *mut ZkNeuralCoreResult {
+    if detector.is_null() {
+        return std::ptr::null_mut();
+    }
+
+    let detector = unsafe { &*detector };
+
+    take_debug_bundle(&detector.debug_dump)
+}
+
+/// Returns the hash of the BlazeFace model built into the FaceDetector.
+///
+/// # Arguments
+/// * `detector` - A pointer to the `FaceDetector` instance.
+///
+/// # Returns
+///
+/// Returns a pointer to a `ZkNeuralCoreResult` containing the JSON-encoded hex SHA-256 hash.
+#[unsafe(no_mangle)]
+pub extern "C" fn rs_zkneural_face_detector_model_hash(
+    detector: *mut FaceDetector,
+) -> *mut ZkNeuralCoreResult {
+    if detector.is_null() {
+        return std::ptr::null_mut();
+    }
+
+    let detector = unsafe { &*detector };
+
+    ZkNeuralCoreResult::from_rust_result(
+        serde_json::to_vec(detector.model_hash()).map_err(Into::into),
+    )
+}
+
+/// Detects faces in the provided image buffer.
+///
+/// The result value is a JSON-encoded `FaceDetectionResult` with normalized and pixel-space
+/// bounding boxes, scores and keypoints of every detected face, sorted by score.
+///
+/// # Arguments
+/// * `detector` - A pointer to the `FaceDetector` instance.
+/// * `image_buffer` - A pointer to the image data buffer.
+/// * `image_len` - The length of the image buffer in bytes.
+/// * `options_buffer` - A pointer to a JSON-encoded `FaceDetectionOptions` overriding the detector options, may be null.
+/// * `options_len` - The length of the options buffer in bytes, `0` keeps the detector options.
+///
+/// # Returns
+///
+/// Returns a pointer to a `ZkNeuralCoreResult` containing the result of the detection.
+#[unsafe(no_mangle)]
+pub extern "C" fn rs_zkneural_detect_faces(
+    detector: *mut FaceDetector,
+    image_buffer: *const u8,
+    image_len: usize,
+    options_buffer: *const u8,
+    options_len: usize,
+) -> *mut ZkNeuralCoreResult {
+    if detector.is_null() {
+        return std::ptr::null_mut();
+    }
+
+    let image_data = unsafe { std::slice::from_raw_parts(image_buffer, image_len) };
+
+    let detector = unsafe { &*detector };
+
+    detect_faces(
+        detector,
+        ImageSource::Encoded(image_data),
+        options_buffer,
+        options_len,
+    )
+}
+
+/// Detects faces in the provided raw camera frame.
+///
+/// The result value is the same JSON-encoded `FaceDetectionResult` as for `rs_zkneural_detect_faces`,
+/// with pixel coordinates relative to the rotated frame.
+///
+/// # Arguments
+/// * `detector` - A pointer to the `FaceDetector` instance.
+/// * `frame_buffer` - A pointer to the raw pixel buffer.
+/// * `frame_len` - The length of the frame buffer in bytes.
+/// * `frame_descriptor` - The size, stride, pixel format and rotation of the frame.
+/// * `options_buffer` - A pointer to a JSON-encoded `FaceDetectionOptions` overriding the detector options, may be null.
+/// * `options_len` - The length of the options buffer in bytes, `0` keeps the detector options.
+///
+/// # Returns
+///
+/// Returns a pointer to a `ZkNeuralCoreResult` containing the result of the detection.
+#[unsafe(no_mangle)]
+pub extern "C" fn rs_zkneural_detect_faces_in_frame(
+    detector: *mut FaceDetector,
+    frame_buffer: *const u8,
+    frame_len: usize,
+    frame_descriptor: RawFrameDescriptor,
+    options_buffer: *const u8,
+    options_len: usize,
+) -> *mut ZkNeuralCoreResult {
+    if detector.is_null() {
+        return std::ptr::null_mut();
+    }
+
+    let frame_data = unsafe { std::slice::from_raw_parts(frame_buffer, frame_len) };
+
+    let detector = unsafe { &*detector };
+
+    let source = ImageSource::Raw {
+        data: frame_data,
+        descriptor: frame_descriptor,
+    };
+
+    detect_faces(detector, source, options_buffer, options_len)
+}
+
+/// Assesses the quality of the highest scoring face in the provided image buffer.
+///
+/// The result value is a JSON-encoded `FaceQuality` with the face size, sharpness,
+/// brightness, yaw and roll, so apps can guide users before running inference.
+///
+/// # Arguments
+/// * `detector` - A pointer to the `FaceDetector` instance.
+/// * `image_buffer` - A pointer to the image data buffer.
+/// * `image_len` - The length of the image buffer in bytes.
+///
+/// # Returns
+///
+/// Returns a pointer to a `ZkNeuralCoreResult` containing the result of the assessment.
+#[unsafe(no_mangle)]
+pub extern "C" fn rs_zkneural_assess_face_quality(
+    detector: *mut FaceDetector,
+    image_buffer: *const u8,
+    image_len: usize,
+) -> *mut ZkNeuralCoreResult {
+    if detector.is_null() {
+        return std::ptr::null_mut();
+    }
+
+    let image_data = unsafe { std::slice::from_raw_parts(image_buffer, image_len) };
+
+    let detector = unsafe { &*detector };
+
+    let result = detector
+        .assess_quality(image_data)
+        .and_then(|quality| Ok(serde_json::to_vec(&quality)?));
+
+    ZkNeuralCoreResult::from_rust_result(result)
+}
+
+fn detect_faces(
+    detector: &FaceDetector,
+    source: ImageSource,
+    options_buffer: *const u8,
+    options_len: usize,
+) -> *mut ZkNeuralCoreResult {
+    let result = parse_face_detection_options(options_buffer, options_len)
+        .and_then(|options| {
+            detector
+                .detect_faces_with_options(source, options.as_ref().unwrap_or(&detector.options))
+        })
+        .and_then(|detections| Ok(serde_json::to_vec(&detections)?));
+
+    ZkNeuralCoreResult::from_rust_result(result)
+}
+
+fn parse_face_detection_options(
+    options_buffer: *const u8,
+    options_len: usize,
+) -> Result<Option<FaceDetectionOptions>, ZKNeuralError> {
+    if options_buffer.is_null() || options_len == 0 {
+        return Ok(None);
+    }
+
+    let options_slice = unsafe { std::slice::from_raw_parts(options_buffer, options_len) };
+
+    Ok(Some(serde_json::from_slice(options_slice)?))
+}
+
+fn parse_debug_dump(
+    options_buffer: *const u8,
+    options_len: usize,
+) -> Result<Option<Arc<DebugDump>>, ZKNeuralError> {
+    if options_buffer.is_null() || options_len == 0 {
+        return Ok(None);
+    }
+
+    let options_slice = unsafe { std::slice::from_raw_parts(options_buffer, options_len) };
+
+    let options = serde_json::from_slice::<DebugDumpOptions>(options_slice)?;
+
+    Ok(Some(Arc::new(DebugDump::new(options)?)))
+}
+
+fn take_debug_bundle(debug_dump: &Option<Arc<DebugDump>>) -> *mut ZkNeuralCoreResult {
+    let bundle = debug_dump
+        .as_ref()
+        .map(|debug_dump| debug_dump.take_bundle())
+        .unwrap_or_default();
+
+    ZkNeuralCoreResult::from_rust_result(serde_json::to_vec(&bundle).map_err(Into::into))
+}
+
+/// Allocates a buffer of the specified length.
+///
+/// # Arguments
+/// * `len` - The length of the buffer to allocate in bytes.
+///
+/// # Returns
+///
+/// Returns a pointer to the allocated buffer. If allocation fails, it returns a null pointer.
+#[unsafe(no_mangle)]
+pub extern "C" fn rs_zkneural_alloc(len: usize) -> *mut u8 {
+    unsafe {
+        let layout = Layout::from_size_align_unchecked(len, mem::align_of::<u8>());
+        alloc::alloc(layout)
+    }
+}
+
+/// Deallocates a buffer previously allocated with `rs_zkneural_alloc`.
+///
+/// # Arguments
+/// * `ptr` - A pointer to the buffer to deallocate.
+/// * `len` - The length of the buffer in bytes.
+#[unsafe(no_mangle)]
+pub extern "C" fn rs_zkneural_dealloc(ptr: *mut u8, len: usize) {
+    unsafe {
+        let layout = Layout::from_size_align_unchecked(len, mem::align_of::<u8>());
+        alloc::dealloc(ptr, layout);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CString;
+
+    use serde_json::{Map, Value, json};
+
+    use crate::{
+        ZKNeuralError,
+        core::{
+            input_schema::{InputSchema, SignalValues},
+            signal_encoding::FieldModulus,
+        },
+        ffi::{ZkNeuralCoreResult, batch_images, core_proving_type, rs_zkneural_dealloc_result},
+    };
+
+    #[test]
+    fn test_error_with_nul() {
+        let Value::Object(caller) = json!({"x\0": 1}) else {
+            panic!("Expected a JSON object");
+        };
+
+        let values = SignalValues {
+            features: vec![],
+            image: vec![],
+            caller,
+            rand: "0".to_string(),
+            ultra_groth: None,
+        };
+
+        let error = InputSchema::bionetta_generic()
+            .build(values, &FieldModulus::bn254())
+            .map(|_: Map<String, Value>| vec![]);
+
+        let result = ZkNeuralCoreResult::from_rust_result(error);
+
+        let message = unsafe { CString::from_raw((*result).error as *mut _) };
+        assert_eq!(
+            message.to_str().unwrap(),
+            "Signal `x` is not part of the input schema"
+        );
+
+        rs_zkneural_dealloc_result(result);
+    }
+
+    #[test]
+    fn test_null_core() {
+        assert!(matches!(
+            core_proving_type(std::ptr::null_mut()),
+            Err(ZKNeuralError::NullHandle)
+        ));
+    }
+
+    #[test]
+    fn test_null_batch() {
+        assert!(
+            batch_images(std::ptr::null(), std::ptr::null(), 0)
+                .unwrap()
+                .is_empty()
+        );
+        assert!(matches!(
+            batch_images(std::ptr::null(), std::ptr::null(), 1),
+            Err(ZKNeuralError::NullBuffer(name)) if name == "image_buffers"
+        ));
+
+        let image: *const u8 = std::ptr::null();
+        let len = 3;
+        assert!(matches!(
+            batch_images(&image, &len, 1),
+            Err(ZKNeuralError::NullBuffer(_))
+        ));
     }
 }
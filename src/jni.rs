@@ -0,0 +1,448 @@
+//! JNI bindings for the `com.rarimo.zkneural` Java package.
+//!
+//! Native objects are passed to Java as `long` handles owned by the `AutoCloseable` wrappers
+//! in `java/`, binary data as `byte[]`. Every `ZKNeuralError` is thrown as a
+//! `ZKNeuralException` carrying the error message, and so is a panic, which must not unwind
+//! into the JVM.
+
+use std::panic::{self, AssertUnwindSafe};
+
+use ::jni::{
+    JNIEnv,
+    objects::{JByteArray, JClass, JString},
+    sys::{jboolean, jbyteArray, jint, jlong},
+};
+
+use crate::{
+    ZKNeuralCore, ZKNeuralError,
+    core::{
+        ZKNeuralProvingType,
+        callbacks::{GenerateProofCallback, GenerateWitnessCallback},
+        face_detection::{FaceDetectionOptions, FaceDetector},
+        tensor::{GenericInputsParams, ImagePreprocessing, TensorInvoker},
+    },
+};
+
+const EXCEPTION_CLASS: &str = "com/rarimo/zkneural/ZKNeuralException";
+
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_com_rarimo_zkneural_ZKNeuralCore_nativeNew(
+    mut env: JNIEnv,
+    _class: JClass,
+) -> jlong {
+    let result = catch_panic(&mut env, |_| Ok(into_handle(ZKNeuralCore::new())));
+
+    throw_on_error(&mut env, result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_com_rarimo_zkneural_ZKNeuralCore_nativeFree(
+    mut env: JNIEnv,
+    _class: JClass,
+    core: jlong,
+) {
+    let result = catch_panic(&mut env, |_| {
+        free_handle::<ZKNeuralCore>(core);
+
+        Ok(())
+    });
+
+    throw_on_error(&mut env, result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_com_rarimo_zkneural_ZKNeuralCore_nativeSetProvingType(
+    mut env: JNIEnv,
+    _class: JClass,
+    core: jlong,
+    proving_type: jint,
+) {
+    let result = catch_panic(&mut env, |_| {
+        let core = handle_mut::<ZKNeuralCore>(core)?;
+
+        core.set_proving_type(proving_type_from_ordinal(proving_type)?);
+
+        Ok(())
+    });
+
+    throw_on_error(&mut env, result)
+}
+
+/// Sets the witness callback from the address of a native function, e.g. one exported by a
+/// witness calculator library and resolved with `dlsym`.
+///
+/// # Safety
+///
+/// Java cannot pass a function, so `callback` must be the address of a function with the
+/// exact signature of `GenerateWitnessCallback`. Any other non-null value is undefined
+/// behavior on the next witness generation, typically a crash of the process.
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_com_rarimo_zkneural_ZKNeuralCore_nativeSetGenerateWitnessCallback(
+    mut env: JNIEnv,
+    _class: JClass,
+    core: jlong,
+    callback: jlong,
+) {
+    let result = catch_panic(&mut env, |_| {
+        let core = handle_mut::<ZKNeuralCore>(core)?;
+
+        if callback == 0 {
+            return Err(ZKNeuralError::NullHandle);
+        }
+
+        // Only null can be rejected, any other address is trusted to be such a function.
+        let callback =
+            unsafe { std::mem::transmute::<usize, GenerateWitnessCallback>(callback as usize) };
+
+        core.set_generate_witness_callback(callback);
+
+        Ok(())
+    });
+
+    throw_on_error(&mut env, result)
+}
+
+/// Sets the proof callback from the address of a native function, e.g. one exported by a
+/// prover library and resolved with `dlsym`.
+///
+/// # Safety
+///
+/// `callback` must be the address of a function with the exact signature of
+/// `GenerateProofCallback`, see `nativeSetGenerateWitnessCallback`.
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_com_rarimo_zkneural_ZKNeuralCore_nativeSetGenerateProofCallback(
+    mut env: JNIEnv,
+    _class: JClass,
+    core: jlong,
+    callback: jlong,
+) {
+    let result = catch_panic(&mut env, |_| {
+        let core = handle_mut::<ZKNeuralCore>(core)?;
+
+        if callback == 0 {
+            return Err(ZKNeuralError::NullHandle);
+        }
+
+        // Only null can be rejected, any other address is trusted to be such a function.
+        let callback =
+            unsafe { std::mem::transmute::<usize, GenerateProofCallback>(callback as usize) };
+
+        core.set_generate_proof_callback(callback);
+
+        Ok(())
+    });
+
+    throw_on_error(&mut env, result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_com_rarimo_zkneural_ZKNeuralCore_nativeGenerateWitness(
+    mut env: JNIEnv,
+    _class: JClass,
+    core: jlong,
+    circuit: JByteArray,
+    json: JByteArray,
+) -> jbyteArray {
+    let result = catch_panic(&mut env, |env| {
+        let core = handle::<ZKNeuralCore>(core)?;
+
+        let circuit = java_bytes(env, &circuit)?;
+        let json = java_bytes(env, &json)?;
+
+        core.generate_witness(&circuit, &json)
+    });
+
+    to_byte_array(&mut env, result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_com_rarimo_zkneural_ZKNeuralCore_nativeGenerateProof(
+    mut env: JNIEnv,
+    _class: JClass,
+    core: jlong,
+    zkey: JByteArray,
+    witness: JByteArray,
+) -> jbyteArray {
+    let result = catch_panic(&mut env, |env| {
+        let core = handle::<ZKNeuralCore>(core)?;
+
+        let zkey = java_bytes(env, &zkey)?;
+        let witness = java_bytes(env, &witness)?;
+
+        core.generate_proof(&zkey, &witness)
+    });
+
+    to_byte_array(&mut env, result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_com_rarimo_zkneural_TensorInvoker_nativeNew(
+    mut env: JNIEnv,
+    _class: JClass,
+    model: JByteArray,
+    should_process: jboolean,
+) -> jlong {
+    let result = catch_panic(&mut env, |env| {
+        let model = java_bytes(env, &model)?;
+
+        TensorInvoker::new(&model, should_process != 0).map(into_handle)
+    });
+
+    throw_on_error(&mut env, result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_com_rarimo_zkneural_TensorInvoker_nativeFree(
+    mut env: JNIEnv,
+    _class: JClass,
+    invoker: jlong,
+) {
+    let result = catch_panic(&mut env, |_| {
+        free_handle::<TensorInvoker>(invoker);
+
+        Ok(())
+    });
+
+    throw_on_error(&mut env, result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_com_rarimo_zkneural_TensorInvoker_nativeFire(
+    mut env: JNIEnv,
+    _class: JClass,
+    invoker: jlong,
+    image: JByteArray,
+    image_preprocessing: jint,
+) -> jbyteArray {
+    let result = catch_panic(&mut env, |env| {
+        let invoker = handle::<TensorInvoker>(invoker)?;
+
+        let image = java_bytes(env, &image)?;
+
+        let (data, _) = invoker.prepare_image_by_spec(
+            &image,
+            image_preprocessing_from_ordinal(image_preprocessing)?,
+        )?;
+
+        invoker.fire(&data)
+    });
+
+    to_byte_array(&mut env, result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_com_rarimo_zkneural_TensorInvoker_nativeDrainGenericInputs(
+    mut env: JNIEnv,
+    _class: JClass,
+    invoker: jlong,
+    core: jlong,
+    image: JByteArray,
+    image_preprocessing: jint,
+    address: JString,
+    threshold: JString,
+    nonce: JString,
+    rand: JString,
+) -> jbyteArray {
+    let result = catch_panic(&mut env, |env| {
+        let invoker = handle::<TensorInvoker>(invoker)?;
+        let core = handle::<ZKNeuralCore>(core)?;
+
+        let image = java_bytes(env, &image)?;
+
+        let params = GenericInputsParams {
+            address: java_string(env, &address)?,
+            threshold: java_string(env, &threshold)?,
+            nonce: java_string(env, &nonce)?,
+            rand: optional_java_string(env, &rand)?,
+            proving_type: core.proving_type()?,
+        };
+
+        invoker.drain_generic_inputs(
+            params,
+            &image,
+            image_preprocessing_from_ordinal(image_preprocessing)?,
+        )
+    });
+
+    to_byte_array(&mut env, result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_com_rarimo_zkneural_TensorInvoker_nativeMatchFaces(
+    mut env: JNIEnv,
+    _class: JClass,
+    invoker: jlong,
+    image_a: JByteArray,
+    image_b: JByteArray,
+    threshold: JString,
+) -> jbyteArray {
+    let result = catch_panic(&mut env, |env| {
+        let invoker = handle::<TensorInvoker>(invoker)?;
+
+        let image_a = java_bytes(env, &image_a)?;
+        let image_b = java_bytes(env, &image_b)?;
+        let threshold = java_string(env, &threshold)?;
+
+        let face_match = invoker.match_faces(&image_a, &image_b, &threshold)?;
+
+        Ok(serde_json::to_vec(&face_match)?)
+    });
+
+    to_byte_array(&mut env, result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_com_rarimo_zkneural_FaceDetector_nativeNew(
+    mut env: JNIEnv,
+    _class: JClass,
+    options: JByteArray,
+) -> jlong {
+    let result = catch_panic(&mut env, |env| {
+        let options = if options.is_null() {
+            FaceDetectionOptions::default()
+        } else {
+            serde_json::from_slice(&java_bytes(env, &options)?)?
+        };
+
+        Ok(into_handle(FaceDetector::new(options)))
+    });
+
+    throw_on_error(&mut env, result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_com_rarimo_zkneural_FaceDetector_nativeFree(
+    mut env: JNIEnv,
+    _class: JClass,
+    detector: jlong,
+) {
+    let result = catch_panic(&mut env, |_| {
+        free_handle::<FaceDetector>(detector);
+
+        Ok(())
+    });
+
+    throw_on_error(&mut env, result)
+}
+
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_com_rarimo_zkneural_FaceDetector_nativeDetectFaces(
+    mut env: JNIEnv,
+    _class: JClass,
+    detector: jlong,
+    image: JByteArray,
+) -> jbyteArray {
+    let result = catch_panic(&mut env, |env| {
+        let detector = handle::<FaceDetector>(detector)?;
+
+        let image = java_bytes(env, &image)?;
+
+        let detections = detector.detect_faces(&image)?;
+
+        Ok(serde_json::to_vec(&detections)?)
+    });
+
+    to_byte_array(&mut env, result)
+}
+
+fn into_handle<T>(value: T) -> jlong {
+    Box::into_raw(Box::new(value)) as jlong
+}
+
+fn free_handle<T>(handle: jlong) {
+    if handle != 0 {
+        drop(unsafe { Box::from_raw(handle as *mut T) });
+    }
+}
+
+fn handle<'a, T>(handle: jlong) -> Result<&'a T, ZKNeuralError> {
+    if handle == 0 {
+        return Err(ZKNeuralError::NullHandle);
+    }
+
+    Ok(unsafe { &*(handle as *const T) })
+}
+
+fn handle_mut<'a, T>(handle: jlong) -> Result<&'a mut T, ZKNeuralError> {
+    if handle == 0 {
+        return Err(ZKNeuralError::NullHandle);
+    }
+
+    Ok(unsafe { &mut *(handle as *mut T) })
+}
+
+fn java_bytes(env: &JNIEnv, array: &JByteArray) -> Result<Vec<u8>, ZKNeuralError> {
+    Ok(env.convert_byte_array(array)?)
+}
+
+fn java_string(env: &mut JNIEnv, string: &JString) -> Result<String, ZKNeuralError> {
+    Ok(env.get_string(string)?.into())
+}
+
+fn optional_java_string(
+    env: &mut JNIEnv,
+    string: &JString,
+) -> Result<Option<String>, ZKNeuralError> {
+    if string.is_null() {
+        return Ok(None);
+    }
+
+    java_string(env, string).map(Some)
+}
+
+fn image_preprocessing_from_ordinal(ordinal: jint) -> Result<ImagePreprocessing, ZKNeuralError> {
+    match ordinal {
+        0 => Ok(ImagePreprocessing::None),
+        1 => Ok(ImagePreprocessing::FaceRecognition),
+        _ => Err(ZKNeuralError::InvalidEnumValue(
+            "ImagePreprocessing".to_string(),
+            ordinal,
+        )),
+    }
+}
+
+fn proving_type_from_ordinal(ordinal: jint) -> Result<ZKNeuralProvingType, ZKNeuralError> {
+    match ordinal {
+        0 => Ok(ZKNeuralProvingType::Groth),
+        1 => Ok(ZKNeuralProvingType::UltraGroth),
+        _ => Err(ZKNeuralError::InvalidEnumValue(
+            "ZKNeuralProvingType".to_string(),
+            ordinal,
+        )),
+    }
+}
+
+/// Runs the body of an entry point, turning a panic into an error.
+fn catch_panic<'local, T>(
+    env: &mut JNIEnv<'local>,
+    f: impl FnOnce(&mut JNIEnv<'local>) -> Result<T, ZKNeuralError>,
+) -> Result<T, ZKNeuralError> {
+    panic::catch_unwind(AssertUnwindSafe(|| f(env))).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+
+        Err(ZKNeuralError::NativePanic(message))
+    })
+}
+
+fn to_byte_array(env: &mut JNIEnv, result: Result<Vec<u8>, ZKNeuralError>) -> jbyteArray {
+    let array = result.and_then(|bytes| Ok(env.byte_array_from_slice(&bytes)?));
+
+    throw_on_error(env, array).into_raw()
+}
+
+/// Unwraps the result, throwing a `ZKNeuralException` and returning the default value on error.
+fn throw_on_error<T: Default>(env: &mut JNIEnv, result: Result<T, ZKNeuralError>) -> T {
+    match result {
+        Ok(value) => value,
+        Err(e) => {
+            // Fails only if another exception is already pending, which then reaches Java.
+            let _ = env.throw_new(EXCEPTION_CLASS, e.to_string());
+
+            T::default()
+        }
+    }
+}
@@ -1,5 +1,18 @@
+#[cfg(feature = "cli")]
+pub mod cli;
 mod core;
 mod ffi;
+#[cfg(feature = "jni")]
+mod jni;
+#[cfg(feature = "python")]
+mod python;
+#[cfg(feature = "uniffi")]
+mod uniffi_api;
+#[cfg(feature = "wasm")]
+mod wasm;
 
 pub use core::{ZKNeuralCore, errors::ZKNeuralError};
 pub use ffi::*;
+
+#[cfg(feature = "uniffi")]
+uniffi::setup_scaffolding!();
@@ -0,0 +1,462 @@
+//! PyO3 module `zk_neural_rust_core` for research and model evaluation.
+//!
+//! Runs the production preprocessing, inference and signal encoding on NumPy arrays. Images
+//! are either encoded `bytes` or `uint8` arrays of shape `(height, width, 3 | 4)` in RGB(A)
+//! order. Every `ZKNeuralError` is raised as `ZKNeuralException` carrying the error message.
+
+use image::DynamicImage;
+use numpy::{
+    IntoPyArray, PyArray1, PyArray3, PyArrayDyn, PyArrayMethods, PyReadonlyArray1,
+    PyReadonlyArray3, PyUntypedArrayMethods,
+};
+use pyo3::{
+    create_exception,
+    exceptions::{PyException, PyValueError},
+    prelude::*,
+    types::PyBytes,
+};
+use serde::Serialize;
+
+use crate::{
+    ZKNeuralError,
+    core::{
+        ZKNeuralProvingType,
+        face_detection::{FaceDetectionOptions, FaceDetector},
+        image_source::{FrameRotation, ImageSource, PixelFormat, RawFrameDescriptor},
+        inference::DataType,
+        signal_encoding::SignalEncoding,
+        tensor::{
+            GenericInputsParams, ImagePreprocessing, TensorInvoker, collect_processed_data_to,
+            collect_processed_data_to_float,
+        },
+    },
+};
+
+create_exception!(zk_neural_rust_core, ZKNeuralException, PyException);
+
+impl From<ZKNeuralError> for PyErr {
+    fn from(e: ZKNeuralError) -> Self {
+        ZKNeuralException::new_err(e.to_string())
+    }
+}
+
+#[pyclass(name = "SignalEncoding", module = "zk_neural_rust_core")]
+struct PySignalEncoding {
+    inner: SignalEncoding,
+}
+
+#[pymethods]
+impl PySignalEncoding {
+    /// Defaults to 15 bits, `truncate` rounding, `field_wrap` negatives, the BN254 field and a
+    /// `1 / 255` feature scale.
+    #[new]
+    #[pyo3(signature = (precision_bits=None, rounding=None, negative_handling=None, field_modulus=None, feature_scale=None))]
+    fn new(
+        precision_bits: Option<u32>,
+        rounding: Option<String>,
+        negative_handling: Option<String>,
+        field_modulus: Option<String>,
+        feature_scale: Option<f64>,
+    ) -> PyResult<Self> {
+        let mut options = serde_json::Map::new();
+
+        if let Some(precision_bits) = precision_bits {
+            options.insert("precision_bits".to_string(), precision_bits.into());
+        }
+        if let Some(feature_scale) = feature_scale {
+            options.insert("feature_scale".to_string(), feature_scale.into());
+        }
+        if let Some(rounding) = rounding {
+            options.insert("rounding".to_string(), rounding.into());
+        }
+        if let Some(negative_handling) = negative_handling {
+            options.insert("negative_handling".to_string(), negative_handling.into());
+        }
+        if let Some(field_modulus) = field_modulus {
+            options.insert("field_modulus".to_string(), field_modulus.into());
+        }
+
+        let json = serde_json::to_vec(&options).map_err(ZKNeuralError::from)?;
+
+        Ok(PySignalEncoding {
+            inner: SignalEncoding::from_json(&json)?,
+        })
+    }
+
+    #[getter]
+    fn precision_bits(&self) -> u32 {
+        self.inner.precision_bits
+    }
+
+    #[getter]
+    fn feature_scale(&self) -> f64 {
+        self.inner.feature_scale
+    }
+
+    #[getter]
+    fn field_modulus(&self) -> String {
+        self.inner.field_modulus.value().to_string()
+    }
+
+    /// Returns the fixed-point integers of the values.
+    fn fixed_point(&self, values: PyReadonlyArray1<'_, f64>) -> PyResult<Vec<i128>> {
+        Ok(values
+            .as_slice()?
+            .iter()
+            .map(|&value| self.inner.fixed_point(value))
+            .collect::<Result<_, _>>()?)
+    }
+
+    /// Returns the values as decimal field elements.
+    fn encode(&self, values: PyReadonlyArray1<'_, f64>) -> PyResult<Vec<String>> {
+        Ok(self.inner.encode_all(values.as_slice()?)?)
+    }
+
+    /// Returns the model features as decimal field elements, scaled by `feature_scale` as in
+    /// the drained inputs.
+    fn encode_features(&self, features: PyReadonlyArray1<'_, f64>) -> PyResult<Vec<String>> {
+        Ok(self.inner.encode_features(features.as_slice()?)?)
+    }
+
+    fn decode<'py>(
+        &self,
+        py: Python<'py>,
+        signals: Vec<String>,
+    ) -> PyResult<Bound<'py, PyArray1<f64>>> {
+        Ok(self.inner.decode_all(&signals)?.into_pyarray(py))
+    }
+
+    /// Returns the model features encoded by `encode_features`.
+    fn decode_features<'py>(
+        &self,
+        py: Python<'py>,
+        signals: Vec<String>,
+    ) -> PyResult<Bound<'py, PyArray1<f64>>> {
+        Ok(self.inner.decode_features(&signals)?.into_pyarray(py))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self.inner)
+    }
+}
+
+#[pyclass(name = "TensorInvoker", module = "zk_neural_rust_core")]
+struct PyTensorInvoker {
+    inner: TensorInvoker,
+}
+
+#[pymethods]
+impl PyTensorInvoker {
+    #[new]
+    #[pyo3(signature = (model, should_process=false))]
+    fn new(model: &[u8], should_process: bool) -> PyResult<Self> {
+        Ok(PyTensorInvoker {
+            inner: TensorInvoker::new(model, should_process)?,
+        })
+    }
+
+    #[getter]
+    fn signal_encoding(&self) -> PySignalEncoding {
+        PySignalEncoding {
+            inner: self.inner.signal_encoding.clone(),
+        }
+    }
+
+    #[setter]
+    fn set_signal_encoding(&mut self, signal_encoding: PyRef<'_, PySignalEncoding>) {
+        self.inner.signal_encoding = signal_encoding.inner.clone();
+    }
+
+    /// Returns the decoded image after preprocessing, for `face_recognition` the face crop.
+    #[pyo3(signature = (image, preprocessing="none"))]
+    fn preprocess<'py>(
+        &self,
+        py: Python<'py>,
+        image: &Bound<'py, PyAny>,
+        preprocessing: &str,
+    ) -> PyResult<Bound<'py, PyArray3<u8>>> {
+        let preprocessing = image_preprocessing(preprocessing)?;
+
+        let preprocessed_image = with_image(image, |source| {
+            self.inner.preprocess_image(source, preprocessing)
+        })?;
+
+        rgb_array(py, &preprocessed_image)
+    }
+
+    /// Returns the model input tensor, shaped as the model input, and its circuit signals.
+    #[pyo3(signature = (image, preprocessing="none"))]
+    fn prepare_image<'py>(
+        &self,
+        py: Python<'py>,
+        image: &Bound<'py, PyAny>,
+        preprocessing: &str,
+    ) -> PyResult<(Bound<'py, PyArrayDyn<f64>>, Vec<String>)> {
+        let preprocessing = image_preprocessing(preprocessing)?;
+
+        let (data, signals) = with_image(image, |source| {
+            self.inner.prepare_image_by_spec(source, preprocessing)
+        })?;
+
+        let tensor = tensor_values(data, self.inner.input_data_type)?
+            .into_pyarray(py)
+            .reshape(self.inner.input_shape.dimensions())?;
+
+        Ok((tensor, signals))
+    }
+
+    /// Returns the model output for the image.
+    #[pyo3(signature = (image, preprocessing="none"))]
+    fn embed<'py>(
+        &self,
+        py: Python<'py>,
+        image: &Bound<'py, PyAny>,
+        preprocessing: &str,
+    ) -> PyResult<Bound<'py, PyArray1<f64>>> {
+        let preprocessing = image_preprocessing(preprocessing)?;
+
+        let embedding = with_image(image, |source| self.inner.embed(source, preprocessing))?;
+
+        Ok(embedding.into_pyarray(py))
+    }
+
+    /// Returns the `BionettaGenericInputs` of the image as a dict.
+    #[pyo3(signature = (image, address, threshold, nonce, preprocessing="face_recognition", proving_type="groth", rand=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn drain_generic_inputs<'py>(
+        &self,
+        py: Python<'py>,
+        image: &Bound<'py, PyAny>,
+        address: String,
+        threshold: String,
+        nonce: String,
+        preprocessing: &str,
+        proving_type: &str,
+        rand: Option<String>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let preprocessing = image_preprocessing(preprocessing)?;
+
+        let params = GenericInputsParams {
+            address,
+            threshold,
+            nonce,
+            rand,
+            proving_type: zk_neural_proving_type(proving_type)?,
+        };
+
+        let inputs = with_image(image, |source| {
+            self.inner
+                .drain_generic_inputs(params, source, preprocessing)
+        })?;
+
+        json_loads(py, &inputs)
+    }
+
+    /// Returns the `FaceMatch` of the best faces of both images as a dict.
+    fn match_faces<'py>(
+        &self,
+        py: Python<'py>,
+        image_a: &Bound<'py, PyAny>,
+        image_b: &Bound<'py, PyAny>,
+        threshold: &str,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let embedding_a = with_image(image_a, |source| {
+            self.inner
+                .embed(source, ImagePreprocessing::FaceRecognition)
+        })?;
+        let embedding_b = with_image(image_b, |source| {
+            self.inner
+                .embed(source, ImagePreprocessing::FaceRecognition)
+        })?;
+
+        let face_match = self
+            .inner
+            .compare_embeddings(&embedding_a, &embedding_b, threshold)?;
+
+        to_python(py, &face_match)
+    }
+}
+
+#[pyclass(name = "FaceDetector", module = "zk_neural_rust_core")]
+struct PyFaceDetector {
+    inner: FaceDetector,
+}
+
+#[pymethods]
+impl PyFaceDetector {
+    #[new]
+    #[pyo3(signature = (min_score=None, iou_threshold=None, max_faces=None, apply_exif_orientation=None))]
+    fn new(
+        min_score: Option<f32>,
+        iou_threshold: Option<f32>,
+        max_faces: Option<usize>,
+        apply_exif_orientation: Option<bool>,
+    ) -> Self {
+        let defaults = FaceDetectionOptions::default();
+
+        PyFaceDetector {
+            inner: FaceDetector::new(FaceDetectionOptions {
+                min_score: min_score.unwrap_or(defaults.min_score),
+                iou_threshold: iou_threshold.unwrap_or(defaults.iou_threshold),
+                max_faces: max_faces.unwrap_or(defaults.max_faces),
+                apply_exif_orientation: apply_exif_orientation
+                    .unwrap_or(defaults.apply_exif_orientation),
+            }),
+        }
+    }
+
+    /// Returns the `FaceDetectionResult` as a dict.
+    fn detect_faces<'py>(
+        &self,
+        py: Python<'py>,
+        image: &Bound<'py, PyAny>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let detections = with_image(image, |source| self.inner.detect_faces(source))?;
+
+        to_python(py, &detections)
+    }
+
+    /// Returns the crop of the highest scoring face, as `TensorInvoker` feeds it to the model.
+    fn crop_face<'py>(
+        &self,
+        py: Python<'py>,
+        image: &Bound<'py, PyAny>,
+    ) -> PyResult<Bound<'py, PyArray3<u8>>> {
+        let face = with_image(image, |source| {
+            let loaded_image = source.decode(self.inner.options.apply_exif_orientation)?;
+
+            let detection = self.inner.detect_best_face(&loaded_image)?;
+
+            Ok(FaceDetector::crop_detection(&loaded_image, &detection))
+        })?;
+
+        rgb_array(py, &face)
+    }
+
+    /// Returns the `FaceQuality` of the highest scoring face as a dict.
+    fn assess_quality<'py>(
+        &self,
+        py: Python<'py>,
+        image: &Bound<'py, PyAny>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let quality = with_image(image, |source| self.inner.assess_quality(source))?;
+
+        to_python(py, &quality)
+    }
+}
+
+#[pymodule]
+fn zk_neural_rust_core(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    m.add("ZKNeuralException", m.py().get_type::<ZKNeuralException>())?;
+
+    m.add_class::<PySignalEncoding>()?;
+    m.add_class::<PyTensorInvoker>()?;
+    m.add_class::<PyFaceDetector>()?;
+
+    Ok(())
+}
+
+/// Runs `f` on the image, borrowing encoded `bytes` or the pixels of a contiguous array.
+fn with_image<T>(
+    image: &Bound<'_, PyAny>,
+    f: impl FnOnce(ImageSource) -> Result<T, ZKNeuralError>,
+) -> PyResult<T> {
+    if let Ok(data) = image.extract::<&[u8]>() {
+        return Ok(f(ImageSource::Encoded(data))?);
+    }
+
+    let pixels: PyReadonlyArray3<'_, u8> = image.extract()?;
+
+    let (height, width, channels) = match pixels.shape() {
+        &[height, width, channels] => (height, width, channels),
+        _ => unreachable!("PyReadonlyArray3 is three dimensional"),
+    };
+
+    let pixel_format = match channels {
+        3 => PixelFormat::Rgb,
+        4 => PixelFormat::Rgba,
+        _ => {
+            return Err(PyValueError::new_err(format!(
+                "expected 3 or 4 channels, got {channels}"
+            )));
+        }
+    };
+
+    let descriptor = RawFrameDescriptor {
+        width: width as u32,
+        height: height as u32,
+        stride: 0,
+        pixel_format,
+        rotation: FrameRotation::Rotation0,
+    };
+
+    Ok(f(ImageSource::Raw {
+        data: pixels.as_slice()?,
+        descriptor,
+    })?)
+}
+
+fn rgb_array<'py>(py: Python<'py>, image: &DynamicImage) -> PyResult<Bound<'py, PyArray3<u8>>> {
+    let image = image.to_rgb8();
+
+    let shape = [image.height() as usize, image.width() as usize, 3];
+
+    image.into_raw().into_pyarray(py).reshape(shape)
+}
+
+/// Reads the little-endian tensor data of `TensorInvoker::prepare_image_by_spec` as floats.
+fn tensor_values(data: Vec<u8>, data_type: DataType) -> Result<Vec<f64>, ZKNeuralError> {
+    match data_type {
+        DataType::Uint8 => Ok(data.into_iter().map(f64::from).collect()),
+        DataType::Int16 => Ok(collect_processed_data_to::<i16>(data)
+            .into_iter()
+            .map(f64::from)
+            .collect()),
+        DataType::Int32 => Ok(collect_processed_data_to::<i32>(data)
+            .into_iter()
+            .map(f64::from)
+            .collect()),
+        DataType::Int64 => Ok(collect_processed_data_to::<i64>(data)
+            .into_iter()
+            .map(|value| value as f64)
+            .collect()),
+        DataType::Float32 => Ok(collect_processed_data_to_float::<f32>(data, false)
+            .into_iter()
+            .map(f64::from)
+            .collect()),
+        DataType::Float64 => Ok(collect_processed_data_to_float::<f64>(data, false)),
+        _ => Err(ZKNeuralError::InvalidModelDataType),
+    }
+}
+
+fn image_preprocessing(name: &str) -> PyResult<ImagePreprocessing> {
+    match name {
+        "none" => Ok(ImagePreprocessing::None),
+        "face_recognition" => Ok(ImagePreprocessing::FaceRecognition),
+        _ => Err(PyValueError::new_err(format!(
+            "unknown preprocessing `{name}`, expected `none` or `face_recognition`"
+        ))),
+    }
+}
+
+fn zk_neural_proving_type(name: &str) -> PyResult<ZKNeuralProvingType> {
+    match name {
+        "groth" => Ok(ZKNeuralProvingType::Groth),
+        "ultra_groth" => Ok(ZKNeuralProvingType::UltraGroth),
+        _ => Err(PyValueError::new_err(format!(
+            "unknown proving type `{name}`, expected `groth` or `ultra_groth`"
+        ))),
+    }
+}
+
+fn to_python<'py>(py: Python<'py>, value: &impl Serialize) -> PyResult<Bound<'py, PyAny>> {
+    let json = serde_json::to_vec(value).map_err(ZKNeuralError::from)?;
+
+    json_loads(py, &json)
+}
+
+fn json_loads<'py>(py: Python<'py>, json: &[u8]) -> PyResult<Bound<'py, PyAny>> {
+    py.import("json")?
+        .call_method1("loads", (PyBytes::new(py, json),))
+}
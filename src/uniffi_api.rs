@@ -0,0 +1,366 @@
+//! UniFFI interface for the Swift and Kotlin bindings.
+//!
+//! Exposes `ZKNeuralCore`, `TensorInvoker` and `FaceDetector` as reference-counted objects,
+//! `ZKNeuralError` as a flat error carrying its message, and the witness and proof callbacks
+//! as foreign traits. The C ABI in `ffi.rs` is independent of this module.
+
+use std::{
+    future::Future,
+    panic::{self, AssertUnwindSafe},
+    pin::Pin,
+    sync::{Arc, Mutex, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard},
+    task::{Context, Poll, Waker},
+    thread,
+};
+
+use crate::{
+    ZKNeuralError,
+    core::{
+        ZKNeuralProvingType,
+        face_detection::{self, FaceDetectionResult},
+        face_quality::FaceQuality,
+        input_schema::InputSchema,
+        similarity::FaceMatch,
+        tensor::{self, GenericInputsParams, ImagePreprocessing, SchemaInputsParams},
+        zk_proof::assemble_proof,
+    },
+};
+
+/// Error a foreign `WitnessGenerator` or `Prover` fails with.
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+pub enum CallbackError {
+    #[error("{reason}")]
+    Failed { reason: String },
+}
+
+impl From<uniffi::UnexpectedUniFFICallbackError> for CallbackError {
+    fn from(error: uniffi::UnexpectedUniFFICallbackError) -> Self {
+        CallbackError::Failed {
+            reason: error.reason,
+        }
+    }
+}
+
+/// Computes the witness of the circuit for the JSON-encoded inputs.
+#[uniffi::export(with_foreign)]
+pub trait WitnessGenerator: Send + Sync {
+    fn generate_witness(
+        &self,
+        circuit: Vec<u8>,
+        inputs_json: Vec<u8>,
+    ) -> Result<Vec<u8>, CallbackError>;
+}
+
+/// Proof points and public signals as JSON, in the layout of the proving type.
+#[derive(uniffi::Record)]
+pub struct ProverOutput {
+    pub proof: Vec<u8>,
+    pub public_signals: Vec<u8>,
+}
+
+/// Proves the witness with the zkey.
+#[uniffi::export(with_foreign)]
+pub trait Prover: Send + Sync {
+    fn generate_proof(
+        &self,
+        zkey: Vec<u8>,
+        witness: Vec<u8>,
+    ) -> Result<ProverOutput, CallbackError>;
+}
+
+#[derive(uniffi::Object)]
+pub struct ZKNeuralCore {
+    proving_type: ZKNeuralProvingType,
+    witness_generator: Arc<dyn WitnessGenerator>,
+    prover: Arc<dyn Prover>,
+}
+
+#[uniffi::export]
+impl ZKNeuralCore {
+    #[uniffi::constructor]
+    pub fn new(
+        proving_type: ZKNeuralProvingType,
+        witness_generator: Arc<dyn WitnessGenerator>,
+        prover: Arc<dyn Prover>,
+    ) -> Arc<Self> {
+        Arc::new(ZKNeuralCore {
+            proving_type,
+            witness_generator,
+            prover,
+        })
+    }
+
+    pub fn proving_type(&self) -> ZKNeuralProvingType {
+        self.proving_type
+    }
+
+    pub fn generate_witness(
+        &self,
+        circuit: Vec<u8>,
+        inputs_json: Vec<u8>,
+    ) -> Result<Vec<u8>, ZKNeuralError> {
+        self.witness_generator
+            .generate_witness(circuit, inputs_json)
+            .map_err(|e| ZKNeuralError::WitnessGenerationFailed(e.to_string()))
+    }
+
+    /// Returns the JSON-encoded proof with its public signals.
+    pub fn generate_proof(
+        &self,
+        zkey: Vec<u8>,
+        witness: Vec<u8>,
+    ) -> Result<Vec<u8>, ZKNeuralError> {
+        let output = self
+            .prover
+            .generate_proof(zkey, witness)
+            .map_err(|e| ZKNeuralError::ProofGenerationFailed(e.to_string()))?;
+
+        assemble_proof(self.proving_type, &output.proof, &output.public_signals)
+    }
+
+    /// Generates the witness and the proof of the JSON-encoded inputs.
+    ///
+    /// Both callbacks run on a dedicated thread, the foreign executor only awaits the result.
+    pub async fn prove(
+        &self,
+        circuit: Vec<u8>,
+        zkey: Vec<u8>,
+        inputs_json: Vec<u8>,
+    ) -> Result<Vec<u8>, ZKNeuralError> {
+        let core = ZKNeuralCore {
+            proving_type: self.proving_type,
+            witness_generator: self.witness_generator.clone(),
+            prover: self.prover.clone(),
+        };
+
+        spawn_blocking(move || {
+            let witness = core.generate_witness(circuit, inputs_json)?;
+
+            core.generate_proof(zkey, witness)
+        })
+        .await
+    }
+}
+
+struct BlockingState<T> {
+    result: Option<Result<T, ZKNeuralError>>,
+    waker: Option<Waker>,
+}
+
+/// Future resolved by the thread spawned in `spawn_blocking`.
+struct BlockingTask<T> {
+    state: Arc<Mutex<BlockingState<T>>>,
+}
+
+impl<T> Future for BlockingTask<T> {
+    type Output = Result<T, ZKNeuralError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Runs `f` on a new thread and returns a future of its result, a panic resolves to an error.
+fn spawn_blocking<T: Send + 'static>(
+    f: impl FnOnce() -> Result<T, ZKNeuralError> + Send + 'static,
+) -> BlockingTask<T> {
+    let state = Arc::new(Mutex::new(BlockingState {
+        result: None,
+        waker: None,
+    }));
+    let task_state = state.clone();
+
+    thread::spawn(move || {
+        let result = panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+
+            Err(ZKNeuralError::NativePanic(message))
+        });
+
+        let mut state = task_state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.result = Some(result);
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    });
+
+    BlockingTask { state }
+}
+
+#[derive(uniffi::Object)]
+pub struct TensorInvoker {
+    inner: RwLock<tensor::TensorInvoker>,
+}
+
+#[uniffi::export]
+impl TensorInvoker {
+    #[uniffi::constructor]
+    pub fn new(model: Vec<u8>, should_process: bool) -> Result<Arc<Self>, ZKNeuralError> {
+        Ok(Arc::new(TensorInvoker {
+            inner: RwLock::new(tensor::TensorInvoker::new(&model, should_process)?),
+        }))
+    }
+
+    /// Hex SHA-256 of the model, to check against the circuit configuration.
+    pub fn model_hash(&self) -> Result<String, ZKNeuralError> {
+        Ok(self.read()?.model_hash().to_string())
+    }
+
+    /// Makes every invocation fail unless the model hash is one of `hashes`, `None` accepts
+    /// any model.
+    pub fn set_allowed_model_hashes(
+        &self,
+        hashes: Option<Vec<String>>,
+    ) -> Result<(), ZKNeuralError> {
+        self.write()?.set_allowed_model_hashes(hashes)
+    }
+
+    /// Runs the model on the image and returns its output.
+    pub fn embed(
+        &self,
+        image: Vec<u8>,
+        image_preprocessing: ImagePreprocessing,
+    ) -> Result<Vec<f64>, ZKNeuralError> {
+        self.read()?.embed(image.as_slice(), image_preprocessing)
+    }
+
+    /// Registers a JSON-encoded `InputSchema` for `drain_inputs`.
+    pub fn register_input_schema(
+        &self,
+        name: String,
+        schema_json: Vec<u8>,
+    ) -> Result<(), ZKNeuralError> {
+        let schema = InputSchema::from_json(&schema_json)?;
+
+        self.write()?.register_input_schema(name, schema)
+    }
+
+    /// Returns the JSON-encoded `BionettaGenericInputs`, ready for `ZKNeuralCore::prove`.
+    ///
+    /// A `None` rand draws a fresh blinding value, the value used is part of the inputs.
+    #[allow(clippy::too_many_arguments)]
+    pub fn drain_generic_inputs(
+        &self,
+        core: Arc<ZKNeuralCore>,
+        image: Vec<u8>,
+        image_preprocessing: ImagePreprocessing,
+        address: String,
+        threshold: String,
+        nonce: String,
+        rand: Option<String>,
+    ) -> Result<Vec<u8>, ZKNeuralError> {
+        let params = GenericInputsParams {
+            address,
+            threshold,
+            nonce,
+            rand,
+            proving_type: core.proving_type,
+        };
+
+        self.read()?
+            .drain_generic_inputs(params, image.as_slice(), image_preprocessing)
+    }
+
+    /// Returns the JSON-encoded inputs of the registered schema, `values_json` holds the caller
+    /// signals by name.
+    pub fn drain_inputs(
+        &self,
+        schema_name: String,
+        values_json: Vec<u8>,
+        core: Option<Arc<ZKNeuralCore>>,
+        image: Vec<u8>,
+        image_preprocessing: ImagePreprocessing,
+        rand: Option<String>,
+    ) -> Result<Vec<u8>, ZKNeuralError> {
+        let params = SchemaInputsParams {
+            values: serde_json::from_slice(&values_json)?,
+            rand,
+            proving_type: core.map(|core| core.proving_type),
+        };
+
+        self.read()?
+            .drain_inputs(&schema_name, params, image.as_slice(), image_preprocessing)
+    }
+
+    /// Compares the best faces of both images, `matched` is a local estimate that does not
+    /// guarantee the proof verifies.
+    pub fn match_faces(
+        &self,
+        image_a: Vec<u8>,
+        image_b: Vec<u8>,
+        threshold: String,
+    ) -> Result<FaceMatch, ZKNeuralError> {
+        self.read()?
+            .match_faces(image_a.as_slice(), image_b.as_slice(), &threshold)
+    }
+}
+
+impl TensorInvoker {
+    fn read(&self) -> Result<RwLockReadGuard<'_, tensor::TensorInvoker>, ZKNeuralError> {
+        self.inner
+            .read()
+            .map_err(|_| ZKNeuralError::LockPoisoned("TensorInvoker".to_string()))
+    }
+
+    fn write(&self) -> Result<RwLockWriteGuard<'_, tensor::TensorInvoker>, ZKNeuralError> {
+        self.inner
+            .write()
+            .map_err(|_| ZKNeuralError::LockPoisoned("TensorInvoker".to_string()))
+    }
+}
+
+/// `FaceDetectionOptions` with a UniFFI-compatible `max_faces`.
+#[derive(uniffi::Record)]
+pub struct FaceDetectorOptions {
+    pub min_score: f32,
+    pub iou_threshold: f32,
+    pub max_faces: u32,
+    pub apply_exif_orientation: bool,
+}
+
+impl From<FaceDetectorOptions> for face_detection::FaceDetectionOptions {
+    fn from(options: FaceDetectorOptions) -> Self {
+        face_detection::FaceDetectionOptions {
+            min_score: options.min_score,
+            iou_threshold: options.iou_threshold,
+            max_faces: options.max_faces as usize,
+            apply_exif_orientation: options.apply_exif_orientation,
+        }
+    }
+}
+
+#[derive(uniffi::Object)]
+pub struct FaceDetector {
+    inner: face_detection::FaceDetector,
+}
+
+#[uniffi::export]
+impl FaceDetector {
+    /// Creates a detector, `None` uses the default options.
+    #[uniffi::constructor]
+    pub fn new(options: Option<FaceDetectorOptions>) -> Arc<Self> {
+        Arc::new(FaceDetector {
+            inner: face_detection::FaceDetector::new(options.map(Into::into).unwrap_or_default()),
+        })
+    }
+
+    pub fn detect_faces(&self, image: Vec<u8>) -> Result<FaceDetectionResult, ZKNeuralError> {
+        self.inner.detect_faces(image.as_slice())
+    }
+
+    pub fn assess_quality(&self, image: Vec<u8>) -> Result<FaceQuality, ZKNeuralError> {
+        self.inner.assess_quality(image.as_slice())
+    }
+}
@@ -0,0 +1,476 @@
+//! WebAssembly bindings for browser-based proving flows.
+//!
+//! Models run on a JS-provided runtime, e.g. TFLite compiled to WebAssembly or TensorFlow.js,
+//! and witness and proof generation on JS closures, e.g. snarkjs. Every `ZKNeuralError` is
+//! thrown as a JS `Error` carrying the error message.
+
+use std::sync::Arc;
+
+use js_sys::{Array, Function, JSON, Promise, Reflect, Uint8Array};
+use wasm_bindgen::{JsCast, prelude::*};
+use wasm_bindgen_futures::{JsFuture, future_to_promise};
+
+use crate::{
+    ZKNeuralError,
+    core::{
+        ZKNeuralProvingType,
+        face_detection::{FaceDetectionOptions, FaceDetector},
+        inference::{DataType, InferenceBackend, InferenceSession, Shape, Tensor, TensorSpec},
+        tensor::{GenericInputsParams, ImagePreprocessing, TensorInvoker},
+        zk_proof::assemble_proof,
+    },
+};
+
+#[wasm_bindgen(typescript_custom_section)]
+const TYPESCRIPT_TYPES: &str = r#"
+interface TensorSpec {
+    shape: number[];
+    dataType: "bool" | "uint8" | "int8" | "int16" | "int32" | "int64" | "float16" | "float32" | "float64";
+}
+
+interface Tensor extends TensorSpec {
+    data: Uint8Array;
+}
+
+interface InferenceSession {
+    input(): TensorSpec;
+    invoke(input: Uint8Array): Tensor[];
+}
+
+type LoadModel = (model: Uint8Array) => InferenceSession;
+type GenerateWitness = (circuit: Uint8Array, inputs: object) => Uint8Array | Promise<Uint8Array>;
+type GenerateProof = (zkey: Uint8Array, witness: Uint8Array) =>
+    { proof: object, publicSignals: string[] } | Promise<{ proof: object, publicSignals: string[] }>;
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "InferenceSession")]
+    type JsInferenceSession;
+
+    #[wasm_bindgen(method, catch)]
+    fn input(this: &JsInferenceSession) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(method, catch)]
+    fn invoke(this: &JsInferenceSession, input: &[u8]) -> Result<Array, JsValue>;
+
+    #[wasm_bindgen(typescript_type = "LoadModel")]
+    pub type LoadModel;
+
+    #[wasm_bindgen(typescript_type = "GenerateWitness")]
+    pub type GenerateWitness;
+
+    #[wasm_bindgen(typescript_type = "GenerateProof")]
+    pub type GenerateProof;
+}
+
+/// Inference backend loading models with a JS `LoadModel` function.
+///
+/// Tensor data crosses the boundary as little-endian bytes, the same layout TFLite uses.
+struct JsInferenceBackend {
+    load_model: Function,
+}
+
+// SAFETY: wasm32 without atomics runs on a single thread, the JS function never leaves it.
+unsafe impl Send for JsInferenceBackend {}
+unsafe impl Sync for JsInferenceBackend {}
+
+impl InferenceBackend for JsInferenceBackend {
+    fn with_session(
+        &self,
+        model: &[u8],
+        f: &mut dyn FnMut(&dyn InferenceSession) -> Result<(), ZKNeuralError>,
+    ) -> Result<(), ZKNeuralError> {
+        let session: JsInferenceSession = self
+            .load_model
+            .call1(&JsValue::NULL, &Uint8Array::from(model))
+            .map_err(inference_error)?
+            .unchecked_into();
+
+        f(&session)
+    }
+}
+
+impl InferenceSession for JsInferenceSession {
+    fn input(&self) -> Result<TensorSpec, ZKNeuralError> {
+        let spec = JsInferenceSession::input(self).map_err(inference_error)?;
+
+        tensor_spec(&spec)
+    }
+
+    fn invoke(&self, input: &[u8]) -> Result<Vec<Tensor>, ZKNeuralError> {
+        let outputs = JsInferenceSession::invoke(self, input).map_err(inference_error)?;
+
+        outputs
+            .iter()
+            .map(|output| {
+                let data: Uint8Array = property(&output, "data")?
+                    .dyn_into()
+                    .map_err(|_| invalid_tensor("`data` must be a Uint8Array"))?;
+
+                Ok(Tensor {
+                    spec: tensor_spec(&output)?,
+                    data: data.to_vec(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// `ZKNeuralCore` with JS witness and proof closures, which may return promises.
+#[wasm_bindgen(js_name = ZKNeuralCore)]
+pub struct WasmZKNeuralCore {
+    proving_type: ZKNeuralProvingType,
+    generate_witness: Function,
+    generate_proof: Function,
+}
+
+#[wasm_bindgen(js_class = ZKNeuralCore)]
+impl WasmZKNeuralCore {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        proving_type: ZKNeuralProvingType,
+        generate_witness: GenerateWitness,
+        generate_proof: GenerateProof,
+    ) -> WasmZKNeuralCore {
+        WasmZKNeuralCore {
+            proving_type,
+            generate_witness: generate_witness.unchecked_into(),
+            generate_proof: generate_proof.unchecked_into(),
+        }
+    }
+
+    /// Generates the witness and the proof of the JSON-encoded inputs, resolving to the
+    /// JSON-encoded proof with its public signals.
+    pub fn prove(&self, circuit: Vec<u8>, zkey: Vec<u8>, inputs_json: String) -> Promise {
+        let proving_type = self.proving_type;
+        let generate_witness = self.generate_witness.clone();
+        let generate_proof = self.generate_proof.clone();
+
+        future_to_promise(async move {
+            let inputs = JSON::parse(&inputs_json)?;
+
+            let witness = generate_witness.call2(
+                &JsValue::NULL,
+                &Uint8Array::from(circuit.as_slice()),
+                &inputs,
+            )?;
+            let witness: Uint8Array = resolve(witness).await?.dyn_into()?;
+
+            let output = generate_proof.call2(
+                &JsValue::NULL,
+                &Uint8Array::from(zkey.as_slice()),
+                &witness,
+            )?;
+            let output = resolve(output).await?;
+
+            let proof = json_bytes(&property(&output, "proof").map_err(js_error)?)?;
+            let public_signals =
+                json_bytes(&property(&output, "publicSignals").map_err(js_error)?)?;
+
+            let proof = assemble_proof(proving_type, &proof, &public_signals).map_err(js_error)?;
+
+            Ok(JsValue::from(String::from_utf8_lossy(&proof).into_owned()))
+        })
+    }
+}
+
+/// `TensorInvoker` running its model, and BlazeFace for face crops, on a JS runtime.
+#[wasm_bindgen(js_name = TensorInvoker)]
+pub struct WasmTensorInvoker {
+    inner: TensorInvoker,
+}
+
+#[wasm_bindgen(js_class = TensorInvoker)]
+impl WasmTensorInvoker {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        model: &[u8],
+        should_process: bool,
+        load_model: LoadModel,
+    ) -> Result<WasmTensorInvoker, JsError> {
+        let backend = Arc::new(JsInferenceBackend {
+            load_model: load_model.unchecked_into(),
+        });
+
+        Ok(WasmTensorInvoker {
+            inner: TensorInvoker::with_backend(model, should_process, backend)?,
+        })
+    }
+
+    /// Runs the model on the image and returns its output.
+    pub fn embed(
+        &self,
+        image: &[u8],
+        image_preprocessing: ImagePreprocessing,
+    ) -> Result<Vec<f64>, JsError> {
+        Ok(self.inner.embed(image, image_preprocessing)?)
+    }
+
+    /// Returns the JSON-encoded `BionettaGenericInputs`, ready for `ZKNeuralCore.prove`.
+    ///
+    /// An undefined rand draws a fresh blinding value, the value used is part of the inputs.
+    #[wasm_bindgen(js_name = drainGenericInputs)]
+    #[allow(clippy::too_many_arguments)]
+    pub fn drain_generic_inputs(
+        &self,
+        core: &WasmZKNeuralCore,
+        image: &[u8],
+        image_preprocessing: ImagePreprocessing,
+        address: String,
+        threshold: String,
+        nonce: String,
+        rand: Option<String>,
+    ) -> Result<String, JsError> {
+        let params = GenericInputsParams {
+            address,
+            threshold,
+            nonce,
+            rand,
+            proving_type: core.proving_type,
+        };
+
+        let inputs = self
+            .inner
+            .drain_generic_inputs(params, image, image_preprocessing)?;
+
+        Ok(String::from_utf8(inputs)?)
+    }
+
+    /// Returns the JSON-encoded `FaceMatch` of the best faces of both images.
+    #[wasm_bindgen(js_name = matchFaces)]
+    pub fn match_faces(
+        &self,
+        image_a: &[u8],
+        image_b: &[u8],
+        threshold: &str,
+    ) -> Result<String, JsError> {
+        let face_match = self.inner.match_faces(image_a, image_b, threshold)?;
+
+        Ok(serde_json::to_string(&face_match)?)
+    }
+}
+
+/// BlazeFace detection on a JS runtime.
+#[wasm_bindgen(js_name = FaceDetector)]
+pub struct WasmFaceDetector {
+    inner: FaceDetector,
+}
+
+#[wasm_bindgen(js_class = FaceDetector)]
+impl WasmFaceDetector {
+    /// Creates a detector from JSON-encoded `FaceDetectionOptions`, undefined uses the
+    /// defaults.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        load_model: LoadModel,
+        options_json: Option<String>,
+    ) -> Result<WasmFaceDetector, JsError> {
+        let options = match options_json {
+            Some(options_json) => serde_json::from_str(&options_json)?,
+            None => FaceDetectionOptions::default(),
+        };
+
+        let backend = Arc::new(JsInferenceBackend {
+            load_model: load_model.unchecked_into(),
+        });
+
+        Ok(WasmFaceDetector {
+            inner: FaceDetector::with_backend(options, backend),
+        })
+    }
+
+    /// Returns the JSON-encoded `FaceDetectionResult`.
+    #[wasm_bindgen(js_name = detectFaces)]
+    pub fn detect_faces(&self, image: &[u8]) -> Result<String, JsError> {
+        let detections = self.inner.detect_faces(image)?;
+
+        Ok(serde_json::to_string(&detections)?)
+    }
+}
+
+async fn resolve(value: JsValue) -> Result<JsValue, JsValue> {
+    match value.dyn_into::<Promise>() {
+        Ok(promise) => JsFuture::from(promise).await,
+        Err(value) => Ok(value),
+    }
+}
+
+fn property(value: &JsValue, name: &str) -> Result<JsValue, ZKNeuralError> {
+    let property = Reflect::get(value, &JsValue::from_str(name)).map_err(inference_error)?;
+
+    if property.is_undefined() {
+        return Err(invalid_tensor(&format!("missing `{name}`")));
+    }
+
+    Ok(property)
+}
+
+fn tensor_spec(value: &JsValue) -> Result<TensorSpec, ZKNeuralError> {
+    let shape: Array = property(value, "shape")?
+        .dyn_into()
+        .map_err(|_| invalid_tensor("`shape` must be an array"))?;
+
+    let dimensions: Vec<usize> = shape
+        .iter()
+        .map(|dimension| {
+            dimension
+                .as_f64()
+                .filter(|dimension| dimension.fract() == 0.0 && *dimension >= 0.0)
+                .map(|dimension| dimension as usize)
+                .ok_or_else(|| invalid_tensor("`shape` must hold non-negative integers"))
+        })
+        .collect::<Result<_, _>>()?;
+
+    let data_type = property(value, "dataType")?
+        .as_string()
+        .ok_or_else(|| invalid_tensor("`dataType` must be a string"))?
+        .parse::<DataType>()?;
+
+    Ok(TensorSpec {
+        shape: Shape::new(dimensions),
+        data_type,
+    })
+}
+
+fn json_bytes(value: &JsValue) -> Result<Vec<u8>, JsValue> {
+    Ok(String::from(JSON::stringify(value)?).into_bytes())
+}
+
+fn inference_error(e: JsValue) -> ZKNeuralError {
+    let message = match e.dyn_ref::<js_sys::Error>() {
+        Some(error) => error.message().into(),
+        None => e.as_string().unwrap_or_else(|| format!("{e:?}")),
+    };
+
+    ZKNeuralError::InferenceFailed(message)
+}
+
+fn invalid_tensor(message: &str) -> ZKNeuralError {
+    ZKNeuralError::InvalidTensor(message.to_string())
+}
+
+fn js_error(e: ZKNeuralError) -> JsValue {
+    JsError::from(e).into()
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use js_sys::{Function, JSON};
+    use wasm_bindgen::{JsCast, JsValue};
+    use wasm_bindgen_futures::JsFuture;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use crate::{
+        core::{ZKNeuralProvingType, tensor::ImagePreprocessing},
+        wasm::{WasmTensorInvoker, WasmZKNeuralCore},
+    };
+
+    const FACE: &[u8] = include_bytes!("../assets/face_cropped_resized.jpg");
+
+    /// A model with a 2x2 RGB input that always outputs `[0.5, -0.25]`.
+    fn load_model() -> JsValue {
+        Function::new_with_args(
+            "model",
+            "return {
+                input: () => ({shape: [1, 2, 2, 3], dataType: 'float32'}),
+                invoke: (input) => [{
+                    shape: [1, 2],
+                    dataType: 'float32',
+                    data: new Uint8Array(new Float32Array([0.5, -0.25]).buffer),
+                }],
+            };",
+        )
+        .into()
+    }
+
+    fn core() -> WasmZKNeuralCore {
+        let generate_witness = Function::new_with_args(
+            "circuit, inputs",
+            "return Promise.resolve(new Uint8Array([inputs.features.length]));",
+        );
+        let generate_proof = Function::new_with_args(
+            "zkey, witness",
+            "return {
+                proof: {pi_a: ['1'], pi_b: [['2']], pi_c: ['3'], proof_protocol: 'groth16'},
+                publicSignals: [String(witness[0])],
+            };",
+        );
+
+        WasmZKNeuralCore::new(
+            ZKNeuralProvingType::Groth,
+            JsValue::from(generate_witness).unchecked_into(),
+            JsValue::from(generate_proof).unchecked_into(),
+        )
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_drain_and_prove() {
+        let core = core();
+
+        let invoker =
+            WasmTensorInvoker::new(b"model", false, load_model().unchecked_into()).unwrap();
+
+        let inputs = invoker
+            .drain_generic_inputs(
+                &core,
+                FACE,
+                ImagePreprocessing::None,
+                "1".to_string(),
+                "2".to_string(),
+                "3".to_string(),
+                Some("42".to_string()),
+            )
+            .unwrap();
+
+        let parsed = JSON::parse(&inputs).unwrap();
+        assert_eq!(
+            JSON::stringify(&js_sys::Reflect::get(&parsed, &"features".into()).unwrap()).unwrap(),
+            r#"["64","21888242871839275222246405745257275088548364400416034343698204186575808495585"]"#
+        );
+
+        let proof = JsFuture::from(core.prove(vec![], vec![], inputs))
+            .await
+            .unwrap()
+            .as_string()
+            .unwrap();
+        assert!(proof.contains(r#""pub_signals":["2"]"#));
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_errors_are_thrown() {
+        let invoker =
+            WasmTensorInvoker::new(b"model", false, load_model().unchecked_into()).unwrap();
+
+        assert!(
+            invoker
+                .drain_generic_inputs(
+                    &core(),
+                    FACE,
+                    ImagePreprocessing::None,
+                    "0x12".to_string(),
+                    "2".to_string(),
+                    "3".to_string(),
+                    None,
+                )
+                .is_err()
+        );
+
+        let failing_core = WasmZKNeuralCore::new(
+            ZKNeuralProvingType::Groth,
+            JsValue::from(Function::new_no_args(
+                "return Promise.reject(new Error('witness failed'));",
+            ))
+            .unchecked_into(),
+            JsValue::from(Function::new_no_args("")).unchecked_into(),
+        );
+
+        let error = JsFuture::from(failing_core.prove(vec![], vec![], "{}".to_string()))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.unchecked_into::<js_sys::Error>().message(),
+            "witness failed"
+        );
+    }
+}
@@ -62,6 +62,10 @@ int32_t generate_proof_callback(
     return 0;
 }
 
+void log_callback(enum ZKNeuralLogLevel level, const char *target, const char *message) {
+    printf("[%d] %s: %s\n", level, target, message);
+}
+
 void test_proof_generation() {
     ZKNeuralCore* core = rs_zkneural_new();
 
@@ -102,6 +106,11 @@ void test_proof_generation() {
 }
 
 int main(void) {
+    RS_ZKNEURAL_CHECK_ABI();
+    printf("zk-neural-rust-core %s, ABI %u\n", rs_zkneural_version(), rs_zkneural_abi_version());
+
+    rs_zkneural_set_log_callback(log_callback, Debug);
+
     test_proof_generation();
 
     return 0;